@@ -1,39 +1,1204 @@
-use reqwest::{Client, Response};
-use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, AUTHORIZATION};
-use std::time::Duration;
+use reqwest::{Client, Proxy, Response, StatusCode};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, AUTHORIZATION, RETRY_AFTER, USER_AGENT};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::body::read_json;
+use crate::coalesce::{follow, is_next_page, Flight, PageCoalescer};
+use crate::gate::RetryAfterGate;
+use crate::limiter::{InFlight, RateLimiter};
+use crate::budget::{CostTable, CreditBudget};
+use crate::errors::{RequestContext, RocketAPIError};
+use crate::helpers::{schema_fingerprint, CURSOR_FIELDS};
+use crate::models::{AccountInfo, Balance};
+use crate::retry::{is_transient_error, AttemptRecord, RetryPolicy};
+use crate::runtime;
+use crate::trace;
+use crate::stats::{EndpointStats, StatsStore};
+use crate::middleware::{Next, RequestInterceptor};
+use crate::transport::Transport;
 
+pub const DEFAULT_BASE_URL: &str = "https://v1.rocketapi.io/";
+
+// `User-Agent` of every request, an application suffix can be appended with `set_user_agent_suffix`.
+const USER_AGENT_PREFIX: &str = concat!("rocketapi-rust/", env!("CARGO_PKG_VERSION"));
+
+// Scheme of the `Authorization` header, some gateways expect `Bearer` instead.
+const DEFAULT_AUTH_SCHEME: &str = "Token";
+
+// Free endpoint used to probe the token and read the quota headers.
+const ACCOUNT_PROBE_METHOD: &str = "instagram/media/get_shortcode_by_id";
+const ACCOUNT_PROBE_MEDIA_ID: u64 = 1;
+
+// Envelope `status` values accepted in strict mode.
+const KNOWN_STATUSES: [&str; 2] = ["done", "error"];
+
+fn invalid(pointer: &str, expected: &str, response: &Value) -> RocketAPIError {
+    RocketAPIError::InvalidResponse {
+        pointer: pointer.to_string(),
+        expected: expected.to_string(),
+        response: response.clone(),
+    }
+}
+
+// Upstream (Instagram) error signals found in `message` / `error_type` of the body.
+const CHALLENGE_SIGNALS: [&str; 3] = ["challenge_required", "checkpoint_required", "checkpoint_challenge_required"];
+const LOGIN_SIGNALS: [&str; 1] = ["login_required"];
+const FEEDBACK_SIGNALS: [&str; 2] = ["feedback_required", "sentry_block"];
+
+fn is_feedback_required(body: &Value) -> bool {
+    // Action blocks carry `feedback_title` / `feedback_message` and often `spam: true` next to the message.
+    body["spam"] == true || body["feedback_title"].is_string() || body["feedback_message"].is_string()
+}
+
+fn upstream_error(response: Value) -> Result<Value, RocketAPIError> {
+    let body = &response["response"]["body"];
+    let signals = [&body["message"], &body["error_type"]];
+    let has = |known: &[&str]| signals.iter().filter_map(|s| s.as_str()).any(|s| known.contains(&s));
+    if has(&CHALLENGE_SIGNALS) {
+        Err(RocketAPIError::ChallengeRequired(response))
+    } else if has(&LOGIN_SIGNALS) {
+        Err(RocketAPIError::LoginRequired(response))
+    } else if has(&FEEDBACK_SIGNALS) || is_feedback_required(body) {
+        Err(RocketAPIError::FeedbackRequired(response))
+    } else {
+        Ok(response)
+    }
+}
+
+// Upstream status codes of an envelope that mean a temporary failure on Instagram's side.
+const GATEWAY_FAILURE_CODES: [i64; 4] = [500, 502, 503, 504];
+
+fn is_gateway_failure(response: &Value) -> bool {
+    response["response"]["status_code"].as_i64().is_some_and(|code| GATEWAY_FAILURE_CODES.contains(&code))
+}
+
+fn seconds_value(value: &Value) -> Option<Duration> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|secs| secs.is_finite() && *secs >= 0.0)
+    .map(Duration::from_secs_f64)
+}
+
+fn retry_after(headers: &HeaderMap, body: &Value) -> Option<Duration> {
+    // `Retry-After` in delta-seconds (HTTP dates are not supported), else a `retry_after` field of the body.
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| seconds_value(&Value::String(v.to_string())))
+        .or_else(|| seconds_value(&body["retry_after"]))
+}
+
+pub(crate) fn envelope_retry_after(response: &Value) -> Option<Duration> {
+    // Upstream headers are surfaced in the envelope by some endpoints, with any casing.
+    let headers = response["response"]["headers"].as_object();
+    let header = headers.and_then(|h| h.iter().find(|(k, _)| k.eq_ignore_ascii_case("retry-after")).map(|(_, v)| v));
+    header.and_then(seconds_value).or_else(|| seconds_value(&response["response"]["body"]["retry_after"]))
+}
+
+// Fields holding a readable error in failed envelopes, in lookup order (upstream body first, then the gateway).
+const ERROR_MESSAGE_FIELDS: [&str; 4] = ["message", "error", "detail", "error_message"];
+
+pub(crate) fn bad_response(body: Value) -> RocketAPIError {
+    let status_code = body["response"]["status_code"].as_i64().unwrap_or(0);
+    let message = [&body["response"]["body"], &body]
+        .into_iter()
+        .flat_map(|source| ERROR_MESSAGE_FIELDS.iter().map(move |field| &source[*field]))
+        .find_map(|value| value.as_str().filter(|message| !message.trim().is_empty()))
+        .map(str::to_string);
+    RocketAPIError::BadResponse { status_code, message, body }
+}
+
+fn attempt_record(attempt: u32, result: &Result<RawResponse, RocketAPIError>, latency: Duration) -> AttemptRecord {
+    let (http_status, status_code, error, headers) = match result {
+        Ok(raw) => (Some(raw.status.as_u16()), raw.body["response"]["status_code"].as_i64(), None, raw.headers.clone()),
+        Err(err @ RocketAPIError::NonJsonResponse { http_status, .. }) => (Some(*http_status), None, Some(err.to_string()), HeaderMap::new()),
+        Err(err) => (None, None, Some(err.to_string()), HeaderMap::new()),
+    };
+    AttemptRecord { attempt, http_status, status_code, latency, error, backoff: None, headers }
+}
+
+pub(crate) fn redacted_proxy(proxy: &str) -> String {
+    // Credentials of the proxy URL are hidden, the rest is kept to tell proxies apart.
+    match reqwest::Url::parse(proxy) {
+        Ok(mut url) if !url.username().is_empty() || url.password().is_some() => {
+            let _ = url.set_username("***");
+            let _ = url.set_password(None);
+            url.to_string()
+        }
+        Ok(url) => url.to_string(),
+        Err(_) => "***".to_string(),
+    }
+}
+
+fn authorization(scheme: &str, token: &str) -> Result<HeaderValue, RocketAPIError> {
+    HeaderValue::from_str(&format!("{} {}", scheme, token))
+        .map_err(|_| RocketAPIError::InvalidParameter("token or auth scheme is not a valid header value".to_string()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HttpConfig {
+    /*
+    Connection pool settings of the HTTP client, see `RocketAPI::with_http_config`.
+    Fields left to `None` keep the reqwest defaults, so `HttpConfig::default()` changes nothing.
+
+    Fields:
+        pool_idle_timeout (Duration): How long an idle connection is kept open (reqwest default: 90 seconds).
+            Lower it if the network drops idle connections silently, which shows as a slow first request after a pause.
+        pool_max_idle_per_host (usize): Maximum number of idle connections kept (reqwest default: no limit)
+        tcp_keepalive (Duration): Interval of TCP keepalive probes on open connections
+    */
+    pub pool_idle_timeout: Option<Duration>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl HttpConfig {
+    pub fn validate(&self) -> Result<(), RocketAPIError> {
+        /*
+        Check the settings, called by `RocketAPI::with_http_config` and the builders.
+        A zero idle timeout would close every connection right away (set `pool_max_idle_per_host` to 0 to disable
+        pooling instead), and keepalive intervals are whole seconds for the OS, so they must be at least 1 second.
+        */
+        if self.pool_idle_timeout.is_some_and(|timeout| timeout.is_zero()) {
+            return Err(RocketAPIError::InvalidParameter("http_config.pool_idle_timeout must be positive".to_string()));
+        }
+        if self.tcp_keepalive.is_some_and(|interval| interval < Duration::from_secs(1)) {
+            return Err(RocketAPIError::InvalidParameter("http_config.tcp_keepalive must be at least 1 second".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn build_client(timeout: Duration, proxy: Option<&Proxy>, http_config: &HttpConfig) -> Result<Client, RocketAPIError> {
+    let mut builder = Client::builder().timeout(timeout);
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy.clone());
+    }
+    if let Some(idle_timeout) = http_config.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(idle_timeout);
+    }
+    if let Some(max_idle) = http_config.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(keepalive) = http_config.tcp_keepalive {
+        builder = builder.tcp_keepalive(keepalive);
+    }
+    builder.build().map_err(RocketAPIError::RequestError)
+}
+
+pub type BodyTransform = Arc<dyn Fn(Value) -> Value + Send + Sync>;
+pub type RequestHook = Arc<dyn Fn(&str, &Value) + Send + Sync>;
+pub type ResponseHook = Arc<dyn Fn(&str, &Value, Duration) + Send + Sync>;
+pub type ErrorHook = Arc<dyn Fn(&str, &RocketAPIError, Duration) + Send + Sync>;
+
+// Callbacks fired around every attempt, see `RocketAPI::on_request`.
+#[derive(Clone, Default)]
+struct Hooks {
+    on_request: Option<RequestHook>,
+    on_response: Option<ResponseHook>,
+    on_error: Option<ErrorHook>,
+}
+
+pub struct TimeoutScope<'a, C> {
+    /*
+    Client whose timeout is temporarily overridden, see `InstagramAPI::with_timeout`.
+    Derefs to the client; the previous timeout is restored when the scope is dropped.
+    */
+    client: &'a mut C,
+    api: fn(&mut C) -> &mut RocketAPI,
+    previous: Duration,
+}
+
+impl<'a, C> TimeoutScope<'a, C> {
+    pub(crate) fn new(client: &'a mut C, api: fn(&mut C) -> &mut RocketAPI, timeout: Duration) -> Self {
+        let previous = api(client).timeout();
+        api(client).set_timeout(timeout);
+        TimeoutScope { client, api, previous }
+    }
+}
+
+impl<C> std::ops::Deref for TimeoutScope<'_, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.client
+    }
+}
+
+impl<C> std::ops::DerefMut for TimeoutScope<'_, C> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.client
+    }
+}
+
+impl<C> Drop for TimeoutScope<'_, C> {
+    fn drop(&mut self) {
+        (self.api)(self.client).set_timeout(self.previous);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    /*
+    Gateway response with its HTTP metadata, see `RocketAPI::request_full`.
+
+    Fields:
+        status (StatusCode): HTTP status of the gateway response
+        headers (HeaderMap): HTTP headers of the gateway response
+        body (serde_json::Value): Envelope, as returned by `request`
+    */
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: serde_json::Value,
+}
+
+// State of a client (`InstagramAPI`, `ThreadsAPI`) updated by each of its calls, see `RocketAPI::call_with_state`.
+pub(crate) struct CallState<'a> {
+    pub(crate) counter: &'a mut u32,
+    pub(crate) last_response: &'a mut Value,
+    pub(crate) last_retries: &'a mut u32,
+    pub(crate) last_attempts: &'a mut Vec<AttemptRecord>,
+}
+
+#[derive(Clone)]
 pub struct RocketAPI {
+    // Built once and shared by clones, so keep-alive connections are reused across requests.
+    client: Client,
+    // The client was passed to `with_client`, so it can't be rebuilt with another proxy.
+    custom_client: bool,
+    http_config: HttpConfig,
     base_url: String,
     token: String,
+    auth_scheme: String,
+    user_agent: HeaderValue,
     max_timeout: Duration,
+    retry_policy: RetryPolicy,
+    strict: bool,
+    body_transform: Option<BodyTransform>,
+    hooks: Hooks,
+    streaming_parse: bool,
+    upstream_errors: bool,
+    schema_baselines: HashMap<String, u64>,
+    transport: Option<Arc<dyn Transport>>,
+    interceptors: Vec<Arc<dyn RequestInterceptor>>,
+    stats: StatsStore,
+    costs: CostTable,
+    credit_budget: Option<CreditBudget>,
+    retry_after_gate: Option<RetryAfterGate>,
+    page_coalescer: Option<PageCoalescer>,
+    error_context: bool,
+    rate_limiter: Option<RateLimiter>,
+    in_flight: InFlight,
+    #[cfg(feature = "cancellation")]
+    cancel_token: Option<tokio_util::sync::CancellationToken>,
+}
+
+impl std::fmt::Debug for RocketAPI {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The token is redacted so clients can be logged safely.
+        f.debug_struct("RocketAPI")
+            .field("base_url", &self.base_url)
+            .field("http_config", &self.http_config)
+            .field("token", &"***")
+            .field("auth_scheme", &self.auth_scheme)
+            .field("user_agent", &self.user_agent)
+            .field("max_timeout", &self.max_timeout)
+            .field("retry_policy", &self.retry_policy)
+            .field("strict", &self.strict)
+            .field("custom_client", &self.custom_client)
+            .field("custom_transport", &self.transport.is_some())
+            .field("interceptors", &self.interceptors.len())
+            .field("rate_limit", &self.rate_limit())
+            .field("max_in_flight", &self.max_in_flight())
+            .finish_non_exhaustive()
+    }
 }
 
 impl RocketAPI {
     pub fn new(token: String, max_timeout: Duration) -> Self {
+        /*
+        Client with the default HTTP settings.
+
+        Panics if the HTTP client can't be built (e.g. no TLS backend could be initialized),
+        see `try_new` to get an error instead.
+        */
+        Self::try_new(token, max_timeout).expect("failed to build the HTTP client")
+    }
+
+    pub fn try_new(token: String, max_timeout: Duration) -> Result<Self, RocketAPIError> {
+        /*
+        Same as `new`, returning `RocketAPIError::RequestError` if the HTTP client can't be built.
+        */
+        let client = build_client(max_timeout, None, &HttpConfig::default())?;
+        Ok(RocketAPI::from_client(token, max_timeout, client))
+    }
+
+    fn from_client(token: String, max_timeout: Duration, client: Client) -> Self {
         RocketAPI {
-            base_url: "https://v1.rocketapi.io/".to_string(),
+            client,
+            custom_client: false,
+            http_config: HttpConfig::default(),
+            base_url: DEFAULT_BASE_URL.to_string(),
             token,
-            max_timeout: max_timeout,
+            auth_scheme: DEFAULT_AUTH_SCHEME.to_string(),
+            user_agent: HeaderValue::from_static(USER_AGENT_PREFIX),
+            max_timeout,
+            retry_policy: RetryPolicy::none(),
+            strict: false,
+            body_transform: None,
+            hooks: Hooks::default(),
+            streaming_parse: false,
+            upstream_errors: false,
+            schema_baselines: HashMap::new(),
+            transport: None,
+            interceptors: Vec::new(),
+            stats: StatsStore::default(),
+            costs: CostTable::default(),
+            credit_budget: None,
+            retry_after_gate: None,
+            page_coalescer: None,
+            error_context: false,
+            rate_limiter: None,
+            in_flight: InFlight::default(),
+            #[cfg(feature = "cancellation")]
+            cancel_token: None,
+        }
+    }
+
+    pub fn with_transport(token: String, max_timeout: Duration, transport: Arc<dyn Transport>) -> Self {
+        /*
+        Client sending requests through a custom `Transport` instead of HTTP
+        (mocks, recorded responses, tower services, ...).
+        */
+        RocketAPI {
+            transport: Some(transport),
+            ..RocketAPI::new(token, max_timeout)
+        }
+    }
+
+    pub fn with_client(token: String, max_timeout: Duration, client: Client) -> Self {
+        /*
+        Client sending requests through an existing `reqwest::Client` (custom root CAs, proxy, pool limits, ...).
+
+        The client is used as is; `max_timeout` is applied to each request on top of its own settings.
+        Its proxy can't be changed afterwards: `set_proxy` and `set_proxy_config` return an error instead of
+        replacing it (and its pool, TLS and middleware settings) with a newly built client.
+        */
+        RocketAPI {
+            custom_client: true,
+            ..RocketAPI::from_client(token, max_timeout, client)
+        }
+    }
+
+    pub fn with_http_config(token: String, max_timeout: Duration, http_config: HttpConfig) -> Result<Self, RocketAPIError> {
+        /*
+        Client with tuned connection pooling, e.g. for long pagination sessions with idle periods.
+
+        Args:
+            http_config (HttpConfig): Pool settings, kept when the client is rebuilt by `set_proxy`
+
+        Returns `RocketAPIError::InvalidParameter` if the settings are invalid, see `HttpConfig::validate`.
+        */
+        http_config.validate()?;
+        Ok(RocketAPI {
+            http_config,
+            ..RocketAPI::from_client(token, max_timeout, build_client(max_timeout, None, &http_config)?)
+        })
+    }
+
+    pub fn http_config(&self) -> &HttpConfig {
+        &self.http_config
+    }
+
+    pub fn with_base_url(token: String, max_timeout: Duration, base_url: &str) -> Result<Self, RocketAPIError> {
+        /*
+        Client sending requests to another gateway (local mock server, self-hosted proxy, ...).
+
+        Args:
+            base_url (str): Gateway URL, with or without a trailing slash
+        */
+        let mut api = RocketAPI::try_new(token, max_timeout)?;
+        api.set_base_url(base_url)?;
+        Ok(api)
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn set_base_url(&mut self, base_url: &str) -> Result<(), RocketAPIError> {
+        /*
+        Change the gateway URL, `DEFAULT_BASE_URL` by default.
+
+        Args:
+            base_url (str): http(s) URL, with or without a trailing slash
+
+        Returns `RocketAPIError::InvalidParameter` if the URL can't be parsed.
+        */
+        let parsed = reqwest::Url::parse(base_url)
+            .map_err(|e| RocketAPIError::InvalidParameter(format!("invalid base URL {}: {}", base_url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(RocketAPIError::InvalidParameter(format!("base URL must be http(s): {}", base_url)));
+        }
+        // Requests are made to `<base_url><method>`, so the base URL always ends with exactly one slash.
+        self.base_url = format!("{}/", base_url.trim_end_matches('/'));
+        Ok(())
+    }
+
+    pub fn auth_scheme(&self) -> &str {
+        &self.auth_scheme
+    }
+
+    pub fn set_auth_scheme(&mut self, scheme: &str) -> Result<(), RocketAPIError> {
+        /*
+        Change the scheme of the `Authorization` header, `Token` by default.
+
+        Args:
+            scheme (str): Scheme expected by the gateway, e.g. `Bearer`
+
+        Returns `RocketAPIError::InvalidParameter` if the scheme and token don't make a valid header value.
+        */
+        if scheme.is_empty() || scheme.contains(char::is_whitespace) {
+            return Err(RocketAPIError::InvalidParameter(format!("invalid auth scheme: {:?}", scheme)));
         }
+        authorization(scheme, &self.token)?;
+        self.auth_scheme = scheme.to_string();
+        Ok(())
+    }
+
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.to_str().unwrap_or(USER_AGENT_PREFIX)
+    }
+
+    pub fn set_user_agent_suffix(&mut self, suffix: Option<&str>) -> Result<(), RocketAPIError> {
+        /*
+        Append an application identifier to the `User-Agent` header (`rocketapi-rust/<version>` by default).
+
+        Args:
+            suffix (str): E.g. `my-scraper/2.1`, `None` to remove it
+
+        Returns `RocketAPIError::InvalidParameter` if the suffix isn't a valid header value.
+        */
+        let user_agent = match suffix {
+            Some(suffix) => HeaderValue::from_str(&format!("{} {}", USER_AGENT_PREFIX, suffix.trim()))
+                .map_err(|_| RocketAPIError::InvalidParameter(format!("invalid user agent suffix: {:?}", suffix)))?,
+            None => HeaderValue::from_static(USER_AGENT_PREFIX),
+        };
+        self.user_agent = user_agent;
+        Ok(())
+    }
+
+    pub fn set_proxy(&mut self, proxy: Option<&str>) -> Result<(), RocketAPIError> {
+        /*
+        Send every request through a proxy.
+
+        Args:
+            proxy (str): Proxy URL (`http://`, `https://` or `socks5://`, credentials as `user:pass@host`),
+                `None` to connect directly
+
+        The HTTP client is rebuilt, so open connections are dropped. Returns `RocketAPIError::InvalidParameter`
+        if the URL is invalid, or if the client was passed to `with_client` (configure the proxy on that client).
+        */
+        let proxy = proxy
+            .map(|url| Proxy::all(url).map_err(|e| RocketAPIError::InvalidParameter(format!("invalid proxy {}: {}", redacted_proxy(url), e))))
+            .transpose()?;
+        self.set_proxy_config(proxy)
+    }
+
+    pub fn set_proxy_config(&mut self, proxy: Option<Proxy>) -> Result<(), RocketAPIError> {
+        /*
+        Same as `set_proxy` with an already built `reqwest::Proxy` (custom auth, per-scheme rules, no-proxy lists, ...).
+        */
+        if self.custom_client {
+            return Err(RocketAPIError::InvalidParameter(
+                "the proxy of a client passed to with_client can't be changed, configure it on that client".to_string(),
+            ));
+        }
+        self.client = build_client(self.max_timeout, proxy.as_ref(), &self.http_config)?;
+        Ok(())
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        /*
+        Strict mode turns envelope drift into `RocketAPIError::InvalidResponse` errors instead of lenient defaults:
+        a missing or mistyped envelope field, an unknown `status` value, or a pagination cursor of the wrong type.
+        The error holds a JSON pointer to the offending field and the expected type. Disabled by default.
+        */
+        self.strict = strict;
+    }
+
+    pub fn set_body_transform<F>(&mut self, transform: F)
+    where
+        F: Fn(Value) -> Value + Send + Sync + 'static,
+    {
+        /*
+        Apply `transform` to the body of every successful response before it is returned,
+        e.g. to unwrap a known envelope layer or normalize fields.
+        */
+        self.body_transform = Some(Arc::new(transform));
+    }
+
+    pub fn clear_body_transform(&mut self) {
+        self.body_transform = None;
+    }
+
+    pub fn on_request<F>(&mut self, hook: F)
+    where
+        F: Fn(&str, &Value) + Send + Sync + 'static,
+    {
+        /*
+        Call `hook` with the method and payload before every attempt (retries included),
+        e.g. for audit logs or cost accounting.
+        */
+        self.hooks.on_request = Some(Arc::new(hook));
+    }
+
+    pub fn on_response<F>(&mut self, hook: F)
+    where
+        F: Fn(&str, &Value, Duration) + Send + Sync + 'static,
+    {
+        /*
+        Call `hook` with the method, the raw envelope and the duration of the attempt after every response,
+        whatever its status.
+        */
+        self.hooks.on_response = Some(Arc::new(hook));
+    }
+
+    pub fn on_error<F>(&mut self, hook: F)
+    where
+        F: Fn(&str, &RocketAPIError, Duration) + Send + Sync + 'static,
+    {
+        /*
+        Call `hook` with the method, the error and the duration of the attempt when no response was received
+        (connection errors, timeouts, transport errors).
+        */
+        self.hooks.on_error = Some(Arc::new(hook));
+    }
+
+    pub fn clear_hooks(&mut self) {
+        self.hooks = Hooks::default();
+    }
+
+    pub fn add_interceptor<I: RequestInterceptor + 'static>(&mut self, interceptor: I) {
+        /*
+        Append an interceptor to the request chain, see the `middleware` module.
+        Interceptors added first run first (outermost).
+        */
+        self.interceptors.push(Arc::new(interceptor));
+    }
+
+    pub fn clear_interceptors(&mut self) {
+        self.interceptors.clear();
+    }
+
+    pub fn stats(&self) -> HashMap<String, EndpointStats> {
+        // Per-endpoint calls of the clients built on this `RocketAPI` and their clones.
+        self.stats.snapshot()
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.reset();
+    }
+
+    pub(crate) fn record_stats(&self, method: &str, success: bool, latency: Duration) {
+        self.stats.record(method, success, latency);
+    }
+
+    pub(crate) async fn call_with_state(&self, method: &str, data: Value, state: CallState<'_>) -> (Result<Value, RocketAPIError>, Duration) {
+        // `call` of `InstagramAPI` and `ThreadsAPI`: the request in its span, then the bookkeeping of `state`.
+        // The latency covers the whole call, retries and backoff included.
+        let context = self.request_context(method, &data);
+        let span = trace::request_span(method, Some(*state.counter));
+        let start = Instant::now();
+        let (result, retries, attempts) = trace::in_span(&span, self.request_counted(method, data)).await;
+        let latency = start.elapsed();
+        *state.last_retries = retries;
+        *state.last_attempts = attempts;
+        let status_code = state.last_attempts.last().and_then(|attempt| attempt.status_code);
+        let result = self.record_call(method, context, result, latency, state.counter, state.last_response);
+        trace::finish(&span, status_code, &result, latency);
+        (result, latency)
+    }
+
+    pub(crate) fn record_call(
+        &self,
+        method: &str,
+        context: Option<RequestContext>,
+        result: Result<Value, RocketAPIError>,
+        latency: Duration,
+        counter: &mut u32,
+        last_response: &mut Value,
+    ) -> Result<Value, RocketAPIError> {
+        // Bookkeeping of a finished request (`last_response`, `counter`, stats, warnings) and envelope parsing.
+        // `context` is attached to the error, see `set_error_context`.
+        let result = result.and_then(|response| {
+            *last_response = response.clone();
+            *counter += 1;
+            #[cfg(feature = "log")]
+            for warning in crate::helpers::response_warnings(&response) {
+                log::warn!("{}: {}", method, warning);
+            }
+            self.parse_response(method, response)
+        });
+        // A cancelled call says nothing about the endpoint, so it's left out of the stats.
+        if !matches!(result, Err(RocketAPIError::Cancelled)) {
+            self.record_stats(method, result.is_ok(), latency);
+        }
+        result.map_err(|err| err.with_context(context))
+    }
+
+    pub fn set_streaming_parse(&mut self, streaming_parse: bool) {
+        /*
+        Parse response bodies while they are downloaded instead of buffering them first.
+        Lowers peak memory for very large pages (e.g. followers with the maximum count) at the cost of
+        a blocking thread per request, and of speed: on a local 5 MB page it takes about twice as long as the
+        buffered path (see the ignored `bench_streaming_against_buffered_parsing` test). Disabled by default.
+        */
+        self.streaming_parse = streaming_parse;
+    }
+
+    pub fn set_upstream_errors(&mut self, upstream_errors: bool) {
+        /*
+        Map known Instagram error signals in the body (`message` or `error_type`) to dedicated errors,
+        even when the gateway reports success:
+            challenge_required, checkpoint_required -> RocketAPIError::ChallengeRequired
+            login_required -> RocketAPIError::LoginRequired
+            feedback_required, sentry_block, or a `spam: true` / `feedback_title` / `feedback_message` field
+                -> RocketAPIError::FeedbackRequired
+        FeedbackRequired is an action block on Instagram's side: back off for hours, not seconds,
+        quick retries only extend it. Disabled by default.
+        */
+        self.upstream_errors = upstream_errors;
+    }
+
+    pub fn set_schema_baselines(&mut self, baselines: HashMap<String, u64>) {
+        /*
+        Expected body fingerprints per endpoint, e.g. `"instagram/user/get_info" => 0x1f3a...`
+        (see `helpers::schema_fingerprint`).
+
+        When a successful body of an endpoint with a baseline has a different set of top-level keys,
+        a warning is emitted with `tracing::warn!` (requires the `tracing` feature). Endpoints without
+        a baseline are not checked. Empty by default.
+        */
+        self.schema_baselines = baselines;
+    }
+
+    pub fn schema_baselines(&self) -> &HashMap<String, u64> {
+        &self.schema_baselines
+    }
+
+    pub fn schema_changed(&self, method: &str, body: &Value) -> bool {
+        /*
+        Whether `body` differs from the recorded baseline of `method`. False if there is no baseline.
+        */
+        self.schema_baselines.get(method).is_some_and(|baseline| *baseline != schema_fingerprint(body))
+    }
+
+    pub(crate) fn parse_response(&self, method: &str, response: Value) -> Result<Value, RocketAPIError> {
+        let body = self.parse_envelope(response)?;
+        if self.schema_changed(method, &body) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                method,
+                expected = self.schema_baselines[method],
+                actual = schema_fingerprint(&body),
+                "response schema differs from the recorded baseline"
+            );
+        }
+        Ok(body)
+    }
+
+    fn parse_envelope(&self, response: Value) -> Result<Value, RocketAPIError> {
+        if self.strict {
+            self.validate_envelope(&response)?;
+        }
+        let response = if self.upstream_errors { upstream_error(response)? } else { response };
+        if response["status"] == "done" {
+            let response_body = &response["response"];
+            let status_code = response_body["status_code"].as_i64().unwrap_or(0);
+            let content_type = response_body["content_type"].as_str().unwrap_or("");
+            if status_code == 200 && content_type == "application/json" {
+                let body = response_body["body"].clone();
+                Ok(match &self.body_transform {
+                    Some(transform) => transform(body),
+                    None => body,
+                })
+            } else if status_code == 404 {
+                Err(RocketAPIError::NotFound(response))
+            } else if status_code == 429 {
+                let retry_after = envelope_retry_after(&response);
+                self.hold_requests(retry_after);
+                Err(RocketAPIError::RateLimited { retry_after, response })
+            } else {
+                Err(bad_response(response))
+            }
+        } else {
+            Err(bad_response(response))
+        }
+    }
+
+    fn validate_envelope(&self, response: &Value) -> Result<(), RocketAPIError> {
+        match response["status"].as_str() {
+            Some(status) if KNOWN_STATUSES.contains(&status) => {}
+            Some(_) => return Err(invalid("/status", "one of \"done\", \"error\"", response)),
+            None => return Err(invalid("/status", "string", response)),
+        }
+        if response["status"] != "done" {
+            return Ok(());
+        }
+        if !response["response"].is_object() {
+            return Err(invalid("/response", "object", response));
+        }
+        if !response["response"]["status_code"].is_i64() {
+            return Err(invalid("/response/status_code", "integer", response));
+        }
+        if !response["response"]["content_type"].is_string() {
+            return Err(invalid("/response/content_type", "string", response));
+        }
+        let body = &response["response"]["body"];
+        if body.is_null() {
+            return Err(invalid("/response/body", "JSON value", response));
+        }
+        for field in CURSOR_FIELDS {
+            match body.get(field) {
+                None | Some(Value::Null) | Some(Value::String(_)) | Some(Value::Number(_)) => {}
+                Some(_) => return Err(invalid(&format!("/response/body/{}", field), "string, number or null", response)),
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn set_token(&mut self, token: String) -> Result<(), RocketAPIError> {
+        /*
+        Replace the token used by subsequent requests.
+
+        The new token is checked with one free request first (see `account_info`); if it is rejected
+        the error is returned and the current token is kept. Only this client is updated: clones made
+        before (e.g. by `BatchExecutor` or a running stream) keep sending the previous token.
+        */
+        authorization(&self.auth_scheme, &token)?;
+        let previous = std::mem::replace(&mut self.token, token);
+        if let Err(e) = self.account_info().await {
+            self.token = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn set_credit_budget(&mut self, budget: Option<u64>) {
+        /*
+        Cap the estimated credits this client may spend.
+
+        Args:
+            budget (u64): Credits, `None` to remove the cap
+
+        Every call is estimated with `endpoint_cost` once, before it is sent; its retries are not charged again.
+        A call that would cross the budget is not sent and returns `RocketAPIError::BudgetExceeded`. The budget is
+        shared with the clones of the client made afterwards (e.g. by `BatchExecutor`); setting it again starts a new budget.
+        */
+        self.credit_budget = budget.map(CreditBudget::new);
+    }
+
+    pub fn remaining_budget(&self) -> Option<u64> {
+        self.credit_budget.as_ref().map(CreditBudget::remaining)
+    }
+
+    pub fn credits_spent(&self) -> Option<u64> {
+        self.credit_budget.as_ref().map(CreditBudget::spent)
+    }
+
+    pub fn top_up_budget(&self, credits: u64) {
+        /*
+        Raise the credit budget at runtime. Does nothing if no budget is set.
+        */
+        if let Some(budget) = &self.credit_budget {
+            budget.top_up(credits);
+        }
+    }
+
+    pub fn endpoint_cost(&self, method: &str) -> u64 {
+        /*
+        Estimated credits of one request to `method`: 1 by default, 0 for free endpoints, unless overridden
+        with `set_endpoint_cost`.
+        */
+        self.costs.cost(method)
+    }
+
+    pub fn set_endpoint_cost(&mut self, method: &str, cost: u64) {
+        self.costs.set(method, cost);
+    }
+
+    pub fn set_retry_after_gate(&mut self, enabled: bool) {
+        /*
+        Pause all requests of this client (and clones made afterwards) after a rate limited response.
+
+        Args:
+            enabled (bool): Once a 429 carrying a `Retry-After` delay is received, new requests wait
+                until the delay has elapsed instead of being sent and rejected too. Disabled by default.
+        */
+        self.retry_after_gate = enabled.then(RetryAfterGate::default);
+    }
+
+    pub fn set_error_context(&mut self, enabled: bool) {
+        /*
+        Attach the method and payload of the request to the errors it returns, e.g. to tell apart
+        the failures of concurrent calls in logs.
+
+        Args:
+            enabled (bool): Errors of requests are wrapped in `RocketAPIError::WithContext`. Disabled by default.
+
+        Use `RocketAPIError::kind` to match on the underlying error and `RocketAPIError::context` to read the request.
+        Errors raised before a request is made (e.g. invalid parameters) have no context.
+        */
+        self.error_context = enabled;
+    }
+
+    pub(crate) fn request_context(&self, method: &str, payload: &Value) -> Option<RequestContext> {
+        // Cloning the payload is only paid for when the option is enabled.
+        self.error_context.then(|| RequestContext { method: method.to_string(), payload: payload.clone() })
+    }
+
+    pub fn set_page_coalescing(&mut self, enabled: bool) {
+        /*
+        Share one call between identical next-page requests in flight at the same time.
+
+        Args:
+            enabled (bool): When a request with a cursor (`max_id`, `min_id`, `page_token`) is made while the same
+                method with the same payload is still pending, it waits for that call and gets a copy of its
+                response instead of sending a duplicate, e.g. when a "load more" button is clicked repeatedly.
+                Disabled by default.
+
+        Applies to this client and clones made afterwards. First pages are never coalesced. Only responses are
+        shared: if the pending call fails, each waiting request is sent on its own. A shared response costs no
+        credits and has no attempts of its own (`last_attempts` is empty).
+        */
+        self.page_coalescer = enabled.then(PageCoalescer::default);
+    }
+
+    pub fn set_rate_limit(&mut self, requests_per_second: Option<f64>, burst: u32) -> Result<(), RocketAPIError> {
+        /*
+        Space out requests client-side to stay under the plan's request rate.
+
+        Args:
+            requests_per_second (f64): Sustained rate, `None` to disable the limiter (default)
+            burst (u32): Requests that may be sent at once before the rate applies (at least 1)
+
+        Every attempt (retries included) waits for a slot, so calls are delayed rather than rejected by the gateway.
+        The limiter is shared by this client and clones made afterwards, including clones used from other tasks.
+        */
+        self.rate_limiter = requests_per_second.map(|rate| RateLimiter::new(rate, burst)).transpose()?;
+        Ok(())
+    }
+
+    pub fn rate_limit(&self) -> Option<(f64, u32)> {
+        // Configured requests per second and burst.
+        self.rate_limiter.as_ref().map(|limiter| (limiter.requests_per_second(), limiter.burst()))
+    }
+
+    pub fn set_max_in_flight(&mut self, max: Option<usize>) -> Result<(), RocketAPIError> {
+        /*
+        Cap the number of requests sent at the same time by this client and clones made afterwards.
+
+        Args:
+            max (usize): Maximum number of requests in flight, `None` for no limit (default)
+
+        Extra requests wait for a slot, which is only held during the HTTP call (not while the body is parsed).
+        */
+        self.in_flight.set_max(max)
+    }
+
+    pub fn max_in_flight(&self) -> Option<usize> {
+        self.in_flight.max()
+    }
+
+    pub fn in_flight(&self) -> usize {
+        // Requests currently being sent, for monitoring.
+        self.in_flight.active()
+    }
+
+    #[cfg(feature = "cancellation")]
+    pub fn set_cancellation_token(&mut self, token: Option<tokio_util::sync::CancellationToken>) {
+        /*
+        Cancel the requests of this client (and clones made afterwards) when `token` is cancelled.
+
+        Pending requests, including those waiting for a retry backoff, return `RocketAPIError::Cancelled`
+        right away, and no new attempt is made once the token is cancelled. Requires the `cancellation` feature.
+
+        A cancelled call doesn't update `counter`, `last_response` or the endpoint stats of the client.
+        Without a token, dropping the future of a call (e.g. with `tokio::time::timeout` or `tokio::select!`)
+        also aborts its HTTP request right away, but gives no error.
+        */
+        self.cancel_token = token;
+    }
+
+    pub fn retry_after_pause(&self) -> Option<Duration> {
+        // Time left before requests are sent again, `None` if they aren't paused.
+        self.retry_after_gate.as_ref().and_then(RetryAfterGate::remaining)
+    }
+
+    fn hold_requests(&self, retry_after: Option<Duration>) {
+        if let (Some(gate), Some(delay)) = (&self.retry_after_gate, retry_after) {
+            gate.close_for(delay);
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.max_timeout
+    }
+
+    pub fn set_timeout(&mut self, max_timeout: Duration) {
+        /*
+        Change the request timeout. The new value applies to requests started after the call.
+        */
+        self.max_timeout = max_timeout;
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) -> Result<(), RocketAPIError> {
+        /*
+        Enable retries of transient failures (connection errors, timeouts, gateway 5xx). Disabled by default.
+        */
+        policy.validate()?;
+        self.retry_policy = policy;
+        Ok(())
     }
     
-    pub async fn request(&self, method: &str, data: serde_json::Value) -> Result<serde_json::Value, reqwest::Error> {
-        let client = Client::builder()
-            .timeout(self.max_timeout)
-            .build()?;
+    pub async fn request(&self, method: &str, data: serde_json::Value) -> Result<serde_json::Value, RocketAPIError> {
+        Ok(self.request_full(method, data).await?.body)
+    }
+
+    pub fn with_timeout(&mut self, timeout: Duration) -> TimeoutScope<'_, RocketAPI> {
+        /*
+        Override the request timeout for the calls made through the returned scope, see `InstagramAPI::with_timeout`.
+        */
+        TimeoutScope::new(self, |client| client, timeout)
+    }
+
+    pub async fn request_with_timeout(&mut self, method: &str, data: serde_json::Value, timeout: Duration) -> Result<serde_json::Value, RocketAPIError> {
+        /*
+        Same as `request`, with a timeout for this call only (each attempt gets `timeout`), e.g. a longer one
+        for a slow endpoint while the client default stays short. Shorthand for `with_timeout(timeout).request(...)`:
+        the client timeout is restored once the call is done, or dropped.
+        */
+        self.with_timeout(timeout).request(method, data).await
+    }
+
+    pub async fn request_full(&self, method: &str, data: serde_json::Value) -> Result<RawResponse, RocketAPIError> {
+        /*
+        Same as `request`, but also returns the HTTP status and headers of the gateway response,
+        e.g. to tell a gateway 503 from an upstream error or to read the rate limit headers.
+        With a custom transport (`with_transport`) the status is always 200 and the headers are empty.
+        */
+        let context = self.request_context(method, &data);
+        let span = trace::request_span(method, None);
+        let start = Instant::now();
+        let result = trace::in_span(&span, self.request_raw(method, data)).await.map_err(|err| err.with_context(context));
+        let status_code = result.as_ref().ok().and_then(|raw| raw.body["response"]["status_code"].as_i64());
+        trace::finish(&span, status_code, &result, start.elapsed());
+        result
+    }
+
+    pub(crate) async fn request_counted(&self, method: &str, data: serde_json::Value) -> (Result<serde_json::Value, RocketAPIError>, u32, Vec<AttemptRecord>) {
+        // Envelope of the call, the number of retries it took and the log of its attempts.
+        let (result, retries, attempts) = self.request_raw_counted(method, data).await;
+        (result.map(|response| response.body), retries, attempts)
+    }
+
+    pub(crate) async fn request_raw(&self, method: &str, data: serde_json::Value) -> Result<RawResponse, RocketAPIError> {
+        self.request_raw_counted(method, data).await.0
+    }
 
+    async fn request_raw_counted(&self, method: &str, data: serde_json::Value) -> (Result<RawResponse, RocketAPIError>, u32, Vec<AttemptRecord>) {
+        let Some(coalescer) = self.page_coalescer.as_ref().filter(|_| is_next_page(&data)) else {
+            return self.request_raw_attempts(method, data).await;
+        };
+        match coalescer.join(method, &data) {
+            Flight::Leader(leader) => {
+                let (result, retries, attempts) = self.request_raw_attempts(method, data).await;
+                leader.finish(result.as_ref().ok().cloned());
+                (result, retries, attempts)
+            }
+            Flight::Follower(receiver) => match follow(receiver).await {
+                Some(response) => (Ok(response), 0, Vec::new()),
+                None => self.request_raw_attempts(method, data).await,
+            },
+        }
+    }
+
+    async fn request_raw_attempts(&self, method: &str, data: serde_json::Value) -> (Result<RawResponse, RocketAPIError>, u32, Vec<AttemptRecord>) {
+        let mut attempt: u32 = 0;
+        let mut waited = Duration::ZERO;
+        let mut attempts: Vec<AttemptRecord> = Vec::new();
+        // One reservation per call: the retries of a request are billed as the request itself.
+        if let Some(budget) = &self.credit_budget {
+            if let Err(err) = budget.reserve(self.costs.cost(method)) {
+                return (Err(err), 0, attempts);
+            }
+        }
+        loop {
+            attempt += 1;
+            #[cfg(feature = "cancellation")]
+            if self.cancel_token.as_ref().is_some_and(|token| token.is_cancelled()) {
+                return (Err(RocketAPIError::Cancelled), attempt - 1, attempts);
+            }
+            if let Some(gate) = &self.retry_after_gate {
+                gate.wait().await;
+            }
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire().await;
+            }
+            if let Some(hook) = &self.hooks.on_request {
+                hook(method, &data);
+            }
+            let start = Instant::now();
+            let result = self.cancellable(self.send(method, &data)).await;
+            let latency = start.elapsed();
+            match (&result, &self.hooks) {
+                (Ok(raw), Hooks { on_response: Some(hook), .. }) => hook(method, &raw.body, latency),
+                (Err(err), Hooks { on_error: Some(hook), .. }) => hook(method, err, latency),
+                _ => {}
+            }
+            attempts.push(attempt_record(attempt, &result, latency));
+            let transient = match &result {
+                Ok(response) => response.status.is_server_error() || is_gateway_failure(&response.body),
+                Err(err) => is_transient_error(err),
+            };
+            if !transient {
+                // The gateway itself rejecting the request (quota, request rate) rather than Instagram.
+                let result = match result {
+                    Ok(raw) if raw.status == StatusCode::TOO_MANY_REQUESTS => {
+                        let retry_after = retry_after(&raw.headers, &raw.body);
+                        self.hold_requests(retry_after);
+                        Err(RocketAPIError::RateLimited { retry_after, response: raw.body })
+                    }
+                    result => result,
+                };
+                return (result, attempt - 1, attempts);
+            }
+            match self.retry_policy.next_delay(attempt, waited) {
+                Some(delay) => {
+                    // A cancellation during the backoff returns right away instead of waiting it out.
+                    if let Err(err) = self.cancellable(async { runtime::sleep(delay).await; Ok(()) }).await {
+                        return (Err(err), attempt - 1, attempts);
+                    }
+                    waited += delay;
+                    if let Some(record) = attempts.last_mut() {
+                        record.backoff = Some(delay);
+                    }
+                }
+                None => return (result, attempt - 1, attempts),
+            }
+        }
+    }
+
+    async fn cancellable<T>(&self, future: impl std::future::Future<Output = Result<T, RocketAPIError>>) -> Result<T, RocketAPIError> {
+        // Resolves to `Cancelled` as soon as the cancellation token fires, dropping `future` (and its HTTP request).
+        #[cfg(feature = "cancellation")]
+        if let Some(token) = &self.cancel_token {
+            return tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(RocketAPIError::Cancelled),
+                result = future => result,
+            };
+        }
+        future.await
+    }
+
+    async fn send(&self, method: &str, data: &serde_json::Value) -> Result<RawResponse, RocketAPIError> {
+        if self.interceptors.is_empty() {
+            return self.send_direct(method, data).await;
+        }
+        // Interceptors exchange envelopes only, the status and headers of the real call are kept aside.
+        let received: Mutex<Option<(StatusCode, HeaderMap)>> = Mutex::new(None);
+        let handler = |method, payload: Value| -> crate::middleware::InterceptorFuture<'_> {
+            let received = &received;
+            Box::pin(async move {
+                let raw = self.send_direct(method, &payload).await?;
+                *received.lock().expect("interceptor mutex poisoned") = Some((raw.status, raw.headers));
+                Ok(raw.body)
+            })
+        };
+        let body = Next::new(&self.interceptors, &handler).run(method, data.clone()).await?;
+        let (status, headers) = received.into_inner().expect("interceptor mutex poisoned").unwrap_or((StatusCode::OK, HeaderMap::new()));
+        Ok(RawResponse { status, headers, body })
+    }
+
+    async fn send_direct(&self, method: &str, data: &serde_json::Value) -> Result<RawResponse, RocketAPIError> {
+        if let Some(transport) = &self.transport {
+            let body = {
+                let _in_flight = self.in_flight.enter().await;
+                transport.call(method, data.clone()).await?
+            };
+            return Ok(RawResponse { status: StatusCode::OK, headers: HeaderMap::new(), body });
+        }
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Token {}", self.token)).unwrap());
+        headers.insert(AUTHORIZATION, authorization(&self.auth_scheme, &self.token)?);
+        headers.insert(USER_AGENT, self.user_agent.clone());
 
         let url = format!("{}{}", self.base_url, method);
-        let response: Response = client.post(&url)
+        // The per-request timeout follows `set_timeout`, the client one is only the initial default.
+        // Only the HTTP exchange holds a slot, reading and parsing the body doesn't.
+        let in_flight = self.in_flight.enter().await;
+        let response: Response = self.client.post(&url)
+            .timeout(self.max_timeout)
             .headers(headers)
-            .json(&data)
+            .json(data)
             .send()
-            .await?;
+            .await
+            .map_err(RocketAPIError::RequestError)?;
+        drop(in_flight);
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let json_response: serde_json::Value = read_json(response, self.streaming_parse).await?;
+        Ok(RawResponse { status, headers, body: json_response })
+    }
+
+    pub async fn account_info(&self) -> Result<AccountInfo, RocketAPIError> {
+        /*
+        Check the token and retrieve plan details.
+
+        RocketAPI has no dedicated account endpoint, so this makes one request to a free endpoint
+        (`instagram/media/get_shortcode_by_id`) and reads the plan and quota from the headers of the response
+        (see `AccountInfo` for their names). Headers the gateway doesn't send give `None` fields, and so does
+        a custom transport, which has no headers.
+
+        Returns `RocketAPIError::Unauthorized` if the gateway answers 401 or 403, whatever the body is.
+        */
+        let response = match self.request_raw(ACCOUNT_PROBE_METHOD, json!({ "id": ACCOUNT_PROBE_MEDIA_ID })).await {
+            // The status decides before the body: a rejected token may come with an HTML page or no body at all.
+            Err(RocketAPIError::NonJsonResponse { http_status: 401 | 403, body }) => {
+                return Err(RocketAPIError::Unauthorized(Value::String(body)));
+            }
+            result => result?,
+        };
+        if matches!(response.status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+            return Err(RocketAPIError::Unauthorized(response.body));
+        }
+        Ok(AccountInfo::from_headers(&response.headers))
+    }
+
+    pub async fn get_account_balance(&self) -> Result<Balance, RocketAPIError> {
+        /*
+        Retrieve the remaining credits of the token, e.g. to stop before requests start failing.
 
-        let json_response: serde_json::Value = response.json().await?;
-        Ok(json_response)
+        RocketAPI has no balance endpoint either: this is `account_info` (one free request) with its quota
+        headers read as credits, `x-ratelimit-remaining` as `credits` and the limit minus the remaining
+        requests as `requests_today`. Plans without these headers give `None` fields.
+        */
+        Ok(Balance::from(&self.account_info().await?))
     }
 }
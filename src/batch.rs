@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use futures::future;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde_json::Value;
+use crate::errors::RocketAPIError;
+use crate::retry::{is_transient_error, RetryPolicy};
+use crate::runtime;
+
+/*
+Batch execution of many independent calls (requires the `stream` feature).
+
+    let report = BatchExecutor::new(&instagram_api)
+        .concurrency(8)
+        .interval(Duration::from_millis(250))
+        .run(user_ids, |mut api: InstagramAPI, user_id: u64| async move { api.get_user_info_by_id(&user_id).await })
+        .await;
+
+Every call gets its own clone of the client, so `last_response` and `counter` of the original client are not updated.
+
+Long runs can be resumed with a `Checkpoint` (see `BatchExecutor::run_checkpointed`): items recorded as done
+by a previous run are skipped, so they are not requested (and billed) again.
+*/
+
+pub trait Checkpoint: Send + Sync {
+    /*
+    Record of the completed items of a batch, identified by user-provided keys.
+    */
+    fn is_done(&self, key: &str) -> bool;
+    fn record_done(&self, key: &str) -> Result<(), RocketAPIError>;
+    fn flush(&self) -> Result<(), RocketAPIError>;
+}
+
+fn checkpoint_error(path: &Path, err: std::io::Error) -> RocketAPIError {
+    RocketAPIError::IoError { path: path.to_path_buf(), error: err }
+}
+
+pub struct JsonlCheckpoint {
+    /*
+    Append-only checkpoint file, one JSON string (the key) per line.
+
+    Every key is written as soon as it is recorded, so a killed run loses at most the items in flight.
+    A truncated last line (crash while writing) is ignored on load.
+    */
+    path: std::path::PathBuf,
+    done: Mutex<HashSet<String>>,
+    file: Mutex<File>,
+}
+
+impl JsonlCheckpoint {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RocketAPIError> {
+        /*
+        Open (or create) a checkpoint file and load the keys recorded so far.
+        */
+        let path = path.as_ref();
+        let file = OpenOptions::new().create(true).read(true).append(true).open(path).map_err(|e| checkpoint_error(path, e))?;
+        let mut done = HashSet::new();
+        for line in BufReader::new(&file).lines() {
+            let line = line.map_err(|e| checkpoint_error(path, e))?;
+            if let Ok(key) = serde_json::from_str::<String>(&line) {
+                done.insert(key);
+            }
+        }
+        Ok(JsonlCheckpoint { path: path.to_path_buf(), done: Mutex::new(done), file: Mutex::new(file) })
+    }
+
+    pub fn len(&self) -> usize {
+        self.done.lock().expect("checkpoint mutex poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Checkpoint for JsonlCheckpoint {
+    fn is_done(&self, key: &str) -> bool {
+        self.done.lock().expect("checkpoint mutex poisoned").contains(key)
+    }
+
+    fn record_done(&self, key: &str) -> Result<(), RocketAPIError> {
+        // The key only counts as done once it is on disk, a failed write leaves it to be done again.
+        let mut done = self.done.lock().expect("checkpoint mutex poisoned");
+        if done.contains(key) {
+            return Ok(());
+        }
+        let line = format!("{}\n", Value::String(key.to_string()));
+        let mut file = self.file.lock().expect("checkpoint mutex poisoned");
+        file.write_all(line.as_bytes()).map_err(|e| checkpoint_error(&self.path, e))?;
+        done.insert(key.to_string());
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), RocketAPIError> {
+        let file = self.file.lock().expect("checkpoint mutex poisoned");
+        file.sync_data().map_err(|e| checkpoint_error(&self.path, e))
+    }
+}
+
+// Checkpoint of a run with the function computing the key of an item.
+type Checkpointing<'a, T> = Option<(Arc<dyn Checkpoint>, Arc<dyn Fn(&T) -> String + Send + Sync + 'a>)>;
+
+// Result of one item: `None` if the item was skipped (already done), and the number of retries.
+type Outcome<T, R> = (T, Option<Result<R, RocketAPIError>>, u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchSummary {
+    /*
+    Statistics of a batch run.
+
+    Fields:
+        succeeded (usize): Items whose call succeeded
+        failed (usize): Items whose call failed after all attempts
+        retried (usize): Items that needed more than one attempt (whatever their final result)
+        skipped (usize): Items skipped because the checkpoint had them as done
+        elapsed (Duration): Wall time of the whole run
+    */
+    pub succeeded: usize,
+    pub failed: usize,
+    pub retried: usize,
+    pub skipped: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug)]
+pub struct BatchReport<T, R> {
+    /*
+    Per-item results of `BatchExecutor::run`, in completion order, with the run summary.
+    */
+    pub results: Vec<(T, Result<R, RocketAPIError>)>,
+    pub summary: BatchSummary,
+}
+
+pub struct BatchExecutor<'a, C> {
+    client: &'a C,
+    concurrency: usize,
+    interval: Duration,
+    retry_policy: RetryPolicy,
+}
+
+async fn wait_turn(next_start: &Mutex<Instant>, interval: Duration) {
+    // Reserves the next start slot, at least `interval` after the previous one.
+    let slot = {
+        let mut next = next_start.lock().expect("batch scheduler mutex poisoned");
+        let slot = (*next).max(Instant::now());
+        *next = slot + interval;
+        slot
+    };
+    let wait = slot.saturating_duration_since(Instant::now());
+    if !wait.is_zero() {
+        runtime::sleep(wait).await;
+    }
+}
+
+impl<'a, C: Clone + Send + Sync + 'a> BatchExecutor<'a, C> {
+    pub fn new(client: &'a C) -> Self {
+        /*
+        Executor running calls on clones of `client`: one call at a time, no delay between calls and no
+        retries until configured otherwise.
+        */
+        BatchExecutor { client, concurrency: 1, interval: Duration::ZERO, retry_policy: RetryPolicy::none() }
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        /*
+        Maximum number of calls running at the same time (at least 1).
+        */
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        /*
+        Minimum delay between the starts of two calls (retries included), across all concurrent calls.
+        */
+        self.interval = interval;
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        /*
+        Retry policy applied per item to transient failures (connection errors, timeouts). It comes on top of
+        the retry policy of the client, which already retries single requests.
+        */
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    fn outcomes<I, T, R, F, Fut>(&self, items: I, checkpointing: Checkpointing<'a, T>, call: F) -> BoxStream<'a, Outcome<T, R>>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'a,
+        T: Clone + Send + 'a,
+        R: Send + 'a,
+        F: Fn(C, T) -> Fut + Send + Sync + 'a,
+        Fut: Future<Output = Result<R, RocketAPIError>> + Send + 'a,
+    {
+        let call = Arc::new(call);
+        let next_start = Arc::new(Mutex::new(Instant::now()));
+        let client = self.client.clone();
+        let interval = self.interval;
+        let policy = self.retry_policy.clone();
+        stream::iter(items)
+            .map(move |item| {
+                let (call, next_start, client, policy) = (call.clone(), next_start.clone(), client.clone(), policy.clone());
+                let checkpointing = checkpointing.clone();
+                async move {
+                    let key = checkpointing.as_ref().map(|(checkpoint, key)| (checkpoint, key(&item)));
+                    if key.as_ref().is_some_and(|(checkpoint, key)| checkpoint.is_done(key)) {
+                        return (item, None, 0);
+                    }
+                    let mut attempt: u32 = 0;
+                    let mut waited = Duration::ZERO;
+                    loop {
+                        attempt += 1;
+                        wait_turn(&next_start, interval).await;
+                        let result = call(client.clone(), item.clone()).await;
+                        if let Err(err) = &result {
+                            if let Some(delay) = is_transient_error(err).then(|| policy.next_delay(attempt, waited)).flatten() {
+                                runtime::sleep(delay).await;
+                                waited += delay;
+                                continue;
+                            }
+                        }
+                        // A call whose success can't be recorded is reported as failed, a rerun would repeat it.
+                        let result = match (result, &key) {
+                            (Ok(value), Some((checkpoint, key))) => checkpoint.record_done(key).map(|_| value),
+                            (result, _) => result,
+                        };
+                        return (item, Some(result), attempt - 1);
+                    }
+                }
+            })
+            .buffer_unordered(self.concurrency)
+            .boxed()
+    }
+
+    pub fn stream<I, T, R, F, Fut>(&self, items: I, call: F) -> BoxStream<'a, (T, Result<R, RocketAPIError>)>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'a,
+        T: Clone + Send + 'a,
+        R: Send + 'a,
+        F: Fn(C, T) -> Fut + Send + Sync + 'a,
+        Fut: Future<Output = Result<R, RocketAPIError>> + Send + 'a,
+    {
+        /*
+        Run `call` for every item and yield `(item, result)` pairs as calls complete.
+
+        Args:
+            items: Work items
+            call: Async closure receiving a clone of the client and an item
+
+        Items are pulled lazily, at most `concurrency` calls run at once.
+        */
+        self.outcomes(items, None, call).filter_map(|(item, result, _)| future::ready(result.map(|r| (item, r)))).boxed()
+    }
+
+    pub async fn run<I, T, R, F, Fut>(&self, items: I, call: F) -> BatchReport<T, R>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'a,
+        T: Clone + Send + 'a,
+        R: Send + 'a,
+        F: Fn(C, T) -> Fut + Send + Sync + 'a,
+        Fut: Future<Output = Result<R, RocketAPIError>> + Send + 'a,
+    {
+        /*
+        Same as `stream`, but waits for every item and returns the results with a `BatchSummary`.
+        */
+        Self::report(self.outcomes(items, None, call)).await
+    }
+
+    pub fn stream_checkpointed<I, T, R, K, F, Fut>(
+        &self,
+        items: I,
+        checkpoint: Arc<dyn Checkpoint>,
+        key: K,
+        call: F,
+    ) -> BoxStream<'a, (T, Result<R, RocketAPIError>)>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'a,
+        T: Clone + Send + 'a,
+        R: Send + 'a,
+        K: Fn(&T) -> String + Send + Sync + 'a,
+        F: Fn(C, T) -> Fut + Send + Sync + 'a,
+        Fut: Future<Output = Result<R, RocketAPIError>> + Send + 'a,
+    {
+        /*
+        Same as `stream`, skipping the items whose key is done in `checkpoint` and recording the key of every
+        successful item. Call `checkpoint.flush()` once the stream is consumed.
+        */
+        self.outcomes(items, Some((checkpoint, Arc::new(key))), call)
+            .filter_map(|(item, result, _)| future::ready(result.map(|r| (item, r))))
+            .boxed()
+    }
+
+    pub async fn run_checkpointed<I, T, R, K, F, Fut>(
+        &self,
+        items: I,
+        checkpoint: Arc<dyn Checkpoint>,
+        key: K,
+        call: F,
+    ) -> Result<BatchReport<T, R>, RocketAPIError>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'a,
+        T: Clone + Send + 'a,
+        R: Send + 'a,
+        K: Fn(&T) -> String + Send + Sync + 'a,
+        F: Fn(C, T) -> Fut + Send + Sync + 'a,
+        Fut: Future<Output = Result<R, RocketAPIError>> + Send + 'a,
+    {
+        /*
+        Same as `run`, with checkpointing.
+
+        Args:
+            items: Work items
+            checkpoint: Checkpoint consulted before each item and updated after each success, e.g. `JsonlCheckpoint`
+            key: Key of an item in the checkpoint, must be stable across runs
+            call: Async closure receiving a clone of the client and an item
+
+        Skipped items are counted in `summary.skipped` and not part of `results`.
+        The checkpoint is flushed at the end; an error is returned only if that fails.
+        */
+        let report = Self::report(self.outcomes(items, Some((checkpoint.clone(), Arc::new(key))), call)).await;
+        checkpoint.flush()?;
+        Ok(report)
+    }
+
+    async fn report<T, R>(mut outcomes: BoxStream<'a, Outcome<T, R>>) -> BatchReport<T, R> {
+        let start = Instant::now();
+        let mut summary = BatchSummary { succeeded: 0, failed: 0, retried: 0, skipped: 0, elapsed: Duration::ZERO };
+        let mut results = Vec::new();
+        while let Some((item, result, retries)) = outcomes.next().await {
+            let Some(result) = result else {
+                summary.skipped += 1;
+                continue;
+            };
+            match result {
+                Ok(_) => summary.succeeded += 1,
+                Err(_) => summary.failed += 1,
+            }
+            if retries > 0 {
+                summary.retried += 1;
+            }
+            results.push((item, result));
+        }
+        summary.elapsed = start.elapsed();
+        BatchReport { results, summary }
+    }
+}
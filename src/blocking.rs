@@ -0,0 +1,233 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use serde_json::Value;
+use tokio::runtime::{Builder, Runtime};
+use crate::errors::RocketAPIError;
+use crate::instagramapi::{self, ProfileOverview};
+use crate::models::{ContactInfo, FriendshipStatus, MediaInfo, NormalizedProfile, MediaPage, SearchRequest, SearchUsersPage, StoryItem, ThreadConversation, ThreadLikersPage, UserInfo, UserSummary, UsersPage};
+use crate::pagination::{CollectResult, Cursor};
+use crate::threadsapi;
+use crate::urls::InstagramUrl;
+
+/*
+Synchronous clients, for CLI scripts and sync handlers. Requires the `blocking` feature.
+
+`InstagramAPIBlocking` and `ThreadsAPIBlocking` wrap the async clients and drive each call to completion on
+a private single-threaded runtime, so payloads, retries and error handling are exactly those of the async
+methods. Methods have the same names and arguments (see the async methods for their documentation); the ones
+not mirrored here (streams) can be called through `run`. `blocking::InstagramAPI` and `blocking::ThreadsAPI`
+are aliases of the two clients, so switching from the async clients only means changing the import and
+dropping the `.await`s.
+
+They are not built on `reqwest::blocking` on purpose: that client is itself a Tokio runtime on a background
+thread behind a channel, and using it would mean a second implementation of every method, of the retries, the
+rate limiting and the middleware, which would drift from the async one. Driving the async client on a runtime
+owned by the blocking client gives the same behaviour with one code path, and no thread hop per request.
+
+Don't use them from inside an async runtime: blocking a runtime thread panics.
+*/
+
+pub use self::{InstagramAPIBlocking as InstagramAPI, ThreadsAPIBlocking as ThreadsAPI};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+fn runtime() -> Runtime {
+    Builder::new_current_thread().enable_all().build().expect("failed to build the blocking runtime")
+}
+
+macro_rules! blocking_methods {
+    ($($name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            pub fn $name(&mut self, $($arg: $ty),*) -> Result<$ret, RocketAPIError> {
+                self.runtime.block_on(self.inner.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+pub struct InstagramAPIBlocking {
+    /*
+    Blocking variant of `InstagramAPI`, see the module documentation.
+    */
+    inner: instagramapi::InstagramAPI,
+    runtime: Runtime,
+}
+
+impl InstagramAPIBlocking {
+    pub fn new(token: String, max_timeout: Duration) -> Self {
+        Self::from_async(instagramapi::InstagramAPI::new(token, max_timeout))
+    }
+
+    pub fn from_async(inner: instagramapi::InstagramAPI) -> Self {
+        // Built with `InstagramAPI::builder()` or `from_config` for more options.
+        InstagramAPIBlocking { inner, runtime: runtime() }
+    }
+
+    pub fn connect(token: String, max_timeout: Duration) -> Result<Self, RocketAPIError> {
+        let runtime = runtime();
+        let inner = runtime.block_on(instagramapi::InstagramAPI::connect(token, max_timeout))?;
+        Ok(InstagramAPIBlocking { inner, runtime })
+    }
+
+    pub fn inner(&self) -> &instagramapi::InstagramAPI {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut instagramapi::InstagramAPI {
+        &mut self.inner
+    }
+
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    pub fn into_inner(self) -> instagramapi::InstagramAPI {
+        self.inner
+    }
+
+    pub fn run<T>(&mut self, call: impl for<'a> FnOnce(&'a mut instagramapi::InstagramAPI) -> BoxFuture<'a, T>) -> T {
+        /*
+        Run any async method of the wrapped client, e.g. `api.run(|api| Box::pin(api.get_user_info("kanyewest")))`.
+        */
+        self.runtime.block_on(call(&mut self.inner))
+    }
+
+    pub fn resolve_share_url(&self, url: &str) -> Result<InstagramUrl, RocketAPIError> {
+        self.runtime.block_on(self.inner.resolve_share_url(url))
+    }
+
+    pub fn find_in_followers<P>(&mut self, user_id: &u64, predicate: P, max_pages: u32) -> Result<Option<Value>, RocketAPIError>
+    where
+        P: Fn(&Value) -> bool,
+    {
+        self.runtime.block_on(self.inner.find_in_followers(user_id, predicate, max_pages))
+    }
+
+    blocking_methods! {
+        call(method: &str, data: Value) -> Value;
+        search(query: &str) -> Value;
+        search_with(request: &SearchRequest) -> Value;
+        get_user_info(username: &str) -> Value;
+        get_user_info_typed(username: &str) -> UserInfo;
+        get_normalized_profile(username: &str) -> NormalizedProfile;
+        get_normalized_profile_by_id(user_id: &u64) -> NormalizedProfile;
+        get_user_contact_info(username: &str) -> ContactInfo;
+        get_user_info_timed(username: &str) -> (Value, Duration);
+        is_verified(username: &str) -> bool;
+        canonical_username(username: &str) -> String;
+        get_user_info_by_id(user_id: &u64) -> Value;
+        get_profile_overview(user_id: &u64, page_size: u8) -> ProfileOverview;
+        is_private(user_id: &u64) -> bool;
+        get_user_media(user_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Value;
+        get_user_media_page(user_id: &u64, count: Option<u8>, cursor: Option<&Cursor>) -> MediaPage;
+        get_all_user_media(user_id: &u64, max_pages: Option<u32>) -> CollectResult<MediaInfo>;
+        get_user_media_all(user_id: &u64, count: Option<u8>, max_pages: Option<u32>) -> Vec<Value>;
+        get_user_pinned_media(user_id: &u64) -> Vec<Value>;
+        get_media_since(user_id: &u64, since: i64, until: Option<i64>) -> Vec<Value>;
+        get_user_clips(user_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Value;
+        get_user_clips_page(user_id: &u64, count: Option<u8>, cursor: Option<&Cursor>) -> MediaPage;
+        get_user_guides(user_id: &u64, max_id: Option<&str>) -> Value;
+        get_user_tags(user_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Value;
+        get_user_following(user_id: &u64, count: Option<u16>, max_id: Option<&str>) -> Value;
+        get_user_following_page(user_id: &u64, count: Option<u16>, cursor: Option<&Cursor>) -> UsersPage;
+        get_all_user_following(user_id: &u64, max_pages: Option<u32>) -> CollectResult<UserSummary>;
+        search_user_following(user_id: &u64, query: &str) -> Value;
+        get_friendship_status(source_id: &u64, target_id: &u64) -> FriendshipStatus;
+        get_user_followers(user_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Value;
+        get_user_followers_page(user_id: &u64, count: Option<u8>, cursor: Option<&Cursor>) -> UsersPage;
+        get_all_user_followers(user_id: &u64, max_pages: Option<u32>) -> CollectResult<UserSummary>;
+        audience_overlap(user_a: &u64, user_b: &u64, sample: usize) -> f64;
+        search_user_followers(user_id: &u64, query: &str) -> Value;
+        get_user_stories_bulk(user_ids: Vec<&u64>) -> Value;
+        get_user_stories(user_id: &u64) -> Value;
+        get_user_highlights(user_id: &u64) -> Value;
+        get_user_live(user_id: &u64) -> Value;
+        get_user_similar_accounts(user_id: &u64) -> Value;
+        get_media_info(media_id: &u64) -> Value;
+        get_media_info_by_shortcode(shortcode: &str) -> Value;
+        get_media_likes(shortcode: &str, count: Option<u8>, max_id: Option<&str>) -> Value;
+        get_media_comments(media_id: &u64, can_support_threading: Option<bool>, min_id: Option<&str>) -> Value;
+        get_media_shortcode_by_id(media_id: &u64) -> Value;
+        get_media_id_by_shortcode(shortcode: &str) -> Value;
+        get_media_insights(media_id: &u64) -> Value;
+        get_guide_info(guide_id: &u64) -> Value;
+        get_location_info(location_id: &u64) -> Value;
+        get_location_media(location_id: &u64, page: Option<&u64>, max_id: Option<&str>) -> Value;
+        get_hashtag_info(name: &str) -> Value;
+        get_hashtag_media(name: &str, page: Option<&u64>, max_id: Option<&str>) -> Value;
+        get_highlight_stories_bulk(highlight_ids: Vec<&u64>) -> Value;
+        get_highlight_stories(highlight_id: &u64) -> Value;
+        get_all_user_highlight_stories(user_id: &u64) -> Vec<StoryItem>;
+        get_comment_likes(comment_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Value;
+        get_comment_replies(comment_id: &u64, media_id: &u64, max_id: Option<&str>) -> Value;
+        get_all_comment_replies(comment_id: &u64, media_id: &u64, max_items: usize) -> Vec<Value>;
+        get_audio_media(audio_id: &u64, max_id: Option<&str>) -> Value;
+        get_user_about(user_id: &u64) -> Value;
+    }
+}
+
+pub struct ThreadsAPIBlocking {
+    /*
+    Blocking variant of `ThreadsAPI`, see the module documentation.
+    */
+    inner: threadsapi::ThreadsAPI,
+    runtime: Runtime,
+}
+
+impl ThreadsAPIBlocking {
+    pub fn new(token: String, max_timeout: Duration) -> Self {
+        Self::from_async(threadsapi::ThreadsAPI::new(token, max_timeout))
+    }
+
+    pub fn from_async(inner: threadsapi::ThreadsAPI) -> Self {
+        ThreadsAPIBlocking { inner, runtime: runtime() }
+    }
+
+    pub fn connect(token: String, max_timeout: Duration) -> Result<Self, RocketAPIError> {
+        let runtime = runtime();
+        let inner = runtime.block_on(threadsapi::ThreadsAPI::connect(token, max_timeout))?;
+        Ok(ThreadsAPIBlocking { inner, runtime })
+    }
+
+    pub fn inner(&self) -> &threadsapi::ThreadsAPI {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut threadsapi::ThreadsAPI {
+        &mut self.inner
+    }
+
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    pub fn into_inner(self) -> threadsapi::ThreadsAPI {
+        self.inner
+    }
+
+    pub fn run<T>(&mut self, call: impl for<'a> FnOnce(&'a mut threadsapi::ThreadsAPI) -> BoxFuture<'a, T>) -> T {
+        /*
+        Run any async method of the wrapped client, e.g. `api.run(|api| Box::pin(api.get_user_info(&35670846775)))`.
+        */
+        self.runtime.block_on(call(&mut self.inner))
+    }
+
+    blocking_methods! {
+        call(method: &str, data: Value) -> Value;
+        search_users(query: &str, rank_token: Option<&str>, page_token: Option<&str>) -> Value;
+        search_users_typed(query: &str, rank_token: Option<&str>, page_token: Option<&str>) -> SearchUsersPage;
+        get_user_info(user_id: &u64) -> Value;
+        get_user_feed(user_id: &u64, max_id: Option<&str>) -> Value;
+        get_user_replies(user_id: &u64, max_id: Option<&str>) -> Value;
+        get_user_followers(user_id: &u64, max_id: Option<&str>) -> Value;
+        search_user_followers(user_id: &u64, query: &str) -> Value;
+        get_user_following(user_id: &u64, max_id: Option<&str>) -> Value;
+        search_user_following(user_id: &u64, query: &str) -> Value;
+        get_thread_replies(thread_id: &u64, max_id: Option<&str>) -> Value;
+        get_thread_likes(thread_id: &u64, max_id: Option<&str>) -> Value;
+        get_thread_likes_typed(thread_id: &u64, cursor: Option<&Cursor>) -> ThreadLikersPage;
+        get_thread_info(thread_id: &u64) -> Value;
+        get_thread_conversation(thread_id: &u64, max_items: usize) -> ThreadConversation;
+    }
+}
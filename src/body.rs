@@ -0,0 +1,68 @@
+use std::io::Read;
+use bytes::{Buf, Bytes};
+use reqwest::{Response, StatusCode};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use crate::errors::RocketAPIError;
+use crate::runtime;
+
+// Number of body chunks buffered between the network and the parser in streaming mode.
+const STREAMING_CHANNEL_CAPACITY: usize = 4;
+// Bytes of a body that isn't JSON (load balancer error page, empty body) kept in `NonJsonResponse`.
+const BODY_EXCERPT_LEN: usize = 512;
+
+fn non_json(status: StatusCode, body: &[u8]) -> RocketAPIError {
+    let excerpt = &body[..body.len().min(BODY_EXCERPT_LEN)];
+    RocketAPIError::NonJsonResponse { http_status: status.as_u16(), body: String::from_utf8_lossy(excerpt).into_owned() }
+}
+
+pub(crate) async fn read_json(response: Response, streaming: bool) -> Result<Value, RocketAPIError> {
+    if streaming {
+        read_json_streaming(response).await
+    } else {
+        let status = response.status();
+        let body = response.bytes().await.map_err(RocketAPIError::RequestError)?;
+        serde_json::from_slice(&body).map_err(|_| non_json(status, &body))
+    }
+}
+
+async fn read_json_streaming(mut response: Response) -> Result<Value, RocketAPIError> {
+    // The body is parsed on a blocking thread while it is being downloaded, so the raw bytes
+    // never have to be held in memory all at once next to the parsed value.
+    let (sender, receiver) = mpsc::channel::<Bytes>(STREAMING_CHANNEL_CAPACITY);
+    let parser = runtime::spawn_blocking(move || {
+        serde_json::from_reader::<_, Value>(ChunkReader { receiver, current: Bytes::new() })
+    });
+    // The start of the body is kept aside to describe it if it turns out not to be JSON.
+    let status = response.status();
+    let mut excerpt: Vec<u8> = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(RocketAPIError::RequestError)? {
+        let missing = BODY_EXCERPT_LEN.saturating_sub(excerpt.len());
+        excerpt.extend_from_slice(&chunk[..chunk.len().min(missing)]);
+        if sender.send(chunk).await.is_err() {
+            // The parser gave up (invalid JSON), its error is returned below.
+            break;
+        }
+    }
+    drop(sender);
+    parser.await.map_err(|_| non_json(status, &excerpt))
+}
+
+struct ChunkReader {
+    receiver: mpsc::Receiver<Bytes>,
+    current: Bytes,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while !self.current.has_remaining() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.current = chunk,
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.current.remaining());
+        self.current.copy_to_slice(&mut buf[..n]);
+        Ok(n)
+    }
+}
@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use crate::api::{bad_response, envelope_retry_after};
+use crate::errors::RocketAPIError;
+use crate::models::deserialize_id;
+
+/*
+Zero-copy models, for high-throughput parsing of raw RocketAPI JSON (recordings, caches, NDJSON archives,
+responses fetched with your own HTTP client).
+
+String fields are `Cow<'a, str>` borrowed from the input text, so they live as long as that text:
+the models can't outlive the buffer passed to `parse_borrowed`. A string is only borrowed if it has no JSON
+escape sequences (`\n`, `\u00e9`, `\/`, ...); escaped strings are decoded into an owned `Cow` instead,
+which is correct but allocates like the owned models do. Call `Cow::into_owned` (or the `into_owned`
+methods) to keep a model past the buffer.
+
+The client methods parse responses into `serde_json::Value` before returning them, so this path
+only applies to raw JSON text.
+*/
+
+fn borrowed_option<'de: 'a, 'a, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Cow<'a, str>>, D::Error> {
+    // `#[serde(borrow)]` only borrows a bare `Cow`, an `Option<Cow>` would always be copied.
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+    Ok(Option::<Borrowed>::deserialize(deserializer)?.map(|borrowed| borrowed.0))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UserRef<'a> {
+    #[serde(deserialize_with = "deserialize_id")]
+    pub pk: u64,
+    #[serde(borrow)]
+    pub username: Cow<'a, str>,
+    #[serde(default, borrow, deserialize_with = "borrowed_option")]
+    pub full_name: Option<Cow<'a, str>>,
+    #[serde(default)]
+    pub is_private: Option<bool>,
+    #[serde(default)]
+    pub is_verified: Option<bool>,
+    #[serde(default, borrow, deserialize_with = "borrowed_option")]
+    pub profile_pic_url: Option<Cow<'a, str>>,
+}
+
+impl UserRef<'_> {
+    pub fn into_owned(self) -> UserRef<'static> {
+        UserRef {
+            pk: self.pk,
+            username: Cow::Owned(self.username.into_owned()),
+            full_name: self.full_name.map(|s| Cow::Owned(s.into_owned())),
+            is_private: self.is_private,
+            is_verified: self.is_verified,
+            profile_pic_url: self.profile_pic_url.map(|s| Cow::Owned(s.into_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UsersPageRef<'a> {
+    /*
+    Page of a user list (followers, following, likers), borrowed variant of `UsersPage`.
+    */
+    #[serde(default, borrow)]
+    pub users: Vec<UserRef<'a>>,
+    #[serde(default, borrow, deserialize_with = "borrowed_option")]
+    pub next_max_id: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    status: String,
+    response: Option<EnvelopeResponse<T>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EnvelopeResponse<T> {
+    status_code: i64,
+    #[serde(default)]
+    content_type: String,
+    body: T,
+}
+
+pub fn parse_borrowed<'a, T: Deserialize<'a>>(raw: &'a str) -> Result<T, RocketAPIError> {
+    /*
+    Parse a raw RocketAPI envelope and deserialize its body into `T`, borrowing from `raw`.
+
+    Args:
+        raw (str): Envelope text, e.g. `{"status": "done", "response": {"status_code": 200, "body": {...}}}`
+
+    Failed envelopes give the same errors as the client (`NotFound`, `BadResponse`).
+    */
+    let parse_error = match serde_json::from_str::<Envelope<T>>(raw) {
+        Ok(Envelope { status, response: Some(response) })
+            if status == "done" && response.status_code == 200 && response.content_type == "application/json" =>
+        {
+            return Ok(response.body);
+        }
+        parsed => parsed.err(),
+    };
+    // Slow path, only for failures: find out what went wrong from the whole document.
+    let envelope: Value = serde_json::from_str(raw).map_err(RocketAPIError::ParseError)?;
+    let done = envelope["status"] == "done";
+    match envelope["response"]["status_code"].as_i64() {
+        Some(200) if done && envelope["response"]["content_type"] == "application/json" => match parse_error {
+            Some(err) => Err(RocketAPIError::ParseError(err)),
+            None => Err(bad_response(envelope)),
+        },
+        Some(404) if done => Err(RocketAPIError::NotFound(envelope)),
+        Some(429) if done => Err(RocketAPIError::RateLimited { retry_after: envelope_retry_after(&envelope), response: envelope }),
+        _ => Err(bad_response(envelope)),
+    }
+}
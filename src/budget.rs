@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::errors::RocketAPIError;
+
+// Endpoints RocketAPI doesn't bill.
+const FREE_ENDPOINTS: [&str; 1] = ["instagram/media/get_shortcode_by_id"];
+// Estimated credits of any other request.
+const DEFAULT_COST: u64 = 1;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CostTable {
+    overrides: HashMap<String, u64>,
+}
+
+impl CostTable {
+    pub(crate) fn cost(&self, method: &str) -> u64 {
+        match self.overrides.get(method) {
+            Some(cost) => *cost,
+            None if FREE_ENDPOINTS.contains(&method) => 0,
+            None => DEFAULT_COST,
+        }
+    }
+
+    pub(crate) fn set(&mut self, method: &str, cost: u64) {
+        self.overrides.insert(method.to_string(), cost);
+    }
+}
+
+#[derive(Debug)]
+struct Credits {
+    budget: u64,
+    spent: u64,
+}
+
+// Shared by the clones of a client, so a budget caps the spend of all of them together.
+#[derive(Debug, Clone)]
+pub(crate) struct CreditBudget {
+    credits: Arc<Mutex<Credits>>,
+}
+
+impl CreditBudget {
+    pub(crate) fn new(budget: u64) -> Self {
+        CreditBudget { credits: Arc::new(Mutex::new(Credits { budget, spent: 0 })) }
+    }
+
+    pub(crate) fn reserve(&self, cost: u64) -> Result<(), RocketAPIError> {
+        // Called before a request is sent: concurrent requests can't overshoot the budget together.
+        let mut credits = self.credits.lock().expect("credit budget mutex poisoned");
+        if credits.spent + cost > credits.budget {
+            return Err(RocketAPIError::BudgetExceeded { spent: credits.spent, budget: credits.budget });
+        }
+        credits.spent += cost;
+        Ok(())
+    }
+
+    pub(crate) fn remaining(&self) -> u64 {
+        let credits = self.credits.lock().expect("credit budget mutex poisoned");
+        credits.budget.saturating_sub(credits.spent)
+    }
+
+    pub(crate) fn spent(&self) -> u64 {
+        self.credits.lock().expect("credit budget mutex poisoned").spent
+    }
+
+    pub(crate) fn top_up(&self, credits: u64) {
+        let mut state = self.credits.lock().expect("credit budget mutex poisoned");
+        state.budget = state.budget.saturating_add(credits);
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+use crate::api::{redacted_proxy, HttpConfig, RocketAPI};
+use crate::errors::RocketAPIError;
+use crate::retry::RetryPolicy;
+
+// Timeout used by the builders when `max_timeout` is not set.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Settings of the underlying `RocketAPI` shared by `InstagramAPIBuilder` and `ThreadsAPIBuilder`.
+#[derive(Clone, Default)]
+pub(crate) struct ApiOptions {
+    pub token: Option<String>,
+    pub auth_scheme: Option<String>,
+    pub user_agent_suffix: Option<String>,
+    pub max_timeout: Option<Duration>,
+    pub base_url: Option<String>,
+    pub proxy: Option<String>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub credit_budget: Option<u64>,
+    pub rate_limit: Option<(f64, u32)>,
+    pub max_in_flight: Option<usize>,
+    pub coalesce_pages: bool,
+    pub error_context: bool,
+    pub http_config: Option<HttpConfig>,
+}
+
+impl std::fmt::Debug for ApiOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Same redaction as `RocketAPI`, so builders can be logged safely.
+        f.debug_struct("ApiOptions")
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .field("auth_scheme", &self.auth_scheme)
+            .field("user_agent_suffix", &self.user_agent_suffix)
+            .field("max_timeout", &self.max_timeout)
+            .field("base_url", &self.base_url)
+            .field("proxy", &self.proxy.as_deref().map(redacted_proxy))
+            .field("retry_policy", &self.retry_policy)
+            .field("credit_budget", &self.credit_budget)
+            .field("rate_limit", &self.rate_limit)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("coalesce_pages", &self.coalesce_pages)
+            .field("error_context", &self.error_context)
+            .field("http_config", &self.http_config)
+            .finish()
+    }
+}
+
+impl ApiOptions {
+    pub(crate) fn build(self) -> Result<RocketAPI, RocketAPIError> {
+        let token = self.token.ok_or_else(|| RocketAPIError::InvalidParameter("token is required".to_string()))?;
+        let max_timeout = self.max_timeout.unwrap_or(DEFAULT_TIMEOUT);
+        let mut api = match self.http_config {
+            Some(http_config) => RocketAPI::with_http_config(token, max_timeout, http_config)?,
+            None => RocketAPI::try_new(token, max_timeout)?,
+        };
+        if let Some(scheme) = &self.auth_scheme {
+            api.set_auth_scheme(scheme)?;
+        }
+        if let Some(suffix) = &self.user_agent_suffix {
+            api.set_user_agent_suffix(Some(suffix))?;
+        }
+        if let Some(base_url) = &self.base_url {
+            api.set_base_url(base_url)?;
+        }
+        if self.proxy.is_some() {
+            api.set_proxy(self.proxy.as_deref())?;
+        }
+        if let Some(policy) = self.retry_policy {
+            api.set_retry_policy(policy)?;
+        }
+        api.set_credit_budget(self.credit_budget);
+        if let Some((requests_per_second, burst)) = self.rate_limit {
+            api.set_rate_limit(Some(requests_per_second), burst)?;
+        }
+        api.set_max_in_flight(self.max_in_flight)?;
+        api.set_page_coalescing(self.coalesce_pages);
+        api.set_error_context(self.error_context);
+        Ok(api)
+    }
+}
+
+macro_rules! api_options_setters {
+    () => {
+        pub fn token(mut self, token: impl Into<String>) -> Self {
+            self.options.token = Some(token.into());
+            self
+        }
+
+        pub fn auth_scheme(mut self, scheme: impl Into<String>) -> Self {
+            self.options.auth_scheme = Some(scheme.into());
+            self
+        }
+
+        pub fn user_agent_suffix(mut self, suffix: impl Into<String>) -> Self {
+            self.options.user_agent_suffix = Some(suffix.into());
+            self
+        }
+
+        pub fn max_timeout(mut self, max_timeout: std::time::Duration) -> Self {
+            self.options.max_timeout = Some(max_timeout);
+            self
+        }
+
+        pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+            self.options.base_url = Some(base_url.into());
+            self
+        }
+
+        pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+            self.options.proxy = Some(proxy.into());
+            self
+        }
+
+        pub fn retry_policy(mut self, retry_policy: crate::retry::RetryPolicy) -> Self {
+            self.options.retry_policy = Some(retry_policy);
+            self
+        }
+
+        pub fn credit_budget(mut self, credits: u64) -> Self {
+            self.options.credit_budget = Some(credits);
+            self
+        }
+
+        pub fn rate_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+            self.options.rate_limit = Some((requests_per_second, burst));
+            self
+        }
+
+        pub fn max_in_flight(mut self, max: usize) -> Self {
+            self.options.max_in_flight = Some(max);
+            self
+        }
+
+        pub fn coalesce_pages(mut self, enabled: bool) -> Self {
+            self.options.coalesce_pages = enabled;
+            self
+        }
+
+        pub fn error_context(mut self, enabled: bool) -> Self {
+            self.options.error_context = enabled;
+            self
+        }
+
+        pub fn http_config(mut self, http_config: crate::api::HttpConfig) -> Self {
+            self.options.http_config = Some(http_config);
+            self
+        }
+    };
+}
+
+pub(crate) use api_options_setters;
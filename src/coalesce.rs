@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use serde_json::Value;
+use tokio::sync::watch;
+use crate::api::RawResponse;
+
+// Payload fields carrying the cursor of a next-page request.
+const NEXT_PAGE_FIELDS: [&str; 3] = ["max_id", "min_id", "page_token"];
+
+pub(crate) fn is_next_page(payload: &Value) -> bool {
+    NEXT_PAGE_FIELDS.iter().any(|field| !payload[*field].is_null())
+}
+
+// `None` until the leader is done, then the response it got (`None` if it failed).
+type Outcome = Option<Option<RawResponse>>;
+
+// Shared by the clones of a client: identical next-page requests in flight at the same time share one call.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PageCoalescer {
+    pending: Arc<Mutex<HashMap<String, watch::Receiver<Outcome>>>>,
+}
+
+pub(crate) enum Flight {
+    Leader(Leader),
+    Follower(watch::Receiver<Outcome>),
+}
+
+impl PageCoalescer {
+    pub(crate) fn join(&self, method: &str, payload: &Value) -> Flight {
+        let key = format!("{} {}", method, payload);
+        let mut pending = self.pending.lock().expect("coalescer mutex poisoned");
+        if let Some(receiver) = pending.get(&key) {
+            return Flight::Follower(receiver.clone());
+        }
+        let (sender, receiver) = watch::channel(None);
+        pending.insert(key.clone(), receiver);
+        Flight::Leader(Leader { coalescer: self.clone(), key, sender })
+    }
+}
+
+// Sends the outcome to the followers; dropping it without `finish` (e.g. a cancelled call) releases them empty-handed.
+pub(crate) struct Leader {
+    coalescer: PageCoalescer,
+    key: String,
+    sender: watch::Sender<Outcome>,
+}
+
+impl Leader {
+    pub(crate) fn finish(self, response: Option<RawResponse>) {
+        self.sender.send_replace(Some(response));
+    }
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        self.coalescer.pending.lock().expect("coalescer mutex poisoned").remove(&self.key);
+    }
+}
+
+pub(crate) async fn follow(mut receiver: watch::Receiver<Outcome>) -> Option<RawResponse> {
+    match receiver.wait_for(Option::is_some).await {
+        Ok(outcome) => outcome.clone().flatten(),
+        Err(_) => None,
+    }
+}
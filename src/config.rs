@@ -0,0 +1,136 @@
+use std::path::Path;
+use std::time::Duration;
+use serde::Deserialize;
+use crate::api::RocketAPI;
+use crate::errors::RocketAPIError;
+use crate::instagramapi::{DefaultCounts, SponsoredFilter};
+use crate::retry::RetryPolicy;
+
+/*
+Client configuration loaded from a file.
+
+Example (TOML, requires the `toml` feature; JSON files with the same keys are always supported):
+
+    token_env = "ROCKETAPI_TOKEN"
+    timeout_secs = 30
+    strict = false
+
+    [retry]
+    max_attempts = 5
+    base_delay_ms = 500
+    max_delay_ms = 8000
+    max_total_delay_ms = 20000
+    jitter_ms = 250
+
+    [default_counts]
+    media = 50
+    followers = 100
+
+Exactly one of `token` and `token_env` (name of the environment variable holding the token) must be set,
+so the file itself doesn't have to contain the secret.
+*/
+
+fn default_timeout_secs() -> f64 {
+    30.0
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RocketAPIConfig {
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub token_env: Option<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: f64,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub retry: Option<RetryConfig>,
+    #[serde(default)]
+    pub default_counts: DefaultCounts,
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub streaming_parse: bool,
+    #[serde(default)]
+    pub expand_carousels: bool,
+    #[serde(default)]
+    pub sponsored: SponsoredFilter,
+    #[serde(default)]
+    pub max_count_by_default: bool,
+    #[serde(default)]
+    pub prefetch_pages: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub max_total_delay_ms: u64,
+    #[serde(default)]
+    pub jitter_ms: u64,
+}
+
+fn config_error(msg: String) -> RocketAPIError {
+    RocketAPIError::InvalidParameter(format!("config: {}", msg))
+}
+
+impl RocketAPIConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RocketAPIError> {
+        /*
+        Read a configuration file. The format is chosen by extension: `.toml` (requires the `toml` feature)
+        or `.json`.
+        */
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| config_error(format!("can't read {}: {}", path.display(), e)))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content).map_err(|e| config_error(e.to_string())),
+            #[cfg(feature = "toml")]
+            Some("toml") => toml::from_str(&content).map_err(|e| config_error(e.to_string())),
+            _ => Err(config_error(format!("unsupported config format: {}", path.display()))),
+        }
+    }
+
+    pub fn resolve_token(&self) -> Result<String, RocketAPIError> {
+        match (&self.token, &self.token_env) {
+            (Some(token), None) => Ok(token.clone()),
+            (None, Some(var)) => std::env::var(var).map_err(|_| config_error(format!("environment variable {} is not set", var))),
+            (Some(_), Some(_)) => Err(config_error("both token and token_env are set".to_string())),
+            (None, None) => Err(config_error("token or token_env is required".to_string())),
+        }
+    }
+
+    pub fn timeout(&self) -> Result<Duration, RocketAPIError> {
+        if !self.timeout_secs.is_finite() || self.timeout_secs <= 0.0 {
+            return Err(config_error(format!("timeout_secs must be positive, got {}", self.timeout_secs)));
+        }
+        Ok(Duration::from_secs_f64(self.timeout_secs))
+    }
+
+    pub fn retry_policy(&self) -> Result<RetryPolicy, RocketAPIError> {
+        match &self.retry {
+            Some(retry) => RetryPolicy::new(
+                retry.max_attempts,
+                Duration::from_millis(retry.base_delay_ms),
+                Duration::from_millis(retry.max_delay_ms),
+                Duration::from_millis(retry.max_total_delay_ms),
+            )
+            .map(|policy| policy.with_jitter(Duration::from_millis(retry.jitter_ms))),
+            None => Ok(RetryPolicy::none()),
+        }
+    }
+
+    pub(crate) fn build_api(&self) -> Result<RocketAPI, RocketAPIError> {
+        let mut api = RocketAPI::new(self.resolve_token()?, self.timeout()?);
+        if let Some(base_url) = &self.base_url {
+            api.set_base_url(base_url)?;
+        }
+        api.set_retry_policy(self.retry_policy()?)?;
+        api.set_strict(self.strict);
+        api.set_streaming_parse(self.streaming_parse);
+        Ok(api)
+    }
+}
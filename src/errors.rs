@@ -1,21 +1,131 @@
 use std::fmt;
 use std::error::Error;
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestContext {
+    /*
+    Request an error comes from, see `RocketAPI::set_error_context`.
+
+    Fields:
+        method (str): RocketAPI method, e.g. `instagram/user/get_info`
+        payload (serde_json::Value): Request payload (the token is sent in a header, never in the payload)
+    */
+    pub method: String,
+    pub payload: serde_json::Value,
+}
+
 #[derive(Debug)]
 pub enum RocketAPIError {
-    BadResponse(serde_json::Value),
+    BadResponse { status_code: i64, message: Option<String>, body: serde_json::Value },
     NotFound(serde_json::Value),
     RequestError(reqwest::Error),
+    Unauthorized(serde_json::Value),
+    InvalidParameter(String),
+    ParseError(serde_json::Error),
+    InvalidResponse { pointer: String, expected: String, response: serde_json::Value },
+    ChallengeRequired(serde_json::Value),
+    LoginRequired(serde_json::Value),
+    FeedbackRequired(serde_json::Value),
+    RateLimited { retry_after: Option<std::time::Duration>, response: serde_json::Value },
+    BudgetExceeded { spent: u64, budget: u64 },
+    Cancelled,
+    NonJsonResponse { http_status: u16, body: String },
+    IoError { path: std::path::PathBuf, error: std::io::Error },
+    // Opt-in wrapper rather than a context field on every variant: matching on the other variants stays the same
+    // whether `RocketAPI::set_error_context` is enabled or not, errors raised before a request (which have no
+    // context) need no placeholder, and the payload is only cloned when asked for. See `kind` and `context`.
+    WithContext { context: RequestContext, error: Box<RocketAPIError> },
+}
+
+impl RocketAPIError {
+    pub fn context(&self) -> Option<&RequestContext> {
+        // Request the error comes from, only set with `RocketAPI::set_error_context`.
+        match self {
+            RocketAPIError::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> &RocketAPIError {
+        /*
+        The error itself, without its request context. Match on this rather than on the error
+        when `RocketAPI::set_error_context` may be enabled:
+
+            match err.kind() {
+                RocketAPIError::NotFound(_) => ...,
+                _ => ...,
+            }
+        */
+        match self {
+            RocketAPIError::WithContext { error, .. } => error.kind(),
+            error => error,
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn variant_name(&self) -> &'static str {
+        match self.kind() {
+            RocketAPIError::BadResponse { .. } => "BadResponse",
+            RocketAPIError::NotFound(_) => "NotFound",
+            RocketAPIError::RequestError(_) => "RequestError",
+            RocketAPIError::Unauthorized(_) => "Unauthorized",
+            RocketAPIError::InvalidParameter(_) => "InvalidParameter",
+            RocketAPIError::ParseError(_) => "ParseError",
+            RocketAPIError::InvalidResponse { .. } => "InvalidResponse",
+            RocketAPIError::ChallengeRequired(_) => "ChallengeRequired",
+            RocketAPIError::LoginRequired(_) => "LoginRequired",
+            RocketAPIError::FeedbackRequired(_) => "FeedbackRequired",
+            RocketAPIError::RateLimited { .. } => "RateLimited",
+            RocketAPIError::BudgetExceeded { .. } => "BudgetExceeded",
+            RocketAPIError::Cancelled => "Cancelled",
+            RocketAPIError::NonJsonResponse { .. } => "NonJsonResponse",
+            RocketAPIError::IoError { .. } => "IoError",
+            RocketAPIError::WithContext { .. } => "WithContext",
+        }
+    }
+
+    pub(crate) fn with_context(self, context: Option<RequestContext>) -> RocketAPIError {
+        match (context, self) {
+            (Some(context), error) if !matches!(error, RocketAPIError::WithContext { .. }) => {
+                RocketAPIError::WithContext { context, error: Box::new(error) }
+            }
+            (_, error) => error,
+        }
+    }
 }
 
 impl fmt::Display for RocketAPIError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            RocketAPIError::BadResponse(msg) => write!(f, "BadResponse: {}", msg),
+            RocketAPIError::BadResponse { status_code, message: Some(message), .. } => write!(f, "BadResponse: status {}: {}", status_code, message),
+            RocketAPIError::BadResponse { status_code, message: None, .. } => write!(f, "BadResponse: status {}", status_code),
             RocketAPIError::NotFound(msg) => write!(f, "NotFound: {}", msg),
             RocketAPIError::RequestError(msg) => write!(f, "RequestError: {}", msg),
+            RocketAPIError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            RocketAPIError::InvalidParameter(msg) => write!(f, "InvalidParameter: {}", msg),
+            RocketAPIError::ParseError(msg) => write!(f, "ParseError: {}", msg),
+            RocketAPIError::InvalidResponse { pointer, expected, .. } => write!(f, "InvalidResponse: expected {} at {}", expected, pointer),
+            RocketAPIError::ChallengeRequired(msg) => write!(f, "ChallengeRequired: {}", msg),
+            RocketAPIError::LoginRequired(msg) => write!(f, "LoginRequired: {}", msg),
+            RocketAPIError::FeedbackRequired(msg) => write!(f, "FeedbackRequired: {}", msg),
+            RocketAPIError::RateLimited { retry_after: Some(delay), response } => write!(f, "RateLimited: retry after {:?}: {}", delay, response),
+            RocketAPIError::RateLimited { retry_after: None, response } => write!(f, "RateLimited: {}", response),
+            RocketAPIError::BudgetExceeded { spent, budget } => write!(f, "BudgetExceeded: {} of {} credits spent", spent, budget),
+            RocketAPIError::Cancelled => write!(f, "Cancelled: the request was cancelled"),
+            RocketAPIError::NonJsonResponse { http_status, body } if body.trim().is_empty() => write!(f, "NonJsonResponse: HTTP {} with an empty body", http_status),
+            RocketAPIError::NonJsonResponse { http_status, body } => write!(f, "NonJsonResponse: HTTP {}: {}", http_status, body),
+            RocketAPIError::IoError { path, error } => write!(f, "IoError: {}: {}", path.display(), error),
+            RocketAPIError::WithContext { context, error } => write!(f, "{} (method {}, payload {})", error, context.method, context.payload),
         }
     }
 }
 
-impl Error for RocketAPIError {}
+impl Error for RocketAPIError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RocketAPIError::WithContext { error, .. } => Some(error.as_ref()),
+            RocketAPIError::IoError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::runtime;
+
+// Shared by the clones of a client: once the gateway asks to back off, every clone waits.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RetryAfterGate {
+    until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RetryAfterGate {
+    pub(crate) fn close_for(&self, delay: Duration) {
+        // Concurrent 429s only ever push the reopening time further.
+        let reopen = Instant::now() + delay;
+        let mut until = self.until.lock().expect("retry-after gate mutex poisoned");
+        if until.is_none_or(|current| current < reopen) {
+            *until = Some(reopen);
+        }
+    }
+
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        let until = *self.until.lock().expect("retry-after gate mutex poisoned");
+        until.and_then(|until| until.checked_duration_since(Instant::now())).filter(|d| !d.is_zero())
+    }
+
+    pub(crate) async fn wait(&self) {
+        // Loop as the gate may be closed again by another request while sleeping.
+        while let Some(delay) = self.remaining() {
+            runtime::sleep(delay).await;
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crate::errors::RocketAPIError;
+use crate::models::{timestamp_seconds, UserSummary};
+use crate::urls::{parse_instagram_url, InstagramUrl};
+use serde_json::Value;
+
+// Body fields holding pagination cursors across endpoints.
+pub(crate) const CURSOR_FIELDS: [&str; 7] = ["next_max_id", "max_id", "next_min_id", "end_cursor", "next_max_child_cursor", "page_token", "rank_token"];
+
+// Envelope fields carrying advisory messages next to a successful body. `message` is left out: error
+// envelopes use it for the error itself.
+const WARNING_FIELDS: [&str; 4] = ["warning", "warnings", "notice", "deprecation"];
+
+fn usertags_of(media: &Value) -> impl Iterator<Item = UserSummary> + '_ {
+    media["usertags"]["in"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| serde_json::from_value(tag["user"].clone()).ok())
+}
+
+pub fn media_tagged_users(media: &Value) -> Vec<UserSummary> {
+    /*
+    Extract users tagged on a media item (people tags).
+
+    Args:
+        media (serde_json::Value): Media item, e.g. an element of `items` from `get_media_info` or `get_user_media`
+
+    Tags are read from `usertags.in`. For carousels, tags of every child are included as well,
+    each user is returned once. Media without tags (or with malformed tag entries) gives an empty list.
+    */
+    let mut users: Vec<UserSummary> = Vec::new();
+    let children = media["carousel_media"].as_array().into_iter().flatten();
+    for user in usertags_of(media).chain(children.flat_map(usertags_of)) {
+        if !users.iter().any(|u| u.pk == user.pk) {
+            users.push(user);
+        }
+    }
+    users
+}
+
+pub fn cursor_value(cursor: &Value) -> Option<String> {
+    /*
+    Normalize a pagination cursor field (e.g. `next_max_id`) to a string.
+
+    Cursors come as strings or numbers; null, missing and empty values mean there are no more pages.
+    */
+    match cursor {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+const MEDIA_EXTENSIONS: [&str; 7] = [".jpg", ".jpeg", ".png", ".webp", ".heic", ".mp4", ".mov"];
+
+fn largest_candidate(candidates: &Value) -> Option<String> {
+    candidates
+        .as_array()?
+        .iter()
+        .filter(|c| c["url"].is_string())
+        .max_by_key(|c| c["width"].as_u64().unwrap_or(0) * c["height"].as_u64().unwrap_or(0))
+        .and_then(|c| c["url"].as_str())
+        .map(str::to_string)
+}
+
+pub fn best_media_url(media: &Value) -> Option<String> {
+    /*
+    Pick the URL of the highest resolution version of a media item.
+
+    Args:
+        media (serde_json::Value): Media or story item
+
+    Videos are preferred over their cover images: `video_versions` is checked first, then `image_versions2.candidates`.
+    Returns `None` if the item has neither, see `best_media_url_with_fallback`.
+    */
+    largest_candidate(&media["video_versions"]).or_else(|| largest_candidate(&media["image_versions2"]["candidates"]))
+}
+
+pub fn find_any_media_url(media: &Value) -> Option<String> {
+    /*
+    Scan the whole value for the first string that looks like an image or video URL
+    (an http(s) URL whose path ends with a known extension, e.g. `.jpg` or `.mp4`).
+    */
+    match media {
+        Value::String(s) => {
+            let path = s.split(['?', '#']).next().unwrap_or("").to_ascii_lowercase();
+            let is_media = s.starts_with("http") && MEDIA_EXTENSIONS.iter().any(|ext| path.ends_with(ext));
+            is_media.then(|| s.clone())
+        }
+        Value::Array(items) => items.iter().find_map(find_any_media_url),
+        Value::Object(map) => map.values().find_map(find_any_media_url),
+        _ => None,
+    }
+}
+
+pub fn best_media_url_with_fallback(media: &Value) -> Option<String> {
+    /*
+    Same as `best_media_url`, but falls back to `find_any_media_url` when no candidates are found,
+    so downloads keep working if the candidate fields are renamed. The fallback URL is not guaranteed
+    to be the best quality one.
+    */
+    best_media_url(media).or_else(|| find_any_media_url(media))
+}
+
+pub fn media_taken_at(media: &Value) -> Option<u64> {
+    /*
+    Publication time of a media item (`taken_at`) in seconds since epoch. Milliseconds are converted.
+    */
+    timestamp_seconds(&media["taken_at"])
+}
+
+pub fn is_pinned(media: &Value) -> bool {
+    /*
+    Whether a media item is pinned to the top of the profile grid or the Reels tab.
+
+    Pinned items carry a non-empty `timeline_pinned_user_ids` (grid) or `clips_tab_pinned_user_ids` (Reels) list.
+    */
+    let pinned = |key: &str| media[key].as_array().is_some_and(|ids| !ids.is_empty());
+    pinned("timeline_pinned_user_ids") || pinned("clips_tab_pinned_user_ids")
+}
+
+pub fn is_sponsored(media: &Value) -> bool {
+    /*
+    Whether a media item is a paid partnership (branded content).
+
+    Such items have `is_paid_partnership` set or a non-empty `sponsor_tags` list (the brands shown in the
+    "Paid partnership with" header). Items without these fields are not sponsored.
+    */
+    media["is_paid_partnership"].as_bool().unwrap_or(false) || media["sponsor_tags"].as_array().is_some_and(|tags| !tags.is_empty())
+}
+
+// Post-level fields copied to carousel children that don't carry them.
+const CAROUSEL_INHERITED_FIELDS: [&str; 6] = ["code", "taken_at", "like_count", "comment_count", "user", "caption"];
+
+pub fn expand_carousel(media: &Value) -> Vec<Value> {
+    /*
+    Split a carousel post into its children, one item per image or video.
+
+    Args:
+        media (serde_json::Value): Media item, e.g. an element of `items` from `get_user_media`
+
+    Children keep their own `id`, `pk`, `media_type` and media URLs, post-level fields they lack
+    (`code`, `taken_at`, counters, `user`, `caption`) are copied from the post.
+    Any other item (or a carousel without children) is returned as is.
+    */
+    let children = match media["carousel_media"].as_array() {
+        Some(children) if !children.is_empty() => children,
+        _ => return vec![media.clone()],
+    };
+    children
+        .iter()
+        .map(|child| {
+            let mut child = child.clone();
+            if let Some(fields) = child.as_object_mut() {
+                for key in CAROUSEL_INHERITED_FIELDS {
+                    if !fields.contains_key(key) && !media[key].is_null() {
+                        fields.insert(key.to_string(), media[key].clone());
+                    }
+                }
+            }
+            child
+        })
+        .collect()
+}
+
+pub(crate) fn expand_page_carousels(page: &mut Value, items_key: &str) {
+    if let Some(items) = page[items_key].as_array_mut() {
+        *items = items.iter().flat_map(expand_carousel).collect();
+    }
+}
+
+const USERNAME_MAX_LENGTH: usize = 30;
+
+pub fn normalize_username(input: &str) -> Result<String, RocketAPIError> {
+    /*
+    Normalize a username as users type or paste it: `@natgeo`, ` NatGeo `, `https://www.instagram.com/natgeo/`.
+
+    Surrounding whitespace and a leading `@` are removed, profile URLs are reduced to the username,
+    and the result is lowercased. Returns `RocketAPIError::InvalidParameter` if the result is not a valid
+    username (1-30 characters: letters, digits, `.` and `_`).
+    */
+    let trimmed = input.trim();
+    let username = if trimmed.contains('/') {
+        let url = if trimmed.starts_with("http") { trimmed.to_string() } else { format!("https://{}", trimmed) };
+        match parse_instagram_url(&url) {
+            Some(InstagramUrl::Profile(username)) => username,
+            _ => return Err(RocketAPIError::InvalidParameter(format!("not a profile URL: {}", trimmed))),
+        }
+    } else {
+        trimmed.trim_start_matches('@').to_string()
+    };
+    let username = username.to_lowercase();
+    let valid_chars = username.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_');
+    if username.is_empty() || username.len() > USERNAME_MAX_LENGTH || !valid_chars {
+        return Err(RocketAPIError::InvalidParameter(format!("invalid username: {:?}", input)));
+    }
+    Ok(username)
+}
+
+const DESCRIBE_STRING_LIMIT: usize = 60;
+
+fn describe_scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) if s.chars().count() > DESCRIBE_STRING_LIMIT => {
+            format!("{:?}... ({} chars)", s.chars().take(DESCRIBE_STRING_LIMIT).collect::<String>(), s.chars().count())
+        }
+        Value::Array(items) => format!("array[{}]", items.len()),
+        Value::Object(map) => format!("object{{{} keys}}", map.len()),
+        other => other.to_string(),
+    }
+}
+
+fn describe_body(body: &Value, out: &mut Vec<String>) {
+    match body {
+        Value::Object(map) => {
+            out.push(format!("body: object{{{} keys}}", map.len()));
+            for (key, value) in map {
+                out.push(format!("  {}: {}", key, describe_scalar(value)));
+            }
+            let cursors: Vec<String> = CURSOR_FIELDS
+                .iter()
+                .filter_map(|field| cursor_value(&body[*field]).map(|cursor| format!("{}={:?}", field, cursor)))
+                .collect();
+            out.push(format!("cursors: {}", if cursors.is_empty() { "none".to_string() } else { cursors.join(", ") }));
+        }
+        other => out.push(format!("body: {}", describe_scalar(other))),
+    }
+}
+
+pub fn describe_value(value: &Value) -> String {
+    /*
+    Compact human readable summary of a response, handy for debugging and bug reports.
+
+    For a RocketAPI envelope (`last_response`) the envelope status, HTTP status and content type are shown,
+    then the body: top-level keys with array lengths and short scalar values, detected pagination cursors
+    and the total JSON size. Any other value is summarized as a body.
+    */
+    let mut out = Vec::new();
+    let body = if value["status"].is_string() && value["response"].is_object() {
+        let response = &value["response"];
+        out.push(format!("status: {}", describe_scalar(&value["status"])));
+        out.push(format!("http status: {}", describe_scalar(&response["status_code"])));
+        out.push(format!("content type: {}", describe_scalar(&response["content_type"])));
+        &response["body"]
+    } else {
+        value
+    };
+    describe_body(body, &mut out);
+    out.push(format!("size: {} bytes", value.to_string().len()));
+    out.join("\n")
+}
+
+pub fn response_warnings(response: &Value) -> Vec<String> {
+    /*
+    Non-fatal messages (deprecation notices, partial data warnings, ...) attached to a RocketAPI envelope.
+
+    Args:
+        response (serde_json::Value): Envelope, e.g. `last_response`
+
+    Looks at the top-level `warning`, `warnings`, `notice` and `deprecation` fields, which may hold a string
+    or a list of strings.
+    */
+    WARNING_FIELDS
+        .iter()
+        .flat_map(|field| match &response[*field] {
+            Value::String(s) if !s.is_empty() => vec![s.clone()],
+            Value::Array(items) => items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+pub fn user_pk(user: &Value) -> Option<u64> {
+    /*
+    Numeric id of a user object (`pk`, `pk_id` or `id`), whether encoded as a number or a string.
+    */
+    ["pk", "pk_id", "id"].iter().find_map(|key| match &user[*key] {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    })
+}
+
+pub fn schema_fingerprint(body: &Value) -> u64 {
+    /*
+    Fingerprint of the shape of a response body: a hash of its sorted top-level keys (0 for non-objects).
+
+    The hash (FNV-1a) is stable across builds, so fingerprints can be stored and used as baselines,
+    see `RocketAPI::set_schema_baselines`.
+    */
+    let mut keys: Vec<&str> = body.as_object().map(|map| map.keys().map(String::as_str).collect()).unwrap_or_default();
+    if keys.is_empty() {
+        return 0;
+    }
+    keys.sort_unstable();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in keys.join("\n").bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
@@ -1,4 +1,38 @@
 pub mod instagramapi;
+pub mod namespaces;
 pub mod threadsapi;
 pub mod errors;
-mod api;
\ No newline at end of file
+pub mod config;
+pub mod models;
+pub mod borrowed;
+pub mod helpers;
+pub mod pagination;
+pub mod retry;
+pub mod urls;
+pub mod transport;
+pub mod middleware;
+pub mod stats;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "test-util")]
+pub mod replay;
+#[cfg(feature = "tower")]
+pub mod service;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "stream")]
+pub mod batch;
+#[cfg(feature = "stream")]
+pub mod ndjson;
+mod api;
+mod body;
+mod builder;
+mod budget;
+mod coalesce;
+mod gate;
+mod limiter;
+mod runtime;
+mod trace;
+
+pub use api::{ErrorHook, HttpConfig, RawResponse, RequestHook, ResponseHook, RocketAPI, TimeoutScope, DEFAULT_BASE_URL};
+
@@ -0,0 +1,106 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use crate::errors::RocketAPIError;
+use crate::runtime;
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+// Token bucket shared by the clones of a client, so concurrent tasks draw from the same rate.
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    requests_per_second: f64,
+    burst: u32,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_second: f64, burst: u32) -> Result<Self, RocketAPIError> {
+        if !requests_per_second.is_finite() || requests_per_second <= 0.0 {
+            return Err(RocketAPIError::InvalidParameter(format!("invalid request rate: {}", requests_per_second)));
+        }
+        let burst = burst.max(1);
+        let bucket = Bucket { tokens: burst as f64, refilled_at: Instant::now() };
+        Ok(RateLimiter { requests_per_second, burst, bucket: Arc::new(Mutex::new(bucket)) })
+    }
+
+    pub(crate) fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    pub(crate) fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    fn reserve(&self) -> Duration {
+        // The token is taken right away (the bucket may go negative), so waiters are served in call order.
+        let mut bucket = self.bucket.lock().expect("rate limiter mutex poisoned");
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.refilled_at).as_secs_f64() * self.requests_per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst as f64) - 1.0;
+        bucket.refilled_at = now;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        }
+    }
+
+    pub(crate) async fn acquire(&self) {
+        let delay = self.reserve();
+        if !delay.is_zero() {
+            runtime::sleep(delay).await;
+        }
+    }
+}
+
+// Caps the requests in flight across the clones of a client, whichever way callers spawn them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InFlight {
+    limit: Option<(Arc<Semaphore>, usize)>,
+    active: Arc<AtomicUsize>,
+}
+
+pub(crate) struct InFlightGuard {
+    _permit: Option<OwnedSemaphorePermit>,
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl InFlight {
+    pub(crate) fn set_max(&mut self, max: Option<usize>) -> Result<(), RocketAPIError> {
+        // Requests already in flight keep the permits of the previous limit.
+        if max == Some(0) {
+            return Err(RocketAPIError::InvalidParameter("max_in_flight must be at least 1".to_string()));
+        }
+        self.limit = max.map(|max| (Arc::new(Semaphore::new(max)), max));
+        Ok(())
+    }
+
+    pub(crate) fn max(&self) -> Option<usize> {
+        self.limit.as_ref().map(|(_, max)| *max)
+    }
+
+    pub(crate) fn active(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    pub(crate) async fn enter(&self) -> InFlightGuard {
+        let permit = match &self.limit {
+            Some((semaphore, _)) => Some(semaphore.clone().acquire_owned().await.expect("in-flight semaphore closed")),
+            None => None,
+        };
+        self.active.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard { _permit: permit, active: self.active.clone() }
+    }
+}
@@ -0,0 +1,56 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use serde_json::Value;
+use crate::errors::RocketAPIError;
+
+/*
+Interceptor chain around the requests of `RocketAPI`, see `RocketAPI::add_interceptor`.
+
+Interceptors run in the order they were added, the first one being the outermost. Each one gets the method
+and payload of an attempt and decides what to do with them: change the payload, call `next.run(...)` to
+continue down the chain (the HTTP call, or the custom `Transport`, is the innermost handler), inspect or
+change the returned envelope, or answer on its own without calling `next` at all (caching, chaos testing).
+
+    struct ExperimentFlag;
+
+    impl RequestInterceptor for ExperimentFlag {
+        fn handle<'a>(&'a self, method: &'a str, mut payload: Value, next: Next<'a>) -> InterceptorFuture<'a> {
+            payload["experiment"] = json!("b");
+            next.run(method, payload)
+        }
+    }
+
+Interceptors see raw RocketAPI envelopes (`{"status": "done", "response": {...}}`), and run once per attempt,
+so retries go through the chain again. An envelope returned without calling `next` is treated as an HTTP 200.
+*/
+
+pub type InterceptorFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, RocketAPIError>> + Send + 'a>>;
+
+pub trait RequestInterceptor: Send + Sync {
+    fn handle<'a>(&'a self, method: &'a str, payload: Value, next: Next<'a>) -> InterceptorFuture<'a>;
+}
+
+pub(crate) type Handler<'a> = dyn Fn(&'a str, Value) -> InterceptorFuture<'a> + Send + Sync + 'a;
+
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    /*
+    Rest of the chain after the current interceptor. It can be run more than once (e.g. to retry).
+    */
+    chain: &'a [Arc<dyn RequestInterceptor>],
+    handler: &'a Handler<'a>,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(chain: &'a [Arc<dyn RequestInterceptor>], handler: &'a Handler<'a>) -> Self {
+        Next { chain, handler }
+    }
+
+    pub fn run(self, method: &'a str, payload: Value) -> InterceptorFuture<'a> {
+        match self.chain.split_first() {
+            Some((first, rest)) => first.handle(method, payload, Next { chain: rest, handler: self.handler }),
+            None => (self.handler)(method, payload),
+        }
+    }
+}
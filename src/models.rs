@@ -0,0 +1,627 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use crate::pagination::Cursor;
+
+/*
+Typed response models.
+
+Instagram is inconsistent about numeric ids: the same `pk` may arrive as a number or as a string
+depending on the endpoint, so every id field goes through `deserialize_id`.
+Unknown fields are ignored and missing optional fields fall back to `None`/defaults.
+*/
+
+pub(crate) fn deserialize_id<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_u64().ok_or_else(|| serde::de::Error::custom("id is not a positive integer")),
+        Value::String(s) => s.parse().map_err(serde::de::Error::custom),
+        other => Err(serde::de::Error::custom(format!("unexpected id value: {}", other))),
+    }
+}
+
+/*
+Unix timestamps (`taken_at`, `created_at`, ...) are kept as seconds since epoch whatever the features are.
+Both seconds and milliseconds encodings are accepted: values above 10^11 are treated as milliseconds.
+With the `time` feature the models also expose them as `time::OffsetDateTime` (UTC), e.g. `taken_at_datetime`.
+*/
+const MILLISECONDS_THRESHOLD: u64 = 100_000_000_000;
+
+pub(crate) fn timestamp_seconds(value: &Value) -> Option<u64> {
+    let raw = match value {
+        Value::Number(n) => n.as_u64().or_else(|| n.as_f64().filter(|f| *f >= 0.0).map(|f| f as u64)),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }?;
+    Some(if raw > MILLISECONDS_THRESHOLD { raw / 1000 } else { raw })
+}
+
+pub(crate) fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    if value.is_null() {
+        return Ok(None);
+    }
+    timestamp_seconds(&value)
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom(format!("unexpected timestamp value: {}", value)))
+}
+
+#[cfg(feature = "time")]
+fn datetime(seconds: Option<u64>) -> Option<time::OffsetDateTime> {
+    time::OffsetDateTime::from_unix_timestamp(i64::try_from(seconds?).ok()?).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaType {
+    /*
+    Value of the `media_type` field. Reels (clips) are videos.
+    */
+    Photo,
+    Video,
+    Carousel,
+}
+
+impl MediaType {
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            1 => Some(MediaType::Photo),
+            2 => Some(MediaType::Video),
+            8 => Some(MediaType::Carousel),
+            _ => None,
+        }
+    }
+
+    pub fn of(media: &Value) -> Option<Self> {
+        media["media_type"].as_u64().and_then(Self::from_code)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UserSummary {
+    /*
+    Compact user representation, as found in user lists, tags, likers, etc.
+    */
+    #[serde(deserialize_with = "deserialize_id")]
+    pub pk: u64,
+    pub username: String,
+    #[serde(default)]
+    pub full_name: Option<String>,
+    #[serde(default)]
+    pub is_private: Option<bool>,
+    #[serde(default)]
+    pub is_verified: Option<bool>,
+    #[serde(default)]
+    pub profile_pic_url: Option<String>,
+}
+
+// Counters are plain numbers in the private API and `{ "count": n }` edges in the web profile.
+fn deserialize_count<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(value.as_u64().or_else(|| value["count"].as_u64()))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UserInfo {
+    /*
+    Full profile, as returned by `InstagramAPI::get_user_info_typed`.
+
+    Follower and following counts are read from `follower_count`/`following_count` or from the
+    `edge_followed_by`/`edge_follow` edges of the web profile, whose `id` is used as `pk`.
+    */
+    #[serde(deserialize_with = "deserialize_id")]
+    pub pk: u64,
+    pub username: String,
+    #[serde(default)]
+    pub full_name: Option<String>,
+    #[serde(default, alias = "edge_followed_by", deserialize_with = "deserialize_count")]
+    pub follower_count: Option<u64>,
+    #[serde(default, alias = "edge_follow", deserialize_with = "deserialize_count")]
+    pub following_count: Option<u64>,
+    #[serde(default)]
+    pub is_private: Option<bool>,
+    #[serde(default)]
+    pub is_verified: Option<bool>,
+    #[serde(default)]
+    pub biography: Option<String>,
+    #[serde(default)]
+    pub profile_pic_url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NormalizedProfile {
+    /*
+    Profile in one shape whichever endpoint it comes from, see `NormalizedProfile::from_response`.
+
+    The web profile (`get_user_info`) and the mobile profile (`get_user_info_by_id`) name and nest
+    the same data differently; fields one of them lacks are `None`.
+
+    Fields:
+        pk (u64): User id (`pk`, or `id` in the web profile)
+        username (str): Username
+        full_name (str): Display name
+        biography (str): Bio
+        external_url (str): Link in bio
+        follower_count (u64): `follower_count` or `edge_followed_by.count`
+        following_count (u64): `following_count` or `edge_follow.count`
+        media_count (u64): `media_count` or `edge_owner_to_timeline_media.count`
+        is_private (bool): Private account
+        is_verified (bool): Verified badge
+        is_business (bool): `is_business` or `is_business_account`
+        category (str): `category` or `category_name`
+        profile_pic_url (str): Largest profile picture available (HD if present)
+    */
+    pub pk: u64,
+    pub username: String,
+    pub full_name: Option<String>,
+    pub biography: Option<String>,
+    pub external_url: Option<String>,
+    pub follower_count: Option<u64>,
+    pub following_count: Option<u64>,
+    pub media_count: Option<u64>,
+    pub is_private: Option<bool>,
+    pub is_verified: Option<bool>,
+    pub is_business: Option<bool>,
+    pub category: Option<String>,
+    pub profile_pic_url: Option<String>,
+}
+
+impl NormalizedProfile {
+    pub fn from_response(body: &Value) -> Option<Self> {
+        /*
+        Normalize a profile response body.
+
+        Args:
+            body (serde_json::Value): Body of `get_user_info` (`data.user`) or `get_user_info_by_id` (`user`),
+                a web profile (`graphql.user`) or a bare user object
+
+        Returns `None` if the body holds no user with an id and a username.
+        */
+        let user = [&body["data"]["user"], &body["user"], &body["graphql"]["user"]]
+            .into_iter()
+            .find(|user| user.is_object())
+            .unwrap_or(body);
+        Self::from_user(user)
+    }
+
+    fn from_user(user: &Value) -> Option<Self> {
+        let text = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| user[*key].as_str().filter(|s| !s.trim().is_empty()))
+                .map(str::to_string)
+        };
+        let count = |keys: &[&str]| keys.iter().find_map(|key| user[*key].as_u64().or_else(|| user[*key]["count"].as_u64()));
+        let flag = |keys: &[&str]| keys.iter().find_map(|key| user[*key].as_bool());
+        Some(NormalizedProfile {
+            pk: crate::helpers::user_pk(user)?,
+            username: text(&["username"])?,
+            full_name: text(&["full_name"]),
+            biography: text(&["biography"]),
+            external_url: text(&["external_url"]),
+            follower_count: count(&["follower_count", "edge_followed_by"]),
+            following_count: count(&["following_count", "edge_follow"]),
+            media_count: count(&["media_count", "edge_owner_to_timeline_media"]),
+            is_private: flag(&["is_private"]),
+            is_verified: flag(&["is_verified"]),
+            is_business: flag(&["is_business", "is_business_account"]),
+            category: text(&["category", "category_name"]),
+            profile_pic_url: user["hd_profile_pic_url_info"]["url"]
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| text(&["profile_pic_url_hd", "profile_pic_url"])),
+        })
+    }
+}
+
+// Instagram sends `""` rather than omitting unset profile fields (and numbers for some phone fields).
+fn deserialize_non_empty<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = match Value::deserialize(deserializer)? {
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+        _ => return Ok(None),
+    };
+    Ok(Some(value).filter(|s| !s.trim().is_empty()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BioLink {
+    pub url: String,
+    #[serde(default, deserialize_with = "deserialize_non_empty")]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct ContactInfo {
+    /*
+    Public links and contact details of a profile, see `InstagramAPI::get_user_contact_info`.
+    Email and phone are only set for business and creator accounts that chose to show them.
+    */
+    #[serde(default, deserialize_with = "deserialize_non_empty")]
+    pub external_url: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_bio_links")]
+    pub bio_links: Vec<BioLink>,
+    #[serde(default, deserialize_with = "deserialize_non_empty")]
+    pub public_email: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_non_empty")]
+    pub public_phone_number: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_non_empty")]
+    pub public_phone_country_code: Option<String>,
+}
+
+impl ContactInfo {
+    pub(crate) fn from_profile(user: &Value) -> Result<Self, serde_json::Error> {
+        // The web profile names the contact fields `business_email` and `business_phone_number`.
+        let mut info: ContactInfo = serde_json::from_value(user.clone())?;
+        let business = |key: &str| user[key].as_str().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+        info.public_email = info.public_email.or_else(|| business("business_email"));
+        info.public_phone_number = info.public_phone_number.or_else(|| business("business_phone_number"));
+        Ok(info)
+    }
+}
+
+fn deserialize_bio_links<'de, D>(deserializer: D) -> Result<Vec<BioLink>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    // `null` and malformed entries are dropped instead of failing the whole profile.
+    let value = Value::deserialize(deserializer)?;
+    Ok(value.as_array().into_iter().flatten()
+        .filter_map(|link| serde_json::from_value::<BioLink>(link.clone()).ok())
+        .filter(|link| !link.url.is_empty())
+        .collect())
+}
+
+// Threads users share the pk space of Instagram, so both use the same compact representation.
+pub type ThreadsUserShort = UserSummary;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchContext {
+    /*
+    Result type filter of `InstagramAPI::search_with` (Instagram's top search `context`).
+    */
+    Blended,
+    User,
+    Hashtag,
+    Place,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchRequest {
+    /*
+    Request of `InstagramAPI::search_with`. Serialized as the request payload, `context` is omitted when `None`.
+    */
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<SearchContext>,
+}
+
+impl SearchRequest {
+    pub fn new(query: impl Into<String>) -> Self {
+        SearchRequest { query: query.into(), context: None }
+    }
+
+    pub fn context(mut self, context: SearchContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SearchUsersPage {
+    /*
+    Page of `ThreadsAPI::search_users`.
+
+    To get the next page pass `rank_token` (taken from the first page) and `page_token` (taken from the
+    current page) back to `search_users`. There are no more pages when `has_more` is false or `page_token` is absent.
+    */
+    #[serde(default)]
+    pub users: Vec<ThreadsUserShort>,
+    #[serde(default)]
+    pub rank_token: Option<String>,
+    #[serde(default)]
+    pub page_token: Option<String>,
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StoryItem {
+    /*
+    Story (or highlight story) item.
+    */
+    pub id: String,
+    #[serde(deserialize_with = "deserialize_id")]
+    pub pk: u64,
+    #[serde(default)]
+    pub media_type: Option<u8>,
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    pub taken_at: Option<u64>,
+    #[serde(default)]
+    pub image_versions2: Option<Value>,
+    #[serde(default)]
+    pub video_versions: Option<Value>,
+}
+
+#[cfg(feature = "time")]
+impl StoryItem {
+    pub fn taken_at_datetime(&self) -> Option<time::OffsetDateTime> {
+        /*
+        `taken_at` as a UTC date time, `None` if it is missing or out of range.
+        */
+        datetime(self.taken_at)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThreadLikersPage {
+    /*
+    Page of `ThreadsAPI::get_thread_likes_typed`. Pass `cursor` back to get the next page.
+    `cursor` is a tagged `Cursor`, see `Cursor::value` for the string.
+    */
+    #[serde(default)]
+    pub users: Vec<ThreadsUserShort>,
+    #[serde(default, alias = "user_count")]
+    pub like_count: u64,
+    #[serde(default, rename = "next_max_id", deserialize_with = "deserialize_cursor")]
+    pub cursor: Option<Cursor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThreadCaption {
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThreadPost {
+    /*
+    Threads post (the `post` of a thread item).
+    */
+    #[serde(deserialize_with = "deserialize_id")]
+    pub pk: u64,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub user: Option<ThreadsUserShort>,
+    #[serde(default)]
+    pub caption: Option<ThreadCaption>,
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    pub taken_at: Option<u64>,
+    #[serde(default)]
+    pub like_count: Option<u64>,
+}
+
+#[cfg(feature = "time")]
+impl ThreadPost {
+    pub fn taken_at_datetime(&self) -> Option<time::OffsetDateTime> {
+        /*
+        `taken_at` as a UTC date time, `None` if it is missing or out of range.
+        */
+        datetime(self.taken_at)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadNode {
+    /*
+    Node of a conversation tree, see `ThreadsAPI::get_thread_conversation`.
+
+    Fields:
+        pk (u64): Post pk
+        post (Option<ThreadPost>): The post, `None` for an unavailable (deleted or not returned) parent
+            that some replies point to
+        replies (Vec<ThreadNode>): Direct replies, in API order
+    */
+    pub pk: u64,
+    pub post: Option<ThreadPost>,
+    pub replies: Vec<ThreadNode>,
+}
+
+impl ThreadNode {
+    pub fn is_unavailable(&self) -> bool {
+        self.post.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        /*
+        Number of available posts in this subtree, including this node.
+        */
+        usize::from(self.post.is_some()) + self.replies.iter().map(ThreadNode::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadConversation {
+    /*
+    Result of `ThreadsAPI::get_thread_conversation`.
+
+    Fields:
+        root (ThreadNode): The original post, with every reply nested under the post it answers
+        missing_parents (Vec<u64>): Pks of parents that are not part of the result (deleted, or on a page that
+            was not fetched). Each one is an unavailable node directly under the root, holding its replies
+        detached (Vec<ThreadPost>): Replies whose chain of parents loops without reaching the root (malformed
+            data), left out of the tree, sorted by pk
+    */
+    pub root: ThreadNode,
+    pub missing_parents: Vec<u64>,
+    pub detached: Vec<ThreadPost>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UsersPage {
+    /*
+    Page of a user list (followers, following). Pass `cursor` back to get the next page.
+    */
+    #[serde(default)]
+    pub users: Vec<UserSummary>,
+    #[serde(default, rename = "next_max_id", deserialize_with = "deserialize_cursor")]
+    pub cursor: Option<Cursor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MediaPage {
+    /*
+    Page of a media list (media, clips). Pass `cursor` back to get the next page.
+    */
+    #[serde(default)]
+    pub items: Vec<MediaInfo>,
+    #[serde(default, rename = "next_max_id", deserialize_with = "deserialize_cursor")]
+    pub cursor: Option<Cursor>,
+}
+
+pub(crate) fn deserialize_cursor<'de, D>(deserializer: D) -> Result<Option<Cursor>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(crate::helpers::cursor_value(&Value::deserialize(deserializer)?).map(Cursor::raw))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UserTag {
+    pub user: UserSummary,
+    #[serde(default)]
+    pub position: Option<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+pub struct UserTags {
+    #[serde(rename = "in", default)]
+    pub tags: Vec<UserTag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MediaInfo {
+    /*
+    Media item as returned by `get_media_info` and the media list endpoints.
+    */
+    pub id: String,
+    #[serde(deserialize_with = "deserialize_id")]
+    pub pk: u64,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub media_type: Option<u8>,
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    pub taken_at: Option<u64>,
+    #[serde(default)]
+    pub like_count: Option<u64>,
+    #[serde(default)]
+    pub comment_count: Option<u64>,
+    #[serde(default)]
+    pub usertags: Option<UserTags>,
+}
+
+impl MediaInfo {
+    pub fn tagged_users(&self) -> Vec<&UserSummary> {
+        /*
+        Users tagged on this media (the `usertags.in` array). Empty if nobody is tagged.
+        */
+        self.usertags.iter().flat_map(|tags| tags.tags.iter().map(|tag| &tag.user)).collect()
+    }
+
+    #[cfg(feature = "time")]
+    pub fn taken_at_datetime(&self) -> Option<time::OffsetDateTime> {
+        /*
+        `taken_at` as a UTC date time, `None` if it is missing or out of range.
+        */
+        datetime(self.taken_at)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FriendshipStatus {
+    /*
+    Relationship between two accounts, see `InstagramAPI::get_friendship_status`.
+
+    Fields:
+        following (bool): The source account follows the target
+        followed_by (bool): The target account follows the source
+    */
+    pub following: bool,
+    pub followed_by: bool,
+}
+
+impl FriendshipStatus {
+    pub fn is_mutual(&self) -> bool {
+        self.following && self.followed_by
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AccountInfo {
+    /*
+    Plan details of the RocketAPI token, see `RocketAPI::account_info`.
+
+    Fields:
+        plan (str): Plan name, from the `x-plan` header
+        quota_limit (u64): Requests allowed in the current quota period, from `x-ratelimit-limit`
+        quota_remaining (u64): Requests left in the current quota period, from `x-ratelimit-remaining`
+
+    Headers the gateway doesn't send are `None`.
+    */
+    pub plan: Option<String>,
+    pub quota_limit: Option<u64>,
+    pub quota_remaining: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Balance {
+    /*
+    Remaining credits of the RocketAPI token, see `RocketAPI::get_account_balance`.
+
+    Fields:
+        credits (f64): Requests left in the current quota period
+        plan (str): Plan name
+        requests_today (u64): Requests already made in the current quota period
+
+    Fields the plan doesn't report are `None`.
+    */
+    pub credits: Option<f64>,
+    pub plan: Option<String>,
+    pub requests_today: Option<u64>,
+}
+
+impl AccountInfo {
+    pub const PLAN_HEADER: &'static str = "x-plan";
+    pub const QUOTA_LIMIT_HEADER: &'static str = "x-ratelimit-limit";
+    pub const QUOTA_REMAINING_HEADER: &'static str = "x-ratelimit-remaining";
+
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        AccountInfo {
+            plan: header(Self::PLAN_HEADER),
+            quota_limit: header(Self::QUOTA_LIMIT_HEADER).and_then(|v| v.parse().ok()),
+            quota_remaining: header(Self::QUOTA_REMAINING_HEADER).and_then(|v| v.parse().ok()),
+        }
+    }
+}
+
+impl From<&AccountInfo> for Balance {
+    fn from(account: &AccountInfo) -> Self {
+        Balance {
+            credits: account.quota_remaining.map(|remaining| remaining as f64),
+            plan: account.plan.clone(),
+            requests_today: account.quota_limit.zip(account.quota_remaining).map(|(limit, remaining)| limit.saturating_sub(remaining)),
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use serde_json::Value;
+use crate::errors::RocketAPIError;
+use crate::instagramapi::{InstagramAPI, ProfileOverview};
+use crate::models::{ContactInfo, FriendshipStatus, MediaInfo, NormalizedProfile, MediaPage, StoryItem, UserInfo, UserSummary, UsersPage};
+use crate::pagination::{CollectResult, Cursor};
+#[cfg(feature = "stream")]
+use crate::stream::PageStream;
+
+/*
+Namespaced facade over `InstagramAPI`, mirroring the endpoint paths:
+
+    api.user().info("natgeo").await?;
+    api.user().followers(&user_id, None, None).await?;
+    api.user().stream_followers(&user_id); // with the `stream` feature
+    api.media().info_by_shortcode("Cx1a2b3").await?;
+    api.hashtag().media("rust", None, None).await?;
+    api.comment().replies(&comment_id, &media_id, None).await?;
+
+Sub-clients borrow the client and delegate to the flat methods (`api.user().info(..)` is `api.get_user_info(..)`),
+so both styles can be mixed and share `last_response`, `counter` and the other settings.
+*/
+
+pub struct UserClient<'a> {
+    /*
+    `instagram/user/...` endpoints, see `InstagramAPI::user`.
+    */
+    api: &'a mut InstagramAPI,
+}
+
+impl<'a> UserClient<'a> {
+    pub(crate) fn new(api: &'a mut InstagramAPI) -> Self {
+        UserClient { api }
+    }
+
+    pub async fn info(&mut self, username: &str) -> Result<Value, RocketAPIError> {
+        self.api.get_user_info(username).await
+    }
+
+    pub async fn info_typed(&mut self, username: &str) -> Result<UserInfo, RocketAPIError> {
+        self.api.get_user_info_typed(username).await
+    }
+
+    pub async fn normalized(&mut self, username: &str) -> Result<NormalizedProfile, RocketAPIError> {
+        self.api.get_normalized_profile(username).await
+    }
+
+    pub async fn normalized_by_id(&mut self, user_id: &u64) -> Result<NormalizedProfile, RocketAPIError> {
+        self.api.get_normalized_profile_by_id(user_id).await
+    }
+
+    pub async fn friendship(&mut self, source_id: &u64, target_id: &u64) -> Result<FriendshipStatus, RocketAPIError> {
+        self.api.get_friendship_status(source_id, target_id).await
+    }
+
+    pub async fn contact_info(&mut self, username: &str) -> Result<ContactInfo, RocketAPIError> {
+        self.api.get_user_contact_info(username).await
+    }
+
+    pub async fn info_timed(&mut self, username: &str) -> Result<(Value, std::time::Duration), RocketAPIError> {
+        self.api.get_user_info_timed(username).await
+    }
+
+    pub async fn info_by_id(&mut self, user_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_user_info_by_id(user_id).await
+    }
+
+    pub async fn is_verified(&mut self, username: &str) -> Result<bool, RocketAPIError> {
+        self.api.is_verified(username).await
+    }
+
+    pub async fn canonical_username(&mut self, username: &str) -> Result<String, RocketAPIError> {
+        self.api.canonical_username(username).await
+    }
+
+    pub async fn is_private(&mut self, user_id: &u64) -> Result<bool, RocketAPIError> {
+        self.api.is_private(user_id).await
+    }
+
+    pub async fn overview(&mut self, user_id: &u64, page_size: u8) -> Result<ProfileOverview, RocketAPIError> {
+        self.api.get_profile_overview(user_id, page_size).await
+    }
+
+    pub async fn about(&mut self, user_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_user_about(user_id).await
+    }
+
+    pub async fn media(&mut self, user_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_user_media(user_id, count, max_id).await
+    }
+
+    pub async fn media_page(&mut self, user_id: &u64, count: Option<u8>, cursor: Option<&Cursor>) -> Result<MediaPage, RocketAPIError> {
+        self.api.get_user_media_page(user_id, count, cursor).await
+    }
+
+    pub async fn all_media(&mut self, user_id: &u64, max_pages: Option<u32>) -> Result<CollectResult<MediaInfo>, RocketAPIError> {
+        self.api.get_all_user_media(user_id, max_pages).await
+    }
+
+    pub async fn pinned_media(&mut self, user_id: &u64) -> Result<Vec<Value>, RocketAPIError> {
+        self.api.get_user_pinned_media(user_id).await
+    }
+
+    pub async fn media_since(&mut self, user_id: &u64, since: i64, until: Option<i64>) -> Result<Vec<Value>, RocketAPIError> {
+        self.api.get_media_since(user_id, since, until).await
+    }
+
+    pub async fn clips(&mut self, user_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_user_clips(user_id, count, max_id).await
+    }
+
+    pub async fn clips_page(&mut self, user_id: &u64, count: Option<u8>, cursor: Option<&Cursor>) -> Result<MediaPage, RocketAPIError> {
+        self.api.get_user_clips_page(user_id, count, cursor).await
+    }
+
+    pub async fn guides(&mut self, user_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_user_guides(user_id, max_id).await
+    }
+
+    pub async fn tags(&mut self, user_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_user_tags(user_id, count, max_id).await
+    }
+
+    pub async fn following(&mut self, user_id: &u64, count: Option<u16>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_user_following(user_id, count, max_id).await
+    }
+
+    pub async fn following_page(&mut self, user_id: &u64, count: Option<u16>, cursor: Option<&Cursor>) -> Result<UsersPage, RocketAPIError> {
+        self.api.get_user_following_page(user_id, count, cursor).await
+    }
+
+    pub async fn all_following(&mut self, user_id: &u64, max_pages: Option<u32>) -> Result<CollectResult<UserSummary>, RocketAPIError> {
+        self.api.get_all_user_following(user_id, max_pages).await
+    }
+
+    pub async fn search_following(&mut self, user_id: &u64, query: &str) -> Result<Value, RocketAPIError> {
+        self.api.search_user_following(user_id, query).await
+    }
+
+    pub async fn followers(&mut self, user_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_user_followers(user_id, count, max_id).await
+    }
+
+    pub async fn followers_page(&mut self, user_id: &u64, count: Option<u8>, cursor: Option<&Cursor>) -> Result<UsersPage, RocketAPIError> {
+        self.api.get_user_followers_page(user_id, count, cursor).await
+    }
+
+    pub async fn all_followers(&mut self, user_id: &u64, max_pages: Option<u32>) -> Result<CollectResult<UserSummary>, RocketAPIError> {
+        self.api.get_all_user_followers(user_id, max_pages).await
+    }
+
+    pub async fn search_followers(&mut self, user_id: &u64, query: &str) -> Result<Value, RocketAPIError> {
+        self.api.search_user_followers(user_id, query).await
+    }
+
+    pub async fn stories(&mut self, user_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_user_stories(user_id).await
+    }
+
+    pub async fn stories_bulk(&mut self, user_ids: Vec<&u64>) -> Result<Value, RocketAPIError> {
+        self.api.get_user_stories_bulk(user_ids).await
+    }
+
+    pub async fn highlights(&mut self, user_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_user_highlights(user_id).await
+    }
+
+    pub async fn all_highlight_stories(&mut self, user_id: &u64) -> Result<Vec<StoryItem>, RocketAPIError> {
+        self.api.get_all_user_highlight_stories(user_id).await
+    }
+
+    pub async fn live(&mut self, user_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_user_live(user_id).await
+    }
+
+    pub async fn similar_accounts(&mut self, user_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_user_similar_accounts(user_id).await
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn stream_media(self, user_id: &u64, page_size: u8) -> PageStream<'a> {
+        self.api.stream_user_media(user_id, page_size)
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn stream_followers(self, user_id: &u64) -> PageStream<'a> {
+        self.api.stream_user_followers(user_id)
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn stream_media_since(self, user_id: &u64, since: i64, until: Option<i64>) -> PageStream<'a> {
+        self.api.stream_media_since(user_id, since, until)
+    }
+}
+
+pub struct MediaClient<'a> {
+    /*
+    `instagram/media/...` endpoints, see `InstagramAPI::media`.
+    */
+    api: &'a mut InstagramAPI,
+}
+
+impl<'a> MediaClient<'a> {
+    pub(crate) fn new(api: &'a mut InstagramAPI) -> Self {
+        MediaClient { api }
+    }
+
+    pub async fn info(&mut self, media_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_media_info(media_id).await
+    }
+
+    pub async fn info_by_shortcode(&mut self, shortcode: &str) -> Result<Value, RocketAPIError> {
+        self.api.get_media_info_by_shortcode(shortcode).await
+    }
+
+    pub async fn likes(&mut self, shortcode: &str, count: Option<u8>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_media_likes(shortcode, count, max_id).await
+    }
+
+    pub async fn comments(&mut self, media_id: &u64, can_support_threading: Option<bool>, min_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_media_comments(media_id, can_support_threading, min_id).await
+    }
+
+    pub async fn shortcode_by_id(&mut self, media_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_media_shortcode_by_id(media_id).await
+    }
+
+    pub async fn id_by_shortcode(&mut self, shortcode: &str) -> Result<Value, RocketAPIError> {
+        self.api.get_media_id_by_shortcode(shortcode).await
+    }
+
+    pub async fn insights(&mut self, media_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_media_insights(media_id).await
+    }
+}
+
+pub struct CommentClient<'a> {
+    /*
+    `instagram/comment/...` endpoints, see `InstagramAPI::comment`.
+    */
+    api: &'a mut InstagramAPI,
+}
+
+impl<'a> CommentClient<'a> {
+    pub(crate) fn new(api: &'a mut InstagramAPI) -> Self {
+        CommentClient { api }
+    }
+
+    pub async fn likes(&mut self, comment_id: &u64, count: Option<u8>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_comment_likes(comment_id, count, max_id).await
+    }
+
+    pub async fn replies(&mut self, comment_id: &u64, media_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_comment_replies(comment_id, media_id, max_id).await
+    }
+
+    pub async fn all_replies(&mut self, comment_id: &u64, media_id: &u64, max_items: usize) -> Result<Vec<Value>, RocketAPIError> {
+        self.api.get_all_comment_replies(comment_id, media_id, max_items).await
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn stream_likes(self, comment_id: &u64) -> PageStream<'a> {
+        self.api.stream_comment_likes(comment_id)
+    }
+}
+
+pub struct HashtagClient<'a> {
+    /*
+    `instagram/hashtag/...` endpoints, see `InstagramAPI::hashtag`.
+    */
+    api: &'a mut InstagramAPI,
+}
+
+impl<'a> HashtagClient<'a> {
+    pub(crate) fn new(api: &'a mut InstagramAPI) -> Self {
+        HashtagClient { api }
+    }
+
+    pub async fn info(&mut self, name: &str) -> Result<Value, RocketAPIError> {
+        self.api.get_hashtag_info(name).await
+    }
+
+    pub async fn media(&mut self, name: &str, page: Option<&u64>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_hashtag_media(name, page, max_id).await
+    }
+}
+
+pub struct LocationClient<'a> {
+    /*
+    `instagram/location/...` endpoints, see `InstagramAPI::location`.
+    */
+    api: &'a mut InstagramAPI,
+}
+
+impl<'a> LocationClient<'a> {
+    pub(crate) fn new(api: &'a mut InstagramAPI) -> Self {
+        LocationClient { api }
+    }
+
+    pub async fn info(&mut self, location_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_location_info(location_id).await
+    }
+
+    pub async fn media(&mut self, location_id: &u64, page: Option<&u64>, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_location_media(location_id, page, max_id).await
+    }
+}
+
+pub struct HighlightClient<'a> {
+    /*
+    `instagram/highlight/...` endpoints, see `InstagramAPI::highlight`.
+    */
+    api: &'a mut InstagramAPI,
+}
+
+impl<'a> HighlightClient<'a> {
+    pub(crate) fn new(api: &'a mut InstagramAPI) -> Self {
+        HighlightClient { api }
+    }
+
+    pub async fn stories(&mut self, highlight_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_highlight_stories(highlight_id).await
+    }
+
+    pub async fn stories_bulk(&mut self, highlight_ids: Vec<&u64>) -> Result<Value, RocketAPIError> {
+        self.api.get_highlight_stories_bulk(highlight_ids).await
+    }
+}
+
+pub struct GuideClient<'a> {
+    /*
+    `instagram/guide/...` endpoints, see `InstagramAPI::guide`.
+    */
+    api: &'a mut InstagramAPI,
+}
+
+impl<'a> GuideClient<'a> {
+    pub(crate) fn new(api: &'a mut InstagramAPI) -> Self {
+        GuideClient { api }
+    }
+
+    pub async fn info(&mut self, guide_id: &u64) -> Result<Value, RocketAPIError> {
+        self.api.get_guide_info(guide_id).await
+    }
+}
+
+pub struct AudioClient<'a> {
+    /*
+    `instagram/audio/...` endpoints, see `InstagramAPI::audio`.
+    */
+    api: &'a mut InstagramAPI,
+}
+
+impl<'a> AudioClient<'a> {
+    pub(crate) fn new(api: &'a mut InstagramAPI) -> Self {
+        AudioClient { api }
+    }
+
+    pub async fn media(&mut self, audio_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
+        self.api.get_audio_media(audio_id, max_id).await
+    }
+}
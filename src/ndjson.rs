@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::errors::RocketAPIError;
+
+/*
+Archives of fetched items as NDJSON (one JSON document per line), requires the `stream` feature.
+
+    export_ndjson("followers.ndjson", &page["users"].as_array().unwrap())?;
+    let users = import_ndjson::<UserSummary>("followers.ndjson")?;
+
+Imported lines go through the same typed models as live responses, so archived data can be processed
+without requesting it again.
+*/
+
+fn file_error(path: &Path, err: std::io::Error) -> RocketAPIError {
+    RocketAPIError::InvalidParameter(format!("{}: {}", path.display(), err))
+}
+
+#[derive(Debug)]
+pub struct LineError {
+    /*
+    Line of an NDJSON file that couldn't be read or deserialized, see `import_ndjson`.
+
+    Fields:
+        line (usize): Line number, starting at 1
+        error (RocketAPIError): `ParseError` for malformed lines, `InvalidParameter` for read errors
+    */
+    pub line: usize,
+    pub error: RocketAPIError,
+}
+
+impl std::fmt::Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+pub fn export_ndjson<T: Serialize>(path: impl AsRef<Path>, items: impl IntoIterator<Item = T>) -> Result<usize, RocketAPIError> {
+    /*
+    Write items to an NDJSON file, replacing it if it exists.
+
+    Args:
+        path: File to write
+        items: Items to write, e.g. the `items` of media pages or typed models
+
+    Returns the number of lines written.
+    */
+    let path = path.as_ref();
+    let mut file = BufWriter::new(File::create(path).map_err(|e| file_error(path, e))?);
+    let mut written = 0;
+    for item in items {
+        serde_json::to_writer(&mut file, &item).map_err(RocketAPIError::ParseError)?;
+        file.write_all(b"\n").map_err(|e| file_error(path, e))?;
+        written += 1;
+    }
+    file.flush().map_err(|e| file_error(path, e))?;
+    Ok(written)
+}
+
+pub fn import_ndjson<T>(path: impl AsRef<Path>) -> Result<BoxStream<'static, Result<T, LineError>>, RocketAPIError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    /*
+    Stream the items of an NDJSON file deserialized into `T`.
+
+    Args:
+        path: File written by `export_ndjson` (or any NDJSON file)
+
+    Blank lines are skipped. A malformed line yields a `LineError` and the stream goes on with the next line.
+    Returns an error right away only if the file can't be opened. Lines are read as the stream is polled.
+    */
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| file_error(path, e))?;
+    let path = path.to_path_buf();
+    // Invalid UTF-8 only spoils its own line, other read errors end the stream.
+    let lines = BufReader::new(file).lines().enumerate().scan(false, move |failed, (index, line)| {
+        if *failed {
+            return None;
+        }
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => return Some(None),
+            Ok(line) => line,
+            Err(err) => {
+                *failed = err.kind() != std::io::ErrorKind::InvalidData;
+                return Some(Some(Err(LineError { line: index + 1, error: file_error(&path, err) })));
+            }
+        };
+        Some(Some(serde_json::from_str(&line).map_err(|err| LineError { line: index + 1, error: RocketAPIError::ParseError(err) })))
+    });
+    Ok(stream::iter(lines.flatten()).boxed())
+}
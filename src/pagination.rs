@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use crate::errors::RocketAPIError;
+use crate::helpers::cursor_value;
+
+/*
+Helpers for manual pagination loops.
+
+A page fetcher is a closure receiving the client and the cursor of the page to fetch (`None` for the first one).
+Wrap it in `page_fetcher` so that the returned future may borrow the client:
+
+    page_fetcher(move |api: &mut InstagramAPI, max_id: Option<String>| {
+        Box::pin(async move { api.get_user_followers(&user_id, None, max_id.as_deref()).await })
+    })
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cursor {
+    /*
+    Pagination cursor tagged with the endpoint that produced it.
+
+    Typed page methods return tagged cursors and refuse a cursor of another endpoint with
+    `RocketAPIError::InvalidParameter` (e.g. a followers cursor passed to a clips request), instead of
+    silently returning confusing results. `Cursor::raw` creates an untagged cursor accepted by any endpoint.
+    */
+    endpoint: Option<String>,
+    value: String,
+}
+
+impl Cursor {
+    pub fn new(endpoint: &str, value: String) -> Self {
+        Cursor { endpoint: Some(endpoint.to_string()), value }
+    }
+
+    pub fn raw(value: impl Into<String>) -> Self {
+        Cursor { endpoint: None, value: value.into() }
+    }
+
+    pub fn endpoint(&self) -> Option<&str> {
+        self.endpoint.as_deref()
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub(crate) fn tagged(self, endpoint: &str) -> Self {
+        Cursor::new(endpoint, self.value)
+    }
+
+    pub fn check(&self, endpoint: &str) -> Result<&str, RocketAPIError> {
+        /*
+        Return the cursor value if it may be used with `endpoint`.
+        */
+        match &self.endpoint {
+            Some(own) if own != endpoint => Err(RocketAPIError::InvalidParameter(format!(
+                "cursor of {} can't be used with {}", own, endpoint
+            ))),
+            _ => Ok(&self.value),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Cursor {
+    // Deserialized cursors are untagged, typed page methods tag them with their endpoint.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        cursor_value(&value).map(Cursor::raw).ok_or_else(|| serde::de::Error::custom("empty cursor"))
+    }
+}
+
+pub(crate) fn checked_cursor<'a>(cursor: Option<&'a Cursor>, endpoint: &str) -> Result<Option<&'a str>, RocketAPIError> {
+    cursor.map(|c| c.check(endpoint)).transpose()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorKind {
+    /*
+    Pagination convention of an endpoint.
+
+    Variants:
+        NextMaxId: `next_max_id` cursor with an optional `more_available` flag (most private API endpoints)
+        EndCursor: GraphQL `page_info { end_cursor, has_next_page }`, possibly nested in the response
+        Downwards: `paging_tokens.downwards` (Threads replies)
+        PageToken: `page_token` with an optional `has_more` flag (Threads search)
+    */
+    NextMaxId,
+    EndCursor,
+    Downwards,
+    PageToken,
+}
+
+// Keys of the items array in list responses, in lookup order.
+const ITEM_FIELDS: [&str; 5] = ["items", "users", "comments", "reply_threads", "data"];
+
+// Object holding a GraphQL `page_info` (and the `edges` next to it), possibly nested in the response.
+fn find_connection(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Object(map) if map.get("page_info").is_some_and(Value::is_object) => Some(value),
+        Value::Object(map) => map.values().find_map(find_connection),
+        Value::Array(items) => items.iter().find_map(find_connection),
+        _ => None,
+    }
+}
+
+fn find_page_info(value: &Value) -> Option<&Value> {
+    find_connection(value).map(|connection| &connection["page_info"])
+}
+
+pub fn next_cursor(response: &Value, cursor_kind: CursorKind) -> Option<String> {
+    /*
+    Cursor of the next page, `None` if `response` is the last page (see `is_last_page`).
+    */
+    let (cursor, flag) = match cursor_kind {
+        CursorKind::NextMaxId => (&response["next_max_id"], &response["more_available"]),
+        CursorKind::Downwards => (&response["paging_tokens"]["downwards"], &Value::Null),
+        CursorKind::PageToken => (&response["page_token"], &response["has_more"]),
+        CursorKind::EndCursor => match find_page_info(response) {
+            Some(info) => (&info["end_cursor"], &info["has_next_page"]),
+            None => return None,
+        },
+    };
+    // An explicit `false` flag wins over a leftover cursor, a missing cursor wins over a `true` flag.
+    if *flag == false || response["more_available"] == false || response["has_next_page"] == false {
+        return None;
+    }
+    cursor_value(cursor)
+}
+
+pub fn is_last_page(response: &Value, cursor_kind: CursorKind) -> bool {
+    /*
+    Whether no page follows `response`.
+
+    Args:
+        response (serde_json::Value): Response body of a paginated endpoint
+        cursor_kind (CursorKind): Pagination convention of the endpoint
+
+    A page is the last one if its cursor is missing, null or empty, or if a `more_available`, `has_more` or
+    `has_next_page` flag is `false` (even when a cursor is still present). A `true` flag without a cursor
+    is the last page as well, since there is nothing to continue with.
+    */
+    next_cursor(response, cursor_kind).is_none()
+}
+
+// Endpoints paginated by `max_id` together with a `page` number.
+const PAGE_NUMBER_ENDPOINTS: [&str; 2] = ["instagram/location/get_media", "instagram/hashtag/get_media"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageState {
+    /*
+    Everything needed to request the page following a response, see `params_to_cursor`.
+
+    Fields:
+        cursor (Cursor): `max_id` of the next page, tagged with the endpoint
+        page (u64): `page` number of the next page, only set for location and hashtag media
+    */
+    pub cursor: Cursor,
+    pub page: Option<u64>,
+}
+
+pub fn cursor_to_params(endpoint: &str, response: &Value) -> BTreeMap<String, String> {
+    /*
+    Paging state of `response` as flat query parameters (`max_id`, and `page` for location and hashtag media),
+    e.g. to echo them in a REST response. Empty if `response` is the last page.
+
+    Args:
+        endpoint (str): Endpoint that returned `response` (e.g. `instagram/location/get_media`)
+        response (serde_json::Value): Response body
+    */
+    let mut params = BTreeMap::new();
+    let Some(max_id) = next_cursor(response, CursorKind::NextMaxId) else {
+        return params;
+    };
+    if PAGE_NUMBER_ENDPOINTS.contains(&endpoint) {
+        if let Some(page) = cursor_value(&response["next_page"]) {
+            params.insert("page".to_string(), page);
+        }
+    }
+    params.insert("max_id".to_string(), max_id);
+    params
+}
+
+pub fn params_to_cursor<K, V>(endpoint: &str, params: impl IntoIterator<Item = (K, V)>) -> Result<Option<PageState>, RocketAPIError>
+where
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    /*
+    Inverse of `cursor_to_params`: paging state from query parameters, `None` for the first page.
+
+    Args:
+        endpoint (str): Endpoint the parameters are meant for
+        params: Query parameters, other keys are ignored
+
+    Returns `RocketAPIError::InvalidParameter` if `page` isn't a number, or if location and hashtag media
+    don't get both `max_id` and `page` (or neither).
+    */
+    let (mut max_id, mut page) = (None, None);
+    for (key, value) in params {
+        match key.as_ref() {
+            "max_id" if !value.as_ref().is_empty() => max_id = Some(value.as_ref().to_string()),
+            "page" => {
+                let number = value.as_ref().parse::<u64>()
+                    .map_err(|_| RocketAPIError::InvalidParameter(format!("invalid page number: {}", value.as_ref())))?;
+                page = Some(number);
+            }
+            _ => {}
+        }
+    }
+    let needs_page = PAGE_NUMBER_ENDPOINTS.contains(&endpoint);
+    match (max_id, page) {
+        (None, None) => Ok(None),
+        (Some(max_id), page) if page.is_some() == needs_page => Ok(Some(PageState { cursor: Cursor::new(endpoint, max_id), page })),
+        _ if needs_page => Err(RocketAPIError::InvalidParameter(format!("{} needs both max_id and page", endpoint))),
+        _ => Err(RocketAPIError::InvalidParameter(format!("{} is not paginated by page number", endpoint))),
+    }
+}
+
+pub fn split_page(response: &Value, cursor_kind: CursorKind) -> (Vec<Value>, Option<String>) {
+    /*
+    Items and next cursor of a list response.
+
+    Args:
+        response (serde_json::Value): Response body of a paginated endpoint
+        cursor_kind (CursorKind): Pagination convention of the endpoint
+
+    Items are read from the first array among `items`, `users`, `comments`, `reply_threads` and `data`
+    (GraphQL responses: the `edges` next to `page_info`). The cursor is `None` on the last page, see `is_last_page`.
+    */
+    let items = ITEM_FIELDS
+        .iter()
+        .find_map(|key| response[*key].as_array())
+        .or_else(|| find_connection(response).and_then(|connection| connection["edges"].as_array()))
+        .cloned()
+        .unwrap_or_default();
+    (items, next_cursor(response, cursor_kind))
+}
+
+pub type PageFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, RocketAPIError>> + Send + 'a>>;
+
+pub fn page_fetcher<C, F>(fetch: F) -> F
+where
+    F: for<'b> FnMut(&'b mut C, Option<String>) -> PageFuture<'b>,
+{
+    fetch
+}
+
+pub async fn find_in_pages<C, F, P>(
+    client: &mut C,
+    mut fetch: F,
+    items_key: &str,
+    cursor_key: &str,
+    predicate: P,
+    max_pages: u32,
+) -> Result<Option<Value>, RocketAPIError>
+where
+    F: for<'b> FnMut(&'b mut C, Option<String>) -> PageFuture<'b>,
+    P: Fn(&Value) -> bool,
+{
+    /*
+    Paginate until an item matching `predicate` is found.
+
+    Args:
+        client: Client passed to `fetch`
+        fetch: Page fetcher, see the module documentation
+        items_key (str): Key of the items array in the page (e.g. `users`, `items`)
+        cursor_key (str): Key of the next cursor in the page (e.g. `next_max_id`)
+        predicate: Returns true for the wanted item
+        max_pages (u32): Maximum number of pages (requests) to fetch
+
+    Returns the first matching item, or `None` if the pages ran out (no cursor, or the same cursor again)
+    or `max_pages` was reached. No page is requested after the one containing the match.
+    */
+    let mut cursor: Option<String> = None;
+    for _ in 0..max_pages {
+        let previous = cursor.take();
+        let page = fetch(client, previous.clone()).await?;
+        if let Some(item) = page[items_key].as_array().and_then(|items| items.iter().find(|item| predicate(item))) {
+            return Ok(Some(item.clone()));
+        }
+        match cursor_value(&page[cursor_key]) {
+            Some(next) if page["more_available"] != false && Some(&next) != previous.as_ref() => cursor = Some(next),
+            _ => break,
+        }
+    }
+    Ok(None)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectResult<T> {
+    /*
+    Items collected across pages, with crawl statistics.
+
+    Fields:
+        items (Vec<T>): Collected items, in API order
+        pages_fetched (u32): Number of pages fetched
+        requests_made (u32): Number of page requests made, one per fetched page (retries and requests made
+            by the fetcher besides the page itself are not counted, see `InstagramAPI::requests_made` for those)
+        truncated (bool): True if the crawl stopped because of the page limit while more pages were available
+    */
+    pub items: Vec<T>,
+    pub pages_fetched: u32,
+    pub requests_made: u32,
+    pub truncated: bool,
+}
+
+pub async fn collect_pages<C, F, T>(
+    client: &mut C,
+    mut fetch: F,
+    items_key: &str,
+    cursor_key: &str,
+    max_pages: Option<u32>,
+) -> Result<CollectResult<T>, RocketAPIError>
+where
+    F: for<'b> FnMut(&'b mut C, Option<String>) -> PageFuture<'b>,
+    T: DeserializeOwned,
+{
+    /*
+    Fetch pages until the last one (or `max_pages`) and deserialize all items into `T`.
+
+    Args:
+        client: Client passed to `fetch`
+        fetch: Page fetcher, see the module documentation
+        items_key (str): Key of the items array in the page (e.g. `users`, `items`)
+        cursor_key (str): Key of the next cursor in the page (e.g. `next_max_id`)
+        max_pages (u32): Maximum number of pages to fetch, `None` for all
+
+    The first error (request or deserialization) is returned.
+    */
+    let mut result = CollectResult { items: Vec::new(), pages_fetched: 0, requests_made: 0, truncated: false };
+    let mut cursor: Option<String> = None;
+    loop {
+        if max_pages.is_some_and(|max| result.pages_fetched >= max) {
+            result.truncated = true;
+            break;
+        }
+        let previous = cursor.take();
+        let page = fetch(client, previous.clone()).await?;
+        result.pages_fetched += 1;
+        result.requests_made += 1;
+        for item in page[items_key].as_array().into_iter().flatten() {
+            result.items.push(serde_json::from_value(item.clone()).map_err(RocketAPIError::ParseError)?);
+        }
+        match cursor_value(&page[cursor_key]) {
+            Some(next) if page["more_available"] != false && Some(&next) != previous.as_ref() => cursor = Some(next),
+            _ => break,
+        }
+    }
+    Ok(result)
+}
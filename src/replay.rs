@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::api::RocketAPI;
+use crate::errors::RocketAPIError;
+use crate::transport::{Transport, TransportFuture};
+
+/*
+Record and replay of RocketAPI responses for deterministic tests (requires the `test-util` feature).
+
+    // Record once against the live API...
+    let transport = ReplayTransport::record("fixtures/natgeo.json", RocketAPI::new(token.clone(), timeout));
+    let mut instagram_api = InstagramAPI::new(token.clone(), timeout);
+    instagram_api.api = RocketAPI::with_transport(token, timeout, Arc::new(transport));
+    // ...then replay without network access or quota.
+    let transport = ReplayTransport::replay("fixtures/natgeo.json")?;
+
+Responses are keyed by method and payload. The file is a JSON array of `{"method", "payload", "response"}`
+entries holding raw envelopes, so it can be edited by hand.
+*/
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Recording {
+    method: String,
+    payload: Value,
+    response: Value,
+}
+
+fn recording_key(method: &str, payload: &Value) -> String {
+    // serde_json keeps object keys sorted, so equal payloads give the same key.
+    format!("{} {}", method, payload)
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> RocketAPIError {
+    RocketAPIError::IoError { path: path.to_path_buf(), error }
+}
+
+enum Mode {
+    Record(Box<RocketAPI>),
+    Replay,
+}
+
+struct Recordings {
+    entries: Vec<Recording>,
+    index: HashMap<String, usize>,
+}
+
+pub struct ReplayTransport {
+    path: PathBuf,
+    mode: Mode,
+    recordings: Arc<Mutex<Recordings>>,
+}
+
+impl ReplayTransport {
+    pub fn record(path: impl AsRef<Path>, live: RocketAPI) -> Self {
+        /*
+        Transport forwarding every request to `live` and writing the envelopes to `path`.
+
+        The file is rewritten after every response, so a test that stops half-way keeps what it recorded.
+        A request recorded twice keeps the latest response.
+        */
+        let recordings = Recordings { entries: Vec::new(), index: HashMap::new() };
+        ReplayTransport { path: path.as_ref().to_path_buf(), mode: Mode::Record(Box::new(live)), recordings: Arc::new(Mutex::new(recordings)) }
+    }
+
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, RocketAPIError> {
+        /*
+        Transport answering from a recording. A request missing from the file fails with
+        `RocketAPIError::InvalidParameter`, no live request is made. An unreadable file gives
+        `RocketAPIError::IoError`, a malformed one `RocketAPIError::ParseError`.
+        */
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+        let entries: Vec<Recording> = serde_json::from_str(&content).map_err(RocketAPIError::ParseError)?;
+        let index = entries.iter().enumerate().map(|(i, r)| (recording_key(&r.method, &r.payload), i)).collect();
+        let recordings = Recordings { entries, index };
+        Ok(ReplayTransport { path: path.to_path_buf(), mode: Mode::Replay, recordings: Arc::new(Mutex::new(recordings)) })
+    }
+
+    pub fn len(&self) -> usize {
+        self.recordings.lock().expect("replay mutex poisoned").entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn save(path: &Path, recordings: &Mutex<Recordings>, recording: Recording) -> Result<(), RocketAPIError> {
+    let mut recordings = recordings.lock().expect("replay mutex poisoned");
+    let key = recording_key(&recording.method, &recording.payload);
+    match recordings.index.get(&key) {
+        Some(&i) => recordings.entries[i] = recording,
+        None => {
+            let i = recordings.entries.len();
+            recordings.entries.push(recording);
+            recordings.index.insert(key, i);
+        }
+    }
+    let content = serde_json::to_string_pretty(&recordings.entries).map_err(RocketAPIError::ParseError)?;
+    std::fs::write(path, content).map_err(|e| io_error(path, e))
+}
+
+impl Transport for ReplayTransport {
+    fn call(&self, method: &str, payload: Value) -> TransportFuture {
+        let path = self.path.clone();
+        let recordings = self.recordings.clone();
+        match &self.mode {
+            Mode::Record(live) => {
+                let live = RocketAPI::clone(live);
+                let method = method.to_string();
+                Box::pin(async move {
+                    let response = live.request(&method, payload.clone()).await?;
+                    save(&path, &recordings, Recording { method, payload, response: response.clone() })?;
+                    Ok(response)
+                })
+            }
+            Mode::Replay => {
+                let key = recording_key(method, &payload);
+                let result = {
+                    let recordings = recordings.lock().expect("replay mutex poisoned");
+                    match recordings.index.get(&key) {
+                        Some(&i) => Ok(recordings.entries[i].response.clone()),
+                        None => Err(RocketAPIError::InvalidParameter(format!("replay {}: no recorded response for {}", path.display(), key))),
+                    }
+                };
+                Box::pin(async move { result })
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::errors::RocketAPIError;
+use reqwest::header::HeaderMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /*
+    Retry policy for transient failures (connection errors, timeouts, gateway 5xx).
+
+    Fields:
+        max_attempts (u32): Total number of attempts, including the first one. `1` disables retries.
+        base_delay (Duration): Delay before the first retry, doubled for every following one.
+        max_delay (Duration): Cap of a single backoff delay.
+        max_total_delay (Duration): Cap of the cumulative backoff time of one call. A retry whose delay would
+            exceed it is not attempted and the last error is returned.
+        jitter (Duration): Random extra delay (up to this value) added to every backoff, so that clients failing
+            together don't retry together. Zero by default, see `with_jitter`.
+
+    Only transient failures are retried: connection errors and resets, timeouts, gateway 5xx and envelopes reporting
+    a 5xx from Instagram. 404, invalid tokens and other client errors are returned right away.
+
+    Retries are disabled by default, see `RetryPolicy::none`.
+    */
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub max_total_delay: Duration,
+    pub jitter: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration, max_delay: Duration, max_total_delay: Duration) -> Result<Self, RocketAPIError> {
+        let policy = RetryPolicy { max_attempts, base_delay, max_delay, max_total_delay, jitter: Duration::ZERO };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            max_total_delay: Duration::ZERO,
+            jitter: Duration::ZERO,
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), RocketAPIError> {
+        if self.max_attempts == 0 {
+            return Err(RocketAPIError::InvalidParameter("max_attempts must be at least 1".to_string()));
+        }
+        if self.base_delay > self.max_delay {
+            return Err(RocketAPIError::InvalidParameter(format!(
+                "base_delay ({:?}) is greater than max_delay ({:?})", self.base_delay, self.max_delay
+            )));
+        }
+        if self.max_delay > self.max_total_delay {
+            return Err(RocketAPIError::InvalidParameter(format!(
+                "max_delay ({:?}) is greater than max_total_delay ({:?})", self.max_delay, self.max_total_delay
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn delay(&self, retry: u32) -> Duration {
+        /*
+        Backoff before the given retry (1 for the first retry): `base_delay * 2^(retry - 1)`, capped at `max_delay`.
+        */
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    pub(crate) fn next_delay(&self, attempt: u32, waited: Duration) -> Option<Duration> {
+        // `attempt` attempts were made and `waited` was already spent in backoff.
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let delay = self.delay(attempt);
+        if waited + delay > self.max_total_delay {
+            return None;
+        }
+        // Jitter never pushes the cumulative backoff past `max_total_delay`.
+        Some((delay + jitter_sample(self.jitter)).min(self.max_total_delay - waited))
+    }
+}
+
+fn jitter_sample(jitter: Duration) -> Duration {
+    // Good enough randomness for spreading retries: clock nanoseconds mixed with a per-process counter.
+    static CALLS: AtomicU64 = AtomicU64::new(0);
+    if jitter.is_zero() {
+        return Duration::ZERO;
+    }
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos() as u64).unwrap_or(0);
+    let mixed = (nanos ^ CALLS.fetch_add(1, Ordering::Relaxed).wrapping_mul(0x9E37_79B9_7F4A_7C15)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let range = jitter.as_nanos().min(u64::MAX as u128) as u64;
+    Duration::from_nanos(mixed % range.saturating_add(1))
+}
+
+pub(crate) fn is_transient_error(err: &RocketAPIError) -> bool {
+    // Failures worth retrying as is: the request didn't get an answer in time, couldn't connect, the connection
+    // was reset while sending it or reading the body, or a load balancer answered with an error page instead
+    // of the gateway.
+    match err.kind() {
+        RocketAPIError::RequestError(e) => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+        RocketAPIError::NonJsonResponse { http_status, .. } => *http_status >= 500,
+        _ => false,
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttemptRecord {
+    /*
+    One attempt of the last call, see `InstagramAPI::last_attempts`.
+
+    Fields:
+        attempt (u32): Attempt number, starting at 1
+        http_status (u16): HTTP status of the gateway response, `None` if no response was received
+        status_code (i64): Instagram status code reported in the envelope, if any
+        latency (Duration): Duration of the attempt
+        error (str): Error of the attempt if no usable response was received (timeout, connection error, non-JSON body, ...)
+        backoff (Duration): Delay waited before the next attempt, `None` for the last one
+        headers (HeaderMap): HTTP headers of the gateway response (rate limit, request id, ...), empty if no response was received
+    */
+    pub attempt: u32,
+    pub http_status: Option<u16>,
+    pub status_code: Option<i64>,
+    pub latency: Duration,
+    pub error: Option<String>,
+    pub backoff: Option<Duration>,
+    pub headers: HeaderMap,
+}
@@ -0,0 +1,40 @@
+use std::future::Future;
+use std::time::Duration;
+
+/*
+Runtime-specific primitives (timers, blocking tasks).
+
+Tasks always run on Tokio. The `async-std` feature only swaps the timers (retry backoff, request intervals, rate
+limiting) for async-std's, which run on their own thread, so enabling it doesn't change which runtime the client
+needs: reqwest and the spawned tasks still need a Tokio runtime.
+*/
+
+#[cfg(not(feature = "async-std"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(feature = "async-std")]
+pub(crate) async fn sleep(duration: Duration) {
+    async_std::task::sleep(duration).await
+}
+
+// The task starts right away, not when the returned future is first polled.
+pub(crate) fn spawn_blocking<F, T>(f: F) -> impl Future<Output = T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let handle = tokio::task::spawn_blocking(f);
+    async move { handle.await.expect("blocking task panicked") }
+}
+
+// Runs `future` in the background; the returned future resolves to its output.
+pub(crate) fn spawn<F>(future: F) -> impl Future<Output = F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let handle = tokio::spawn(future);
+    async move { handle.await.expect("background task panicked") }
+}
@@ -0,0 +1,58 @@
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use serde_json::Value;
+use tower::util::BoxCloneService;
+use tower::{Service, ServiceExt};
+use crate::api::RocketAPI;
+use crate::errors::RocketAPIError;
+use crate::transport::{Transport, TransportFuture};
+
+/*
+tower integration (requires the `tower` feature).
+
+`RocketAPI` implements `Service<RocketRequest>`: the response is the raw RocketAPI envelope, so middleware
+(retry, concurrency limits, load shedding, ...) can be layered around it with `tower::ServiceBuilder`,
+and the resulting service can drive a client again via `InstagramAPI::from_service` / `ThreadsAPI::from_service`.
+*/
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RocketRequest {
+    pub method: String,
+    pub payload: Value,
+}
+
+pub type BoxRocketService = BoxCloneService<RocketRequest, Value, RocketAPIError>;
+
+impl Service<RocketRequest> for RocketAPI {
+    type Response = Value;
+    type Error = RocketAPIError;
+    type Future = TransportFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RocketRequest) -> Self::Future {
+        let api = self.clone();
+        Box::pin(async move { api.request(&request.method, request.payload).await })
+    }
+}
+
+pub(crate) struct ServiceTransport {
+    // BoxCloneService is not Sync, a clone is taken for every call.
+    service: Mutex<BoxRocketService>,
+}
+
+impl ServiceTransport {
+    pub(crate) fn new(service: BoxRocketService) -> Self {
+        ServiceTransport { service: Mutex::new(service) }
+    }
+}
+
+impl Transport for ServiceTransport {
+    fn call(&self, method: &str, payload: Value) -> TransportFuture {
+        let service = self.service.lock().expect("service mutex poisoned").clone();
+        let request = RocketRequest { method: method.to_string(), payload };
+        Box::pin(service.oneshot(request))
+    }
+}
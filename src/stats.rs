@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EndpointStats {
+    /*
+    Calls of one endpoint, see `InstagramAPI::stats`.
+
+    Fields:
+        calls (u64): Finished calls
+        successes (u64): Calls that returned a response body
+        errors (u64): Calls that returned an error (request, gateway or envelope)
+        min_latency, max_latency, total_latency (Duration): Latency of the calls, retries and backoff included
+    */
+    pub calls: u64,
+    pub successes: u64,
+    pub errors: u64,
+    pub min_latency: Duration,
+    pub max_latency: Duration,
+    pub total_latency: Duration,
+}
+
+impl EndpointStats {
+    pub fn avg_latency(&self) -> Duration {
+        match u32::try_from(self.calls) {
+            Ok(0) => Duration::ZERO,
+            Ok(calls) => self.total_latency / calls,
+            Err(_) => Duration::from_secs_f64(self.total_latency.as_secs_f64() / self.calls as f64),
+        }
+    }
+}
+
+// Distinct endpoints tracked per client (RocketAPI has far fewer), calls to more endpoints aren't recorded.
+const MAX_ENDPOINTS: usize = 256;
+
+// Counters of one endpoint, updated without locking. Latencies are in nanoseconds.
+#[derive(Debug)]
+struct Counters {
+    calls: AtomicU64,
+    successes: AtomicU64,
+    errors: AtomicU64,
+    min_nanos: AtomicU64,
+    max_nanos: AtomicU64,
+    total_nanos: AtomicU64,
+}
+
+impl Counters {
+    fn new() -> Self {
+        Counters {
+            calls: AtomicU64::new(0),
+            successes: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            min_nanos: AtomicU64::new(u64::MAX),
+            max_nanos: AtomicU64::new(0),
+            total_nanos: AtomicU64::new(0),
+        }
+    }
+
+    fn add(&self, success: bool, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.min_nanos.fetch_min(nanos, Ordering::Relaxed);
+        self.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+        self.total_nanos.fetch_add(nanos, Ordering::Relaxed);
+        let outcome = if success { &self.successes } else { &self.errors };
+        outcome.fetch_add(1, Ordering::Relaxed);
+        self.calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> EndpointStats {
+        let calls = self.calls.load(Ordering::Relaxed);
+        EndpointStats {
+            calls,
+            successes: self.successes.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            min_latency: if calls == 0 { Duration::ZERO } else { Duration::from_nanos(self.min_nanos.load(Ordering::Relaxed)) },
+            max_latency: Duration::from_nanos(self.max_nanos.load(Ordering::Relaxed)),
+            total_latency: Duration::from_nanos(self.total_nanos.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn reset(&self) {
+        self.calls.store(0, Ordering::Relaxed);
+        self.successes.store(0, Ordering::Relaxed);
+        self.errors.store(0, Ordering::Relaxed);
+        self.min_nanos.store(u64::MAX, Ordering::Relaxed);
+        self.max_nanos.store(0, Ordering::Relaxed);
+        self.total_nanos.store(0, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+struct Slot {
+    method: String,
+    counters: Counters,
+}
+
+// Shared by the clones of a client: a fixed open-addressing table whose slots are claimed once per endpoint.
+// Recording a call only does atomic operations; the first call of an endpoint allocates its slot before
+// claiming it. Snapshots read each counter separately, so a call finishing meanwhile may be half counted.
+#[derive(Debug, Clone)]
+pub(crate) struct StatsStore {
+    slots: Arc<[OnceLock<Slot>]>,
+}
+
+impl Default for StatsStore {
+    fn default() -> Self {
+        StatsStore { slots: (0..MAX_ENDPOINTS).map(|_| OnceLock::new()).collect() }
+    }
+}
+
+impl StatsStore {
+    fn slot(&self, method: &str) -> Option<&Slot> {
+        let mut hasher = DefaultHasher::new();
+        method.hash(&mut hasher);
+        let start = hasher.finish() as usize;
+        let mut claim: Option<Slot> = None;
+        for probe in 0..MAX_ENDPOINTS {
+            let cell = &self.slots[(start.wrapping_add(probe)) % MAX_ENDPOINTS];
+            if cell.get().is_none() {
+                let slot = claim.take().unwrap_or_else(|| Slot { method: method.to_string(), counters: Counters::new() });
+                // Another call may claim the slot first, the allocation is then kept for the next empty one.
+                if let Err(slot) = cell.set(slot) {
+                    claim = Some(slot);
+                }
+            }
+            match cell.get() {
+                Some(slot) if slot.method == method => return Some(slot),
+                _ => continue,
+            }
+        }
+        None
+    }
+
+    pub(crate) fn record(&self, method: &str, success: bool, latency: Duration) {
+        if let Some(slot) = self.slot(method) {
+            slot.counters.add(success, latency);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> HashMap<String, EndpointStats> {
+        self.slots
+            .iter()
+            .filter_map(OnceLock::get)
+            .map(|slot| (slot.method.clone(), slot.counters.snapshot()))
+            .filter(|(_, stats)| stats.calls > 0)
+            .collect()
+    }
+
+    pub(crate) fn reset(&self) {
+        for slot in self.slots.iter().filter_map(OnceLock::get) {
+            slot.counters.reset();
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures::future::BoxFuture;
+use futures::future;
+use futures::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
+use serde_json::Value;
+use crate::errors::RocketAPIError;
+use crate::helpers::cursor_value;
+use crate::models::MediaType;
+
+/*
+Auto-pagination streams (requires the `stream` feature).
+
+A `PageStream` yields the items of every page one by one and requests the next page only when
+the items of the current one are consumed. Dropping the stream stops the pagination.
+*/
+
+pub(crate) type PageFetcher<'a, C, K = String> =
+    Box<dyn for<'b> FnMut(&'b mut C, Option<K>) -> BoxFuture<'b, Result<Value, RocketAPIError>> + Send + 'a>;
+
+// Computes the cursor of the next page from a page and the cursor it was fetched with.
+pub(crate) type NextCursor<'a, K> = Box<dyn Fn(&Value, Option<&K>) -> Option<K> + Send + 'a>;
+
+pub(crate) fn fetcher<'a, C, K, F>(fetch: F) -> PageFetcher<'a, C, K>
+where
+    F: for<'b> FnMut(&'b mut C, Option<K>) -> BoxFuture<'b, Result<Value, RocketAPIError>> + Send + 'a,
+{
+    Box::new(fetch)
+}
+
+#[derive(Debug)]
+pub struct ChunkError {
+    /*
+    Failure of one request of a fanned-out stream (e.g. `InstagramAPI::stream_users_stories`).
+
+    Fields:
+        ids (Vec<u64>): Ids requested by the failed call, none of their items were yielded. For an item that
+            couldn't be parsed, only the id it belongs to
+        error (RocketAPIError): The error of the call
+    */
+    pub ids: Vec<u64>,
+    pub error: RocketAPIError,
+}
+
+impl std::fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.ids, self.error)
+    }
+}
+
+pub struct PageStream<'a> {
+    inner: BoxStream<'a, Result<Value, RocketAPIError>>,
+}
+
+struct PageState<'a, C, K> {
+    client: &'a mut C,
+    fetch: PageFetcher<'a, C, K>,
+    items_key: &'static str,
+    next_cursor: NextCursor<'a, K>,
+    buffer: VecDeque<Value>,
+    cursor: Option<K>,
+    done: bool,
+}
+
+pub(crate) fn paginate<'a, C: Send + 'a>(
+    client: &'a mut C,
+    fetch: PageFetcher<'a, C>,
+    items_key: &'static str,
+    cursor_key: &'static str,
+) -> PageStream<'a> {
+    let next_cursor: NextCursor<'a, String> = Box::new(move |page: &Value, _: Option<&String>| {
+        if page["more_available"] == false {
+            return None;
+        }
+        cursor_value(&page[cursor_key])
+    });
+    paginate_with(client, fetch, items_key, next_cursor)
+}
+
+pub(crate) fn paginate_with<'a, C: Send + 'a, K: Clone + PartialEq + Send + 'a>(
+    client: &'a mut C,
+    fetch: PageFetcher<'a, C, K>,
+    items_key: &'static str,
+    next_cursor: NextCursor<'a, K>,
+) -> PageStream<'a> {
+    let state = PageState {
+        client,
+        fetch,
+        items_key,
+        next_cursor,
+        buffer: VecDeque::new(),
+        cursor: None,
+        done: false,
+    };
+    let inner = stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.buffer.pop_front() {
+                return Some((Ok(item), state));
+            }
+            if state.done {
+                return None;
+            }
+            let previous = state.cursor.take();
+            match (state.fetch)(&mut *state.client, previous.clone()).await {
+                Ok(page) => {
+                    if let Some(items) = page[state.items_key].as_array() {
+                        state.buffer.extend(items.iter().cloned());
+                    }
+                    let next = (state.next_cursor)(&page, previous.as_ref());
+                    // A missing cursor or a cursor that does not move means the last page.
+                    state.done = next.is_none() || next == previous;
+                    state.cursor = next;
+                }
+                Err(err) => {
+                    state.done = true;
+                    return Some((Err(err), state));
+                }
+            }
+        }
+    });
+    PageStream { inner: Box::pin(inner) }
+}
+
+impl<'a> PageStream<'a> {
+    pub(crate) fn new(inner: BoxStream<'a, Result<Value, RocketAPIError>>) -> Self {
+        PageStream { inner }
+    }
+
+    pub fn take_until<P>(self, predicate: P) -> PageStream<'a>
+    where
+        P: Fn(&Value) -> bool + Send + 'a,
+    {
+        /*
+        End the stream at the first item matching `predicate` (the item itself is not yielded).
+        Pages are fetched lazily, so no request is made after the page containing that item.
+        */
+        PageStream::new(self.inner.try_take_while(move |item| future::ready(Ok(!predicate(item)))).boxed())
+    }
+
+    pub fn filter_media_type(self, media_type: MediaType) -> PageStream<'a> {
+        /*
+        Keep only media items of the given type. Errors are passed through.
+        */
+        PageStream::new(self.inner.try_filter(move |item| future::ready(MediaType::of(item) == Some(media_type))).boxed())
+    }
+}
+
+impl Stream for PageStream<'_> {
+    type Item = Result<Value, RocketAPIError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
@@ -1,12 +1,100 @@
 use std::time::Duration;
-use crate::api::RocketAPI;
+use crate::api::{CallState, RocketAPI, TimeoutScope};
+use crate::builder::{api_options_setters, ApiOptions};
+use crate::config::RocketAPIConfig;
 use crate::errors::RocketAPIError;
+use std::collections::{HashMap, HashSet};
+use crate::helpers::{cursor_value, user_pk};
+use crate::models::{SearchUsersPage, ThreadConversation, ThreadLikersPage, ThreadNode, ThreadPost};
+use crate::pagination::{checked_cursor, Cursor};
+use crate::retry::AttemptRecord;
+use reqwest::header::HeaderMap;
+use crate::stats::EndpointStats;
 use serde_json::{json, Value};
+#[cfg(feature = "stream")]
+use crate::stream::{fetcher, paginate, paginate_with, PageStream};
 
+const THREAD_LIKES: &str = "threads/thread/get_likes";
+
+fn thread_info_post(body: &Value) -> Option<&Value> {
+    // The post is the first item of `containing_thread` (as in reply pages) or of a top-level `thread_items`.
+    [&body["containing_thread"]["thread_items"][0]["post"], &body["thread_items"][0]["post"], &body["post"]]
+        .into_iter()
+        .find(|post| post.is_object())
+}
+
+fn reply_parent(post: &Value) -> Option<u64> {
+    // Explicit parent reference, either an id or a post object.
+    let info = &post["text_post_app_info"];
+    [&post["reply_to"], &info["reply_to"], &info["reply_to_id"]]
+        .into_iter()
+        .find_map(|parent| match parent {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.split('_').next().and_then(|pk| pk.parse().ok()),
+            Value::Object(_) => user_pk(parent),
+            _ => None,
+        })
+}
+
+// Reply posts of a `get_thread_replies` page with the pk of their parent.
+// Items of one `reply_threads` entry form a chain: each one answers the previous one, the first one answers the root.
+fn page_replies(page: &Value, root: u64) -> Vec<(u64, Value)> {
+    let mut replies = Vec::new();
+    for reply_thread in page["reply_threads"].as_array().into_iter().flatten() {
+        let mut previous = root;
+        for item in reply_thread["thread_items"].as_array().into_iter().flatten() {
+            let post = &item["post"];
+            let Some(pk) = user_pk(post) else { continue };
+            replies.push((reply_parent(post).unwrap_or(previous), post.clone()));
+            previous = pk;
+        }
+    }
+    replies
+}
+
+fn build_node(pk: u64, post: Option<ThreadPost>, children: &mut HashMap<u64, Vec<(u64, ThreadPost)>>) -> ThreadNode {
+    // Children are taken out of the map, so a (malformed) cycle can't recurse forever.
+    let replies = children
+        .remove(&pk)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(child_pk, child)| build_node(child_pk, Some(child), children))
+        .collect();
+    ThreadNode { pk, post, replies }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ThreadsAPIBuilder {
+    /*
+    Builder of `ThreadsAPI`, see `InstagramAPIBuilder` (same options). Only `token` is required.
+    */
+    options: ApiOptions,
+}
+
+impl ThreadsAPIBuilder {
+    api_options_setters!();
+
+    pub fn build(self) -> Result<ThreadsAPI, RocketAPIError> {
+        Ok(ThreadsAPI::from_api(self.options.build()?))
+    }
+
+    pub async fn connect(self) -> Result<ThreadsAPI, RocketAPIError> {
+        /*
+        Same as `build`, then checks the token with one free request, see `ThreadsAPI::connect`.
+        */
+        let client = self.build()?;
+        client.api.account_info().await?;
+        Ok(client)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ThreadsAPI {
     pub api: RocketAPI,
     pub last_response: Value,
-    pub counter: u32
+    pub counter: u32,
+    pub last_retries: u32,
+    last_attempts: Vec<AttemptRecord>,
 }
 
 impl ThreadsAPI {
@@ -20,42 +108,168 @@ impl ThreadsAPI {
     For debugging purposes you can use the following variables:
         last_response (serde_json::Value): contains the last response from the API.
         counter (u32): contains the number of requests made in the current session.
+        last_retries (u32): contains the number of retries the last call needed (see `RocketAPI::set_retry_policy`).
 
     For more information, see documentation: https://docs.rocketapi.io/api/
     */
     pub fn new(token: String, max_timeout: Duration) -> Self {
+        Self::from_api(RocketAPI::new(token, max_timeout))
+    }
+
+    pub fn with_client(token: String, max_timeout: Duration, client: reqwest::Client) -> Self {
+        /*
+        Client sending requests through an existing `reqwest::Client`, see `RocketAPI::with_client`.
+        */
+        Self::from_api(RocketAPI::with_client(token, max_timeout, client))
+    }
+
+    pub fn builder() -> ThreadsAPIBuilder {
+        ThreadsAPIBuilder::default()
+    }
+
+    fn from_api(api: RocketAPI) -> Self {
         ThreadsAPI {
-            api: RocketAPI::new(token, max_timeout),
+            api,
             last_response: Value::Null,
-            counter: 0
+            counter: 0,
+            last_retries: 0,
+            last_attempts: Vec::new(),
         }
     }
 
-    async fn request(&mut self, method: &str, data: Value) -> Result<Value, RocketAPIError> {
-        match self.api.request(method, data).await {
-            Ok(response) => {
-                self.last_response = response.clone();
-                self.counter += 1;
-                if response["status"] == "done" {
-                    let response_body = &response["response"];
-                    let status_code = response_body["status_code"].as_i64().unwrap_or(0);
-                    let content_type = response_body["content_type"].as_str().unwrap_or("");
-        
-                    if status_code == 200 && content_type == "application/json" {
-                        Ok(response_body["body"].clone())
-                    } else if status_code == 404 {
-                        Err(RocketAPIError::NotFound(response))
-                    } else {
-                        Err(RocketAPIError::BadResponse(response))
-                    }
-                } else {
-                    Err(RocketAPIError::BadResponse(response))
-                }
-            }
-            Err(e) => {
-                Err(RocketAPIError::RequestError(e))
-            }
+    #[cfg(feature = "tower")]
+    pub fn from_service<S>(service: S) -> Self
+    where
+        S: tower::Service<crate::service::RocketRequest, Response = Value, Error = RocketAPIError> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+    {
+        /*
+        Client sending every request through a tower service returning raw RocketAPI envelopes,
+        e.g. a `RocketAPI` wrapped in `tower::ServiceBuilder` layers. Requires the `tower` feature.
+        */
+        let transport = crate::service::ServiceTransport::new(tower::util::BoxCloneService::new(service));
+        Self::from_api(RocketAPI::with_transport(String::new(), Duration::from_secs(30), std::sync::Arc::new(transport)))
+    }
+
+    pub fn from_config(config: &RocketAPIConfig) -> Result<Self, RocketAPIError> {
+        /*
+        Build a client from a configuration, see `config::RocketAPIConfig`. `default_counts` is not used by Threads.
+        */
+        Ok(Self::from_api(config.build_api()?))
+    }
+
+    pub fn from_config_file(path: impl AsRef<std::path::Path>) -> Result<Self, RocketAPIError> {
+        /*
+        Same as `from_config`, reading the configuration from a `.toml` or `.json` file.
+        */
+        Self::from_config(&RocketAPIConfig::from_file(path)?)
+    }
+
+    pub async fn connect(token: String, max_timeout: Duration) -> Result<Self, RocketAPIError> {
+        /*
+        Same as `new`, but checks the token right away with one free request (see `RocketAPI::account_info`).
+
+        Returns `RocketAPIError::Unauthorized` if the token is invalid. Use `ThreadsAPIBuilder::connect`
+        to set other options (e.g. the base URL) before the check.
+        */
+        ThreadsAPI::builder().token(token).max_timeout(max_timeout).connect().await
+    }
+
+    pub fn requests_made(&self) -> u32 {
+        self.counter
+    }
+
+    pub fn reset(&mut self) {
+        /*
+        Forget the previous calls (`counter`, `last_response`, `last_retries` and `last_attempts`),
+        e.g. between jobs sharing a long-lived client. Stats are kept, see `reset_stats`.
+        */
+        self.counter = 0;
+        self.last_response = Value::Null;
+        self.last_retries = 0;
+        self.last_attempts.clear();
+    }
+
+    pub fn last_attempts(&self) -> &[AttemptRecord] {
+        /*
+        Attempts of the last call (status, latency, error and backoff of each), oldest first.
+        Replaced by every call; empty if no request could be sent.
+        */
+        &self.last_attempts
+    }
+
+    pub fn last_status(&self) -> Option<u16> {
+        // HTTP status of the gateway response to the last attempt, `None` if no response was received.
+        self.last_attempts.last().and_then(|attempt| attempt.http_status)
+    }
+
+    pub fn last_headers(&self) -> Option<&HeaderMap> {
+        /*
+        HTTP headers of the gateway response to the last attempt (rate limit, request id, ...),
+        `None` if no response was received.
+        */
+        self.last_attempts.last().filter(|attempt| attempt.http_status.is_some()).map(|attempt| &attempt.headers)
+    }
+
+    pub fn stats(&self) -> HashMap<String, EndpointStats> {
+        /*
+        Calls, successes, errors and latency of every endpoint called so far, keyed by method
+        (e.g. `threads/user/get_followers`). Clones of the client share their stats.
+        */
+        self.api.stats()
+    }
+
+    pub fn reset_stats(&self) {
+        self.api.reset_stats();
+    }
+
+    pub fn with_timeout(&mut self, timeout: Duration) -> TimeoutScope<'_, ThreadsAPI> {
+        /*
+        Override the request timeout for the calls made through the returned scope, e.g. for a slow endpoint:
+
+            api.with_timeout(Duration::from_secs(90)).get_user_followers(&user_id, Some(100), None).await?;
+
+        The client timeout is restored when the scope is dropped (at the end of the statement above).
+        */
+        TimeoutScope::new(self, |client| &mut client.api, timeout)
+    }
+
+    pub fn last_warnings(&self) -> Vec<String> {
+        /*
+        Non-fatal messages attached to `last_response`, see `helpers::response_warnings`.
+        With the `log` feature enabled they are also logged with `log::warn!` as responses arrive.
+        */
+        crate::helpers::response_warnings(&self.last_response)
+    }
+
+    pub fn describe_last_response(&self) -> String {
+        /*
+        Compact summary of `last_response`, see `helpers::describe_value`.
+        */
+        if self.last_response.is_null() {
+            return "no response yet".to_string();
         }
+        crate::helpers::describe_value(&self.last_response)
+    }
+
+    pub async fn call(&mut self, method: &str, data: Value) -> Result<Value, RocketAPIError> {
+        /*
+        Call any RocketAPI method, e.g. an endpoint not wrapped by this crate yet.
+
+        Args:
+            method (str): Method path, e.g. `threads/user/get_info`
+            data (serde_json::Value): Request payload
+
+        The response goes through the same handling as the wrapped methods (retries, status codes, envelope
+        unwrapping, `last_response` and `counter`) and the unwrapped body is returned.
+        */
+        let state = CallState {
+            counter: &mut self.counter,
+            last_response: &mut self.last_response,
+            last_retries: &mut self.last_retries,
+            last_attempts: &mut self.last_attempts,
+        };
+        self.api.call_with_state(method, data, state).await.0
     }
     
     pub async fn search_users(&mut self, query: &str, rank_token: Option<&str>, page_token: Option<&str>) -> Result<Value, RocketAPIError> {
@@ -64,6 +278,10 @@ impl ThreadsAPI {
 
         Args:
             query (str): Username to search for
+            rank_token (str): Use for pagination
+            page_token (str): Use for pagination
+
+        To get the next page pass the `rank_token` of the first page and the `page_token` of the current page.
 
         For more information, see documentation: https://docs.rocketapi.io/api/threads/search_users
         */
@@ -74,7 +292,47 @@ impl ThreadsAPI {
         if let Some(page) = page_token {
             payload["page_token"] = json!(page);
         }
-        self.request("threads/search_users", payload).await
+        self.call("threads/search_users", payload).await
+    }
+
+    pub async fn search_users_typed(&mut self, query: &str, rank_token: Option<&str>, page_token: Option<&str>) -> Result<SearchUsersPage, RocketAPIError> {
+        /*
+        Same as `search_users`, but returns a typed page with the pagination tokens.
+        */
+        let body = self.search_users(query, rank_token, page_token).await?;
+        serde_json::from_value(body).map_err(RocketAPIError::ParseError)
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn stream_search_users(&mut self, query: &str) -> PageStream<'_> {
+        /*
+        Stream users found by `query` across all pages.
+
+        The `rank_token` of the first page is kept for the whole search and the `page_token` of every
+        page is fed into the next request. Stops when `has_more` is false or no `page_token` is returned.
+        Every page costs one request. Requires the `stream` feature.
+        */
+        let query = query.to_string();
+        let fetch = fetcher(move |api: &mut ThreadsAPI, tokens: Option<(Option<String>, String)>| {
+            let query = query.clone();
+            Box::pin(async move {
+                match tokens {
+                    Some((rank, page)) => api.search_users(&query, rank.as_deref(), Some(&page)).await,
+                    None => api.search_users(&query, None, None).await,
+                }
+            })
+        });
+        let next_cursor = Box::new(|page: &Value, previous: Option<&(Option<String>, String)>| {
+            if page["has_more"] == false {
+                return None;
+            }
+            let page_token = page["page_token"].as_str().filter(|t| !t.is_empty())?.to_string();
+            let rank_token = previous
+                .and_then(|(rank, _)| rank.clone())
+                .or_else(|| page["rank_token"].as_str().map(str::to_string));
+            Some((rank_token, page_token))
+        });
+        paginate_with(self, fetch, "users", next_cursor)
     }
 
     pub async fn get_user_info(&mut self, user_id: &u64) -> Result<Value, RocketAPIError> {
@@ -87,7 +345,7 @@ impl ThreadsAPI {
         For more information, see documentation: https://docs.rocketapi.io/api/threads/user/get_info
         */
         let payload = json!({ "id": user_id });
-        self.request("threads/user/get_info", payload).await
+        self.call("threads/user/get_info", payload).await
     }
 
     pub async fn get_user_feed(&mut self, user_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
@@ -106,7 +364,7 @@ impl ThreadsAPI {
         if let Some(max) = max_id {
             payload["max_id"] = json!(max);
         }
-        self.request("threads/user/get_feed", payload).await
+        self.call("threads/user/get_feed", payload).await
     }
 
     pub async fn get_user_replies(&mut self, user_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
@@ -125,7 +383,7 @@ impl ThreadsAPI {
         if let Some(max) = max_id {
             payload["max_id"] = json!(max);
         }
-        self.request("threads/user/get_replies", payload).await
+        self.call("threads/user/get_replies", payload).await
     }
 
     pub async fn get_user_followers(&mut self, user_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
@@ -144,7 +402,7 @@ impl ThreadsAPI {
         if let Some(max) = max_id {
             payload["max_id"] = json!(max);
         }
-        self.request("threads/user/get_followers", payload).await
+        self.call("threads/user/get_followers", payload).await
     }
     
     pub async fn search_user_followers(&mut self, user_id: &u64, query: &str) -> Result<Value, RocketAPIError> {
@@ -161,7 +419,7 @@ impl ThreadsAPI {
             "id": user_id,
             "query": query,
         });
-        self.request("threads/user/get_followers", payload).await
+        self.call("threads/user/get_followers", payload).await
     }
 
     pub async fn get_user_following(&mut self, user_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
@@ -180,7 +438,7 @@ impl ThreadsAPI {
         if let Some(max) = max_id {
             payload["max_id"] = json!(max);
         }
-        self.request("threads/user/get_following", payload).await
+        self.call("threads/user/get_following", payload).await
     }
 
     pub async fn search_user_following(&mut self, user_id: &u64, query: &str) -> Result<Value, RocketAPIError> {
@@ -197,7 +455,7 @@ impl ThreadsAPI {
             "id": user_id,
             "query": query,
         });
-        self.request("threads/user/get_following", payload).await
+        self.call("threads/user/get_following", payload).await
     }
 
     pub async fn get_thread_replies(&mut self, thread_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
@@ -216,19 +474,130 @@ impl ThreadsAPI {
         if let Some(max) = max_id {
             payload["max_id"] = json!(max);
         }
-        self.request("threads/thread/get_replies", payload).await
+        self.call("threads/thread/get_replies", payload).await
     }
 
-    pub async fn get_thread_likes(&mut self, thread_id: &u64) -> Result<Value, RocketAPIError> {
+    pub async fn get_thread_likes(&mut self, thread_id: &u64, max_id: Option<&str>) -> Result<Value, RocketAPIError> {
         /*
         Retrieve thread likes by id.
 
         Args:
             thread_id (u64): Thread id
+            max_id (str): Use for pagination
+
+        You can use the `max_id` parameter to paginate through likers (take from the `next_max_id` field of the response).
 
         For more information, see documentation: https://docs.rocketapi.io/api/threads/thread/get_likes
         */
+        let mut payload = json!({ "id": thread_id });
+        if let Some(max) = max_id {
+            payload["max_id"] = json!(max);
+        }
+        self.call(THREAD_LIKES, payload).await
+    }
+
+    pub async fn get_thread_likes_typed(&mut self, thread_id: &u64, cursor: Option<&Cursor>) -> Result<ThreadLikersPage, RocketAPIError> {
+        /*
+        Same as `get_thread_likes`, but returns a typed page. Likers are `ThreadsUserShort` (the same struct as
+        Instagram user lists use), the next page cursor is in `cursor`.
+
+        Pass the `cursor` of the previous page back as is; a cursor of another endpoint is refused with
+        `RocketAPIError::InvalidParameter`, and a stored string can be wrapped with `Cursor::raw`.
+        */
+        let max_id = checked_cursor(cursor, THREAD_LIKES)?;
+        let body = self.get_thread_likes(thread_id, max_id).await?;
+        let mut page: ThreadLikersPage = serde_json::from_value(body).map_err(RocketAPIError::ParseError)?;
+        page.cursor = page.cursor.map(|c| c.tagged(THREAD_LIKES));
+        Ok(page)
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn stream_thread_likes(&mut self, thread_id: &u64) -> PageStream<'_> {
+        /*
+        Stream all likers of a thread, following `next_max_id` until the last page.
+
+        Args:
+            thread_id (u64): Thread id
+
+        Every page costs one request. Requires the `stream` feature.
+        */
+        let thread_id = *thread_id;
+        let fetch = fetcher(move |api: &mut ThreadsAPI, max_id: Option<String>| {
+            Box::pin(async move { api.get_thread_likes(&thread_id, max_id.as_deref()).await })
+        });
+        paginate(self, fetch, "users", "next_max_id")
+    }
+
+    pub async fn get_thread_info(&mut self, thread_id: &u64) -> Result<Value, RocketAPIError> {
+        /*
+        Retrieve a thread (the original post) by id.
+
+        Args:
+            thread_id (u64): Thread id
+        */
         let payload = json!({ "id": thread_id });
-        self.request("threads/thread/get_likes", payload).await
+        self.call("threads/thread/get_info", payload).await
+    }
+
+    pub async fn get_thread_conversation(&mut self, thread_id: &u64, max_items: usize) -> Result<ThreadConversation, RocketAPIError> {
+        /*
+        Retrieve the replies of a thread as a conversation tree.
+
+        Args:
+            thread_id (u64): Thread id
+            max_items (usize): Maximum number of replies to collect
+
+        The original post is fetched with `get_thread_info` and is the root. Reply pages are then fetched following
+        `paging_tokens["downwards"]` until `max_items` replies are collected or the pages run out, so the call costs
+        one request plus one per page.
+
+        A reply whose parent is not part of the result (deleted, or on a page that was not fetched) is attached
+        under an unavailable node (`post: None`) with the parent pk, placed directly under the root, and the pk is
+        listed in `missing_parents`. Replies whose parents form a loop can't be placed and are listed in `detached`.
+        Returns `RocketAPIError::NotFound` if the thread info has no post.
+        */
+        let info = self.get_thread_info(thread_id).await?;
+        let root_post: ThreadPost = match thread_info_post(&info) {
+            Some(post) => serde_json::from_value(post.clone()).map_err(RocketAPIError::ParseError)?,
+            None => return Err(RocketAPIError::NotFound(info)),
+        };
+        let mut replies: Vec<(u64, Value)> = Vec::new();
+        let mut max_id: Option<String> = None;
+        while replies.len() < max_items {
+            let page = self.get_thread_replies(thread_id, max_id.as_deref()).await?;
+            replies.extend(page_replies(&page, *thread_id));
+            match cursor_value(&page["paging_tokens"]["downwards"]) {
+                Some(next) if Some(&next) != max_id.as_ref() => max_id = Some(next),
+                _ => break,
+            }
+        }
+        replies.truncate(max_items);
+
+        let root_pk = root_post.pk;
+        let mut known: HashSet<u64> = HashSet::from([root_pk, *thread_id]);
+        let mut parsed = Vec::with_capacity(replies.len());
+        for (parent, post) in replies {
+            let post: ThreadPost = serde_json::from_value(post).map_err(RocketAPIError::ParseError)?;
+            if known.insert(post.pk) {
+                parsed.push((if parent == *thread_id { root_pk } else { parent }, post));
+            }
+        }
+        let mut children: HashMap<u64, Vec<(u64, ThreadPost)>> = HashMap::new();
+        let mut missing_parents: Vec<u64> = Vec::new();
+        for (parent, post) in parsed {
+            if !known.contains(&parent) && !missing_parents.contains(&parent) {
+                missing_parents.push(parent);
+            }
+            children.entry(parent).or_default().push((post.pk, post));
+        }
+        let mut root = build_node(root_pk, Some(root_post), &mut children);
+        for parent in &missing_parents {
+            let node = build_node(*parent, None, &mut children);
+            root.replies.push(node);
+        }
+        // Whatever is left can't be reached from the root: its parents answer each other in a loop.
+        let mut detached: Vec<ThreadPost> = children.into_values().flatten().map(|(_, post)| post).collect();
+        detached.sort_by_key(|post| post.pk);
+        Ok(ThreadConversation { root, missing_parents, detached })
     }
 }
@@ -0,0 +1,57 @@
+use std::future::Future;
+use std::time::Duration;
+use crate::errors::RocketAPIError;
+
+/*
+Spans around the calls of the clients, with the `tracing` feature.
+
+Every call opens a `rocketapi_request` span with the `method` and the client `counter`; once it is done the span
+records the envelope `status_code` and `elapsed_ms`, and errors are logged at `warn` with their variant name.
+Without the feature these helpers do nothing and cost nothing.
+*/
+
+#[cfg(feature = "tracing")]
+pub(crate) type Span = tracing::Span;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+#[cfg(feature = "tracing")]
+pub(crate) fn request_span(method: &str, counter: Option<u32>) -> Span {
+    tracing::info_span!(
+        "rocketapi_request",
+        method,
+        counter,
+        status_code = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+    )
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn request_span(_method: &str, _counter: Option<u32>) -> Span {
+    Span
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) async fn in_span<F: Future>(span: &Span, future: F) -> F::Output {
+    tracing::Instrument::instrument(future, span.clone()).await
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) async fn in_span<F: Future>(_span: &Span, future: F) -> F::Output {
+    future.await
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn finish<T>(span: &Span, status_code: Option<i64>, result: &Result<T, RocketAPIError>, elapsed: Duration) {
+    if let Some(status_code) = status_code {
+        span.record("status_code", status_code);
+    }
+    span.record("elapsed_ms", u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX));
+    if let Err(err) = result {
+        span.in_scope(|| tracing::warn!(error = err.variant_name(), "{}", err));
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn finish<T>(_span: &Span, _status_code: Option<i64>, _result: &Result<T, RocketAPIError>, _elapsed: Duration) {}
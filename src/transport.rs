@@ -0,0 +1,18 @@
+use std::future::Future;
+use std::pin::Pin;
+use serde_json::Value;
+use crate::errors::RocketAPIError;
+
+pub type TransportFuture = Pin<Box<dyn Future<Output = Result<Value, RocketAPIError>> + Send>>;
+
+pub trait Transport: Send + Sync {
+    /*
+    Replacement for the HTTP layer of `RocketAPI`.
+
+    `call` receives the API method (e.g. `instagram/user/get_info`) and the payload, and returns the raw
+    RocketAPI envelope (`{"status": "done", "response": {...}}`), exactly as the HTTP endpoint would.
+    Envelope parsing, retries and the other client features work the same on top of a custom transport.
+    See `RocketAPI::with_transport`.
+    */
+    fn call(&self, method: &str, payload: Value) -> TransportFuture;
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+use reqwest::redirect::{Attempt, Policy};
+use reqwest::{Client, Url};
+use crate::errors::RocketAPIError;
+
+// Hosts (and their subdomains) that share links may redirect through.
+const INSTAGRAM_HOSTS: [&str; 4] = ["instagram.com", "instagr.am", "ig.me", "threads.net"];
+const MAX_REDIRECTS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstagramUrl {
+    /*
+    Resource an instagram.com URL points to.
+    */
+    Profile(String),
+    Post(String),
+    Reel(String),
+    Story { username: String, story_id: u64 },
+    Highlight(u64),
+    Hashtag(String),
+    Location(u64),
+}
+
+pub fn is_instagram_host(host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    INSTAGRAM_HOSTS.iter().any(|h| host == *h || host.ends_with(&format!(".{}", h)))
+}
+
+pub fn parse_instagram_url(url: &str) -> Option<InstagramUrl> {
+    /*
+    Parse a canonical instagram.com URL.
+
+    Args:
+        url (str): URL, e.g. `https://www.instagram.com/p/Cx1a2b3/?igsh=...`
+
+    Recognized paths: `/<username>/`, `/p/<code>/`, `/reel/<code>/`, `/tv/<code>/`, `/stories/<username>/<id>/`,
+    `/stories/highlights/<id>/`, `/explore/tags/<name>/` and `/explore/locations/<id>/`.
+    Short share links (`/share/...`, ig.me) must be resolved first, see `InstagramAPI::resolve_share_url`.
+    */
+    let parsed = Url::parse(url.trim()).ok()?;
+    if !is_instagram_host(parsed.host_str()?) {
+        return None;
+    }
+    let segments: Vec<&str> = parsed.path_segments()?.filter(|s| !s.is_empty()).collect();
+    match segments.as_slice() {
+        ["p", code, ..] | [_, "p", code, ..] | ["tv", code, ..] => Some(InstagramUrl::Post(code.to_string())),
+        ["reel" | "reels", code, ..] | [_, "reel", code, ..] => Some(InstagramUrl::Reel(code.to_string())),
+        ["stories", "highlights", id, ..] => id.parse().ok().map(InstagramUrl::Highlight),
+        ["stories", username, id, ..] => id.parse().ok().map(|story_id| InstagramUrl::Story { username: username.to_string(), story_id }),
+        ["explore", "tags", name, ..] => Some(InstagramUrl::Hashtag(name.to_string())),
+        ["explore", "locations", id, ..] => id.parse().ok().map(InstagramUrl::Location),
+        [username] if !is_reserved_path(username) => Some(InstagramUrl::Profile(username.to_string())),
+        _ => None,
+    }
+}
+
+fn is_reserved_path(segment: &str) -> bool {
+    ["accounts", "explore", "direct", "share", "stories", "reels", "about", "legal", "developer"].contains(&segment)
+}
+
+fn redirect_policy(attempt: Attempt) -> reqwest::redirect::Action {
+    if attempt.previous().len() > MAX_REDIRECTS {
+        attempt.error("too many redirects")
+    } else if attempt.url().host_str().is_some_and(is_instagram_host) {
+        attempt.follow()
+    } else {
+        // Not followed: the redirect response is returned and rejected by `resolve_share_url`.
+        attempt.stop()
+    }
+}
+
+pub(crate) async fn resolve_share_url(url: &str, timeout: Duration) -> Result<InstagramUrl, RocketAPIError> {
+    resolve_share_url_with(url, Client::builder(), timeout).await
+}
+
+async fn resolve_share_url_with(url: &str, builder: reqwest::ClientBuilder, timeout: Duration) -> Result<InstagramUrl, RocketAPIError> {
+    let start = Url::parse(url.trim()).map_err(|e| RocketAPIError::InvalidParameter(format!("invalid URL {}: {}", url, e)))?;
+    if !start.host_str().is_some_and(is_instagram_host) {
+        return Err(RocketAPIError::InvalidParameter(format!("{} is not an Instagram URL", url)));
+    }
+    let client = builder
+        .redirect(Policy::custom(redirect_policy))
+        .timeout(timeout)
+        .build()
+        .map_err(RocketAPIError::RequestError)?;
+    let response = client.get(start).send().await.map_err(RocketAPIError::RequestError)?;
+    if response.status().is_redirection() {
+        let location = response.headers().get(reqwest::header::LOCATION).and_then(|l| l.to_str().ok()).unwrap_or("");
+        return Err(RocketAPIError::InvalidParameter(format!("{} redirects outside of Instagram ({})", url, location)));
+    }
+    let resolved = response.url().to_string();
+    parse_instagram_url(&resolved)
+        .ok_or_else(|| RocketAPIError::InvalidParameter(format!("{} resolved to an unsupported URL: {}", url, resolved)))
+}
+
+// Resolves every host name to one address, so redirect chains between Instagram hosts can be served locally.
+#[cfg(feature = "test-util")]
+struct FixedResolver(std::net::SocketAddr);
+
+#[cfg(feature = "test-util")]
+impl reqwest::dns::Resolve for FixedResolver {
+    fn resolve(&self, _name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let addr = self.0;
+        Box::pin(async move { Ok(Box::new(std::iter::once(addr)) as reqwest::dns::Addrs) })
+    }
+}
+
+#[cfg(feature = "test-util")]
+pub async fn resolve_share_url_at(url: &str, timeout: Duration, addr: std::net::SocketAddr) -> Result<InstagramUrl, RocketAPIError> {
+    /*
+    Same as `InstagramAPI::resolve_share_url`, with every host name resolved to `addr`, e.g. a local test server.
+    The port of the URLs is kept (DNS has no ports), so `addr` should listen on the port the URLs use.
+    Requires the `test-util` feature.
+    */
+    let builder = Client::builder().dns_resolver(std::sync::Arc::new(FixedResolver(addr)));
+    resolve_share_url_with(url, builder, timeout).await
+}
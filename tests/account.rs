@@ -0,0 +1,137 @@
+mod common;
+
+use common::{ok, HttpResponse, MockServer, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::models::{AccountInfo, Balance};
+use rocketapi::RocketAPI;
+use serde_json::json;
+
+fn client(server: &MockServer, token: &str) -> RocketAPI {
+    let mut api = RocketAPI::new(token.to_string(), TIMEOUT);
+    api.set_base_url(&server.url()).unwrap();
+    api
+}
+
+#[tokio::test]
+async fn rejected_tokens_are_unauthorized_whatever_the_body() {
+    let server = MockServer::start(|request| match request.header("authorization") {
+        Some("Token html") => HttpResponse::text(401, "<html>401 Authorization Required</html>"),
+        Some("Token empty") => HttpResponse::text(403, ""),
+        _ => HttpResponse::json(403, &json!({ "detail": "Invalid token." })),
+    });
+    for token in ["html", "empty", "json"] {
+        let api = client(&server, token);
+        let result = api.account_info().await;
+        assert!(matches!(result, Err(RocketAPIError::Unauthorized(_))), "{}: {:?}", token, result);
+    }
+}
+
+#[tokio::test]
+async fn valid_tokens_return_the_plan_and_quota_headers() {
+    let server = MockServer::start(|_| {
+        HttpResponse::json(200, &ok(json!({ "shortcode": "B" })))
+            .header("X-Plan", "pro")
+            .header("X-RateLimit-Limit", "100000")
+            .header("X-RateLimit-Remaining", "99250")
+    });
+    let api = client(&server, "test-token");
+    let account = api.account_info().await.unwrap();
+    assert_eq!(
+        account,
+        AccountInfo { plan: Some("pro".to_string()), quota_limit: Some(100000), quota_remaining: Some(99250) }
+    );
+    assert_eq!(server.requests()[0].path, "/instagram/media/get_shortcode_by_id");
+}
+
+#[tokio::test]
+async fn missing_or_malformed_quota_headers_are_none() {
+    let server = MockServer::start(|_| {
+        HttpResponse::json(200, &ok(json!({}))).header("X-Plan", " ").header("X-RateLimit-Limit", "unlimited")
+    });
+    let api = client(&server, "test-token");
+    assert_eq!(api.account_info().await.unwrap(), AccountInfo::default());
+}
+
+#[tokio::test]
+async fn set_token_checks_the_new_token_with_the_auth_scheme() {
+    let server = MockServer::start(|request| match request.header("authorization") {
+        Some("Bearer good") | Some("Bearer other") => HttpResponse::json(200, &ok(json!({}))),
+        _ => HttpResponse::json(401, &json!({ "detail": "Invalid token." })),
+    });
+    let mut api = client(&server, "good");
+    api.set_auth_scheme("Bearer").unwrap();
+    let before = api.clone();
+    assert!(matches!(api.set_token("bad".to_string()).await, Err(RocketAPIError::Unauthorized(_))));
+    assert!(matches!(api.set_token("bad\ntoken".to_string()).await, Err(RocketAPIError::InvalidParameter(_))));
+    api.set_token("other".to_string()).await.unwrap();
+    // Header values are checked before any request, with the scheme actually sent.
+    let sent: Vec<String> = server.requests().iter().map(|request| request.header("authorization").unwrap().to_string()).collect();
+    assert_eq!(sent, vec!["Bearer bad", "Bearer other"]);
+    // Clones made before keep their token.
+    before.account_info().await.unwrap();
+    assert_eq!(server.requests()[2].header("authorization"), Some("Bearer good"));
+}
+
+#[tokio::test]
+async fn balance_is_read_from_the_quota_headers() {
+    let server = MockServer::start(|_| {
+        HttpResponse::json(200, &ok(json!({})))
+            .header("X-Plan", "business")
+            .header("X-RateLimit-Limit", "5000")
+            .header("X-RateLimit-Remaining", "4873")
+    });
+    let balance = client(&server, "test-token").get_account_balance().await.unwrap();
+    assert_eq!(balance, Balance { credits: Some(4873.0), plan: Some("business".to_string()), requests_today: Some(127) });
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn balance_fields_are_none_without_quota_reporting() {
+    let server = MockServer::start(|_| HttpResponse::json(200, &ok(json!({}))).header("X-RateLimit-Remaining", "12"));
+    let balance = client(&server, "test-token").get_account_balance().await.unwrap();
+    // Without the limit, the requests made can't be told.
+    assert_eq!(balance, Balance { credits: Some(12.0), plan: None, requests_today: None });
+}
+
+fn token_checking_server() -> MockServer {
+    MockServer::start(|request| match request.header("authorization") {
+        Some("Token valid") => HttpResponse::json(200, &ok(json!({ "shortcode": "B" }))).header("X-Plan", "pro"),
+        _ => HttpResponse::json(401, &json!({ "detail": "Invalid token." })),
+    })
+}
+
+#[tokio::test]
+async fn connect_checks_the_token_before_returning_the_client() {
+    use rocketapi::instagramapi::InstagramAPI;
+    use rocketapi::threadsapi::ThreadsAPI;
+
+    let server = token_checking_server();
+    let mut api = InstagramAPI::builder().token("valid").base_url(server.url()).connect().await.unwrap();
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(server.requests()[0].path, "/instagram/media/get_shortcode_by_id");
+    // The check is not counted as a call of the client.
+    assert_eq!(api.requests_made(), 0);
+    api.get_user_info("natgeo").await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+
+    let threads = ThreadsAPI::builder().token("valid").base_url(server.url()).connect().await.unwrap();
+    assert_eq!(threads.requests_made(), 0);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn connect_rejects_an_invalid_token() {
+    use rocketapi::instagramapi::InstagramAPI;
+    use rocketapi::threadsapi::ThreadsAPI;
+
+    let server = token_checking_server();
+    let result = InstagramAPI::builder().token("revoked").base_url(server.url()).connect().await;
+    assert!(matches!(result, Err(RocketAPIError::Unauthorized(_))), "{:?}", result.map(|_| ()));
+    let result = ThreadsAPI::builder().token("revoked").base_url(server.url()).connect().await;
+    assert!(matches!(result, Err(RocketAPIError::Unauthorized(_))), "{:?}", result.map(|_| ()));
+    assert_eq!(server.requests().len(), 2);
+    // Invalid options are reported before any request.
+    let result = InstagramAPI::builder().base_url(server.url()).connect().await;
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result.map(|_| ()));
+    assert_eq!(server.requests().len(), 2);
+}
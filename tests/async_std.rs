@@ -0,0 +1,31 @@
+#![cfg(feature = "async-std")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::retry::RetryPolicy;
+use serde_json::json;
+
+// The feature only swaps the timers: a Tokio application keeps working with it enabled.
+#[tokio::test]
+async fn tokio_clients_keep_working_with_async_std_timers() {
+    let transport = MockTransport::new(|method, _| {
+        Ok(match method {
+            "instagram/user/get_info_by_id" => ok(json!({ "user": { "pk": 1 } })),
+            _ => ok(json!({ "items": [], "users": [] })),
+        })
+    });
+    let mut api = instagram(&transport);
+    let overview = api.get_profile_overview(&1, 12).await.unwrap();
+    assert!(overview.info.is_ok());
+    assert_eq!(transport.call_count(), 4);
+
+    let transport = MockTransport::scripted(vec![Ok(envelope(502, json!({}))), Ok(ok(json!({ "user": {} })))]);
+    let mut api = instagram(&transport);
+    let policy = RetryPolicy::new(2, Duration::from_millis(20), Duration::from_millis(20), Duration::from_secs(1)).unwrap();
+    api.api.set_retry_policy(policy).unwrap();
+    api.get_user_info("natgeo").await.unwrap();
+    assert_eq!(transport.call_count(), 2);
+}
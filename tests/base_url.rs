@@ -0,0 +1,47 @@
+mod common;
+
+use common::{ok, HttpResponse, MockServer};
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::{RocketAPI, DEFAULT_BASE_URL};
+use serde_json::json;
+
+#[test]
+fn clients_default_to_the_production_gateway() {
+    let api = RocketAPI::new("test-token".to_string(), common::TIMEOUT);
+    assert_eq!(api.base_url(), DEFAULT_BASE_URL);
+    assert_eq!(DEFAULT_BASE_URL, "https://v1.rocketapi.io/");
+}
+
+#[tokio::test]
+async fn a_staging_gateway_gets_every_request_instead_of_production() {
+    let staging = MockServer::start(|_| HttpResponse::json(200, &ok(json!({ "user": { "pk": 1, "username": "natgeo" } }))));
+    let mut api = InstagramAPI::builder().token("test-token").base_url(staging.url()).build().unwrap();
+    assert_eq!(api.api.base_url(), staging.url());
+    api.get_user_info("natgeo").await.unwrap();
+    let requests = staging.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/instagram/user/get_info");
+}
+
+#[tokio::test]
+async fn search_is_routed_to_the_base_url_with_or_without_a_trailing_slash() {
+    let gateway = MockServer::start(|_| HttpResponse::json(200, &ok(json!({ "users": [] }))));
+    for base_url in [format!("{}gateway", gateway.url()), format!("{}gateway/", gateway.url()), format!("{}gateway//", gateway.url())] {
+        let mut api = InstagramAPI::new("test-token".to_string(), common::TIMEOUT);
+        api.api = RocketAPI::with_base_url("test-token".to_string(), common::TIMEOUT, &base_url).unwrap();
+        assert_eq!(api.api.base_url(), format!("{}gateway/", gateway.url()));
+        api.search("natgeo").await.unwrap();
+        let request = gateway.requests().pop().unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/gateway/instagram/search"));
+        assert_eq!(request.json(), json!({ "query": "natgeo" }));
+    }
+    assert_eq!(gateway.requests().len(), 3);
+}
+
+#[test]
+fn base_urls_must_be_http_urls() {
+    for base_url in ["not a url", "ftp://example.com/", "/relative/"] {
+        let result = RocketAPI::with_base_url("test-token".to_string(), common::TIMEOUT, base_url);
+        assert!(matches!(result, Err(rocketapi::errors::RocketAPIError::InvalidParameter(_))), "{}", base_url);
+    }
+}
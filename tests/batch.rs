@@ -0,0 +1,199 @@
+#![cfg(feature = "stream")]
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::{ok, rocket, MockTransport};
+use futures::StreamExt;
+use rocketapi::batch::{BatchExecutor, Checkpoint, JsonlCheckpoint};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::RocketAPI;
+use serde_json::json;
+
+fn checkpoint_path(name: &str) -> PathBuf {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    std::env::temp_dir().join(format!("rocketapi-{}-{}-{}.jsonl", name, std::process::id(), nanos))
+}
+
+async fn get_info(api: RocketAPI, user_id: u64) -> Result<serde_json::Value, RocketAPIError> {
+    api.request("instagram/user/get_info_by_id", json!({ "id": user_id })).await
+}
+
+#[tokio::test]
+async fn rerun_after_a_kill_only_requests_the_remaining_items() {
+    let path = checkpoint_path("resume");
+    let items: Vec<u64> = (1..=10).collect();
+
+    // First run, killed after 4 items: the stream is dropped once 4 results came in.
+    let first = MockTransport::always(ok(json!({})));
+    {
+        let api = rocket(&first);
+        let checkpoint: Arc<dyn Checkpoint> = Arc::new(JsonlCheckpoint::open(&path).unwrap());
+        let executor = BatchExecutor::new(&api);
+        let done: Vec<_> = executor
+            .stream_checkpointed(items.clone(), checkpoint.clone(), |id: &u64| id.to_string(), get_info)
+            .take(4)
+            .collect()
+            .await;
+        assert_eq!(done.len(), 4);
+    }
+    assert_eq!(first.call_count(), 4);
+
+    // Rerun with the same file: the 4 recorded items are skipped.
+    let second = MockTransport::always(ok(json!({})));
+    let api = rocket(&second);
+    let checkpoint = Arc::new(JsonlCheckpoint::open(&path).unwrap());
+    assert_eq!(checkpoint.len(), 4);
+    let report = BatchExecutor::new(&api)
+        .concurrency(3)
+        .run_checkpointed(items, checkpoint.clone(), |id: &u64| id.to_string(), get_info)
+        .await
+        .unwrap();
+    assert_eq!(second.call_count(), 6);
+    assert_eq!(report.summary.skipped, 4);
+    assert_eq!(report.summary.succeeded, 6);
+    assert_eq!(checkpoint.len(), 10);
+    let requested: Vec<u64> = second.payloads().iter().map(|payload| payload["id"].as_u64().unwrap()).collect();
+    assert!(requested.iter().all(|id| *id > 4), "{:?}", requested);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn failed_items_are_not_recorded() {
+    let path = checkpoint_path("failed");
+    let transport = MockTransport::new(|_, payload| match payload["id"].as_u64() {
+        Some(2) => Ok(common::envelope(404, json!({}))),
+        _ => Ok(ok(json!({}))),
+    });
+    let api = rocket(&transport);
+    let checkpoint = Arc::new(JsonlCheckpoint::open(&path).unwrap());
+    let mut instagram = common::instagram(&transport);
+    instagram.api = api;
+    let report = BatchExecutor::new(&instagram)
+        .run_checkpointed(vec![1u64, 2, 3], checkpoint.clone(), |id: &u64| id.to_string(), |mut api, id: u64| async move {
+            api.get_user_info_by_id(&id).await
+        })
+        .await
+        .unwrap();
+    assert_eq!(report.summary.failed, 1);
+    assert!(checkpoint.is_done("1") && !checkpoint.is_done("2") && checkpoint.is_done("3"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn checkpoint_io_failures_are_io_errors() {
+    match JsonlCheckpoint::open(std::env::temp_dir()) {
+        Err(RocketAPIError::IoError { path, .. }) => assert_eq!(path, std::env::temp_dir()),
+        other => panic!("{:?}", other.map(|checkpoint| checkpoint.len())),
+    }
+}
+
+fn ids_by_outcome(report: &rocketapi::batch::BatchReport<u64, serde_json::Value>) -> (Vec<u64>, Vec<(u64, String)>) {
+    // Results come in completion order: sorted by item for stable assertions. Errors are reduced to their variant.
+    let mut succeeded: Vec<u64> = report.results.iter().filter(|(_, result)| result.is_ok()).map(|(id, _)| *id).collect();
+    let mut failed: Vec<(u64, String)> = report
+        .results
+        .iter()
+        .filter_map(|(id, result)| result.as_ref().err().map(|err| (*id, err.to_string().split(':').next().unwrap().to_string())))
+        .collect();
+    succeeded.sort_unstable();
+    failed.sort_unstable();
+    (succeeded, failed)
+}
+
+#[tokio::test]
+async fn every_item_gets_its_own_result() {
+    let transport = MockTransport::new(|_, payload| match payload["id"].as_u64().unwrap() {
+        3 => Ok(common::envelope(404, json!({ "message": "not found" }))),
+        5 => Err(RocketAPIError::InvalidParameter("bad id".to_string())),
+        id => Ok(ok(json!({ "user": { "pk": id } }))),
+    });
+    let api = common::instagram(&transport);
+    let report = BatchExecutor::new(&api)
+        .concurrency(3)
+        .run((1..=6).collect::<Vec<u64>>(), |mut api, id: u64| async move { api.get_user_info_by_id(&id).await })
+        .await;
+    let (succeeded, failed) = ids_by_outcome(&report);
+    assert_eq!(succeeded, vec![1, 2, 4, 6]);
+    assert_eq!(failed, vec![(3, "NotFound".to_string()), (5, "InvalidParameter".to_string())]);
+    for (id, result) in &report.results {
+        if let Ok(body) = result {
+            assert_eq!(body["user"]["pk"], *id);
+        }
+    }
+    assert_eq!((report.summary.succeeded, report.summary.failed, report.summary.retried, report.summary.skipped), (4, 2, 0, 0));
+    // The calls are made on clones: the original client is untouched.
+    assert_eq!(api.requests_made(), 0);
+}
+
+#[tokio::test]
+async fn starts_are_spaced_by_the_interval_across_concurrent_calls() {
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    let starts = Arc::new(Mutex::new(Vec::new()));
+    let recorded = starts.clone();
+    let transport = MockTransport::new(move |_, _| {
+        recorded.lock().unwrap().push(Instant::now());
+        Ok(ok(json!({})))
+    });
+    let api = rocket(&transport);
+    let report = BatchExecutor::new(&api).concurrency(4).interval(Duration::from_millis(30)).run(vec![1u64, 2, 3, 4], get_info).await;
+    assert_eq!(report.summary.succeeded, 4);
+    let starts = starts.lock().unwrap().clone();
+    assert_eq!(starts.len(), 4);
+    for pair in starts.windows(2) {
+        // A little slack for the timer resolution.
+        assert!(pair[1] - pair[0] >= Duration::from_millis(25), "{:?}", pair[1] - pair[0]);
+    }
+    assert!(report.summary.elapsed >= Duration::from_millis(85), "{:?}", report.summary);
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried_per_item() {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use rocketapi::retry::RetryPolicy;
+
+    // Item 2 fails twice then succeeds, item 4 always fails, item 6 gets a 404 (not retried).
+    let attempts: Arc<Mutex<HashMap<u64, u32>>> = Arc::default();
+    let counted = attempts.clone();
+    let transport = MockTransport::new(move |_, payload| {
+        let id = payload["id"].as_u64().unwrap();
+        let attempt = {
+            let mut attempts = counted.lock().unwrap();
+            let attempt = attempts.entry(id).or_insert(0);
+            *attempt += 1;
+            *attempt
+        };
+        let gateway_error = || Err(RocketAPIError::NonJsonResponse { http_status: 502, body: "Bad Gateway".to_string() });
+        match (id, attempt) {
+            (2, 1..=2) | (4, _) => gateway_error(),
+            (6, _) => Ok(common::envelope(404, json!({}))),
+            _ => Ok(ok(json!({ "id": id }))),
+        }
+    });
+    let api = rocket(&transport);
+    let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(2), Duration::from_millis(10)).unwrap();
+    let report = BatchExecutor::new(&api)
+        .concurrency(2)
+        .retry_policy(policy)
+        .run((1..=6).collect::<Vec<u64>>(), |api: RocketAPI, id: u64| async move {
+            api.request("instagram/user/get_info_by_id", json!({ "id": id })).await.and_then(|envelope| match envelope["response"]["status_code"].as_i64() {
+                Some(404) => Err(RocketAPIError::NotFound(envelope)),
+                _ => Ok(envelope),
+            })
+        })
+        .await;
+    let (succeeded, failed) = ids_by_outcome(&report);
+    assert_eq!(succeeded, vec![1, 2, 3, 5]);
+    assert_eq!(failed, vec![(4, "NonJsonResponse".to_string()), (6, "NotFound".to_string())]);
+    assert_eq!((report.summary.succeeded, report.summary.failed, report.summary.retried), (4, 2, 2));
+    let attempts = attempts.lock().unwrap().clone();
+    assert_eq!(attempts, HashMap::from([(1, 1), (2, 3), (3, 1), (4, 3), (5, 1), (6, 1)]));
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{ok, HttpResponse, MockServer, TIMEOUT};
+use rocketapi::blocking::InstagramAPI;
+use rocketapi::errors::RocketAPIError;
+use serde_json::json;
+
+// A plain test: no async runtime around the blocking client.
+#[test]
+fn get_user_info_without_a_runtime() {
+    let server = MockServer::start(|request| match request.json()["username"].as_str() {
+        Some("natgeo") => HttpResponse::json(200, &ok(json!({ "data": { "user": { "id": "787132", "username": "natgeo" } } }))),
+        _ => HttpResponse::json(200, &common::envelope(404, json!({}))),
+    });
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.inner_mut().api.set_base_url(&server.url()).unwrap();
+    let body = api.get_user_info("natgeo").unwrap();
+    assert_eq!(body["data"]["user"]["username"], "natgeo");
+    assert!(matches!(api.get_user_info("missing"), Err(RocketAPIError::NotFound(_))));
+    assert_eq!(server.requests()[0].path, "/instagram/user/get_info");
+    assert_eq!(server.requests()[0].header("authorization"), Some("Token test-token"));
+}
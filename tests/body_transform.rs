@@ -0,0 +1,50 @@
+mod common;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use serde_json::{json, Value};
+
+// Unwraps a `data` layer the gateway would add around the usual body.
+fn unwrap_data(body: Value) -> Value {
+    match body {
+        Value::Object(mut map) if map.contains_key("data") => map.remove("data").unwrap_or_default(),
+        other => other,
+    }
+}
+
+#[tokio::test]
+async fn successful_bodies_are_transformed() {
+    let transport = MockTransport::always(ok(json!({ "data": { "user": { "pk": "1", "username": "natgeo" } } })));
+    let mut api = instagram(&transport);
+    api.api.set_body_transform(unwrap_data);
+    let body = api.get_user_info("natgeo").await.unwrap();
+    assert_eq!(body, json!({ "user": { "pk": "1", "username": "natgeo" } }));
+    // Typed methods parse the transformed body, `last_response` keeps the envelope as received.
+    let user = api.get_user_info_typed("natgeo").await.unwrap();
+    assert_eq!((user.pk, user.username.as_str()), (1, "natgeo"));
+    assert!(api.last_response["response"]["body"]["data"].is_object());
+}
+
+#[tokio::test]
+async fn error_envelopes_are_not_transformed() {
+    let transport = MockTransport::always(envelope(404, json!({ "message": "not found" })));
+    let mut api = instagram(&transport);
+    api.api.set_body_transform(|_| panic!("transform called for an error"));
+    let result = api.get_media_info(&1).await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+}
+
+#[tokio::test]
+async fn clones_keep_the_transform_until_cleared() {
+    let transport = MockTransport::always(ok(json!({ "count": 1 })));
+    let mut api = instagram(&transport);
+    api.api.set_body_transform(|mut body| {
+        body["count"] = json!(body["count"].as_u64().unwrap_or(0) * 10);
+        body
+    });
+    let mut clone = api.clone();
+    assert_eq!(clone.get_media_info(&1).await.unwrap(), json!({ "count": 10 }));
+    api.api.clear_body_transform();
+    assert_eq!(api.get_media_info(&1).await.unwrap(), json!({ "count": 1 }));
+    assert_eq!(clone.get_media_info(&1).await.unwrap(), json!({ "count": 10 }));
+}
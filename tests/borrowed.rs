@@ -0,0 +1,59 @@
+use std::borrow::Cow;
+
+use rocketapi::borrowed::{parse_borrowed, UserRef, UsersPageRef};
+use rocketapi::errors::RocketAPIError;
+
+fn page_text() -> String {
+    r#"{"status": "done", "response": {"status_code": 200, "content_type": "application/json", "body": {
+        "users": [
+            {"pk": "1", "username": "natgeo", "full_name": "National Geographic", "is_verified": true},
+            {"pk": 2, "username": "caf\u00e9", "full_name": null}
+        ],
+        "next_max_id": "QVFE"
+    }}}"#
+        .to_string()
+}
+
+fn points_into(text: &str, value: &str) -> bool {
+    let range = text.as_bytes().as_ptr_range();
+    range.contains(&value.as_ptr())
+}
+
+#[test]
+fn unescaped_strings_are_borrowed_from_the_input() {
+    let raw = page_text();
+    let page: UsersPageRef = parse_borrowed(&raw).unwrap();
+    let natgeo = &page.users[0];
+    assert_eq!((natgeo.pk, natgeo.is_verified), (1, Some(true)));
+    for value in [&natgeo.username, natgeo.full_name.as_ref().unwrap(), page.next_max_id.as_ref().unwrap()] {
+        assert!(matches!(value, Cow::Borrowed(_)), "{:?}", value);
+        assert!(points_into(&raw, value), "{:?} was copied", value);
+    }
+    // An escaped string has to be decoded, so it's owned.
+    assert!(matches!(page.users[1].username, Cow::Owned(_)));
+    assert_eq!(page.users[1].username, "café");
+    assert_eq!(page.users[1].full_name, None);
+}
+
+#[test]
+fn into_owned_keeps_the_data_past_the_buffer() {
+    let owned: UserRef<'static> = {
+        let raw = page_text();
+        let page: UsersPageRef = parse_borrowed(&raw).unwrap();
+        page.users.into_iter().next().unwrap().into_owned()
+    };
+    assert!(matches!(owned.username, Cow::Owned(_)));
+    assert_eq!(owned.username, "natgeo");
+    assert_eq!(owned.full_name.as_deref(), Some("National Geographic"));
+    assert_eq!((owned.pk, owned.is_verified, owned.is_private), (1, Some(true), None));
+}
+
+#[test]
+fn failed_envelopes_give_the_client_errors() {
+    let not_found = r#"{"status": "done", "response": {"status_code": 404, "content_type": "application/json", "body": {}}}"#;
+    let result = parse_borrowed::<UsersPageRef>(not_found);
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+    let malformed = r#"{"status": "done", "response": {"status_code": 200, "content_type": "application/json", "body": {"users": [{"pk": 1}]}}}"#;
+    let result = parse_borrowed::<UsersPageRef>(malformed);
+    assert!(matches!(result, Err(RocketAPIError::ParseError(_))), "{:?}", result);
+}
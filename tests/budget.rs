@@ -0,0 +1,35 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::retry::RetryPolicy;
+use serde_json::json;
+
+#[tokio::test]
+async fn budget_cutoff_happens_before_the_transport() {
+    let transport = MockTransport::always(ok(json!({ "user": {} })));
+    let mut api = instagram(&transport);
+    api.api.set_credit_budget(Some(2));
+    api.get_user_info("natgeo").await.unwrap();
+    api.get_user_info("nasa").await.unwrap();
+    match api.get_user_info("nike").await {
+        Err(RocketAPIError::BudgetExceeded { spent: 2, budget: 2 }) => {}
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(transport.call_count(), 2);
+    assert_eq!(api.api.remaining_budget(), Some(0));
+}
+
+#[tokio::test]
+async fn retries_are_charged_once() {
+    let transport = MockTransport::scripted(vec![Ok(envelope(500, json!({}))), Ok(envelope(500, json!({}))), Ok(ok(json!({ "user": {} })))]);
+    let mut api = instagram(&transport);
+    let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(1), Duration::from_secs(1)).unwrap();
+    api.api.set_retry_policy(policy).unwrap();
+    api.api.set_credit_budget(Some(1));
+    api.get_user_info("natgeo").await.unwrap();
+    assert_eq!(transport.call_count(), 3);
+    assert_eq!(api.api.credits_spent(), Some(1));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{envelope, instagram, ok, threads, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use serde_json::json;
+
+fn transport() -> std::sync::Arc<MockTransport> {
+    MockTransport::scripted(vec![Ok(ok(json!({ "pk": 1 }))), Ok(envelope(404, json!({ "message": "not found" })))])
+}
+
+// The same bookkeeping is expected from `call` on both clients.
+macro_rules! check_call {
+    ($client:expr, $method:expr) => {{
+        let transport = transport();
+        let mut api = $client(&transport);
+        let body = api.call($method, json!({ "id": 1 })).await.unwrap();
+        assert_eq!(body, json!({ "pk": 1 }));
+        assert_eq!(api.counter, 1);
+        assert_eq!(api.last_response, ok(json!({ "pk": 1 })));
+        assert_eq!(api.last_retries, 0);
+        assert_eq!(api.last_attempts().len(), 1);
+
+        // An error envelope still counts as a response.
+        let result = api.call($method, json!({ "id": 2 })).await;
+        assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+        assert_eq!(api.counter, 2);
+        assert_eq!(api.last_response["response"]["status_code"], 404);
+        let stats = api.stats()[$method];
+        assert_eq!((stats.calls, stats.successes, stats.errors), (2, 1, 1));
+        assert_eq!(transport.calls(), vec![($method.to_string(), json!({ "id": 1 })), ($method.to_string(), json!({ "id": 2 }))]);
+    }};
+}
+
+#[tokio::test]
+async fn instagram_call_keeps_the_client_state() {
+    check_call!(instagram, "instagram/user/get_info_by_id");
+}
+
+#[tokio::test]
+async fn threads_call_keeps_the_client_state() {
+    check_call!(threads, "threads/user/get_info");
+}
+
+// `reset` forgets the previous calls but keeps the stats.
+macro_rules! check_reset {
+    ($client:expr, $method:expr) => {{
+        let transport = transport();
+        let mut api = $client(&transport);
+        api.call($method, json!({ "id": 1 })).await.unwrap();
+        api.reset();
+        assert_eq!(api.counter, 0);
+        assert!(api.last_response.is_null());
+        assert_eq!(api.last_retries, 0);
+        assert!(api.last_attempts().is_empty());
+        assert_eq!(api.last_status(), None);
+        assert_eq!(api.stats()[$method].calls, 1);
+        // Counting starts over with the next call.
+        api.call($method, json!({ "id": 2 })).await.unwrap_err();
+        assert_eq!(api.counter, 1);
+    }};
+}
+
+#[tokio::test]
+async fn instagram_reset_forgets_the_previous_calls() {
+    check_reset!(instagram, "instagram/user/get_info_by_id");
+}
+
+#[tokio::test]
+async fn threads_reset_forgets_the_previous_calls() {
+    check_reset!(threads, "threads/user/get_info");
+}
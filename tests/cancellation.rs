@@ -0,0 +1,65 @@
+#![cfg(feature = "cancellation")]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{instagram, ok, HttpResponse, MockServer, MockTransport, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::retry::RetryPolicy;
+use rocketapi::RocketAPI;
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+
+fn cancel_after(token: &CancellationToken, delay: Duration) {
+    let token = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        token.cancel();
+    });
+}
+
+#[tokio::test]
+async fn cancelling_during_a_long_backoff_returns_right_away() {
+    let server = MockServer::start(|_| HttpResponse::text(503, "<html>503 Service Unavailable</html>"));
+    let mut api = RocketAPI::with_base_url("test-token".to_string(), TIMEOUT, &server.url()).unwrap();
+    let policy = RetryPolicy::new(3, Duration::from_secs(30), Duration::from_secs(30), Duration::from_secs(60)).unwrap();
+    api.set_retry_policy(policy).unwrap();
+    let token = CancellationToken::new();
+    api.set_cancellation_token(Some(token.clone()));
+    cancel_after(&token, Duration::from_millis(100));
+    let start = Instant::now();
+    let result = api.request("instagram/user/get_info", json!({ "username": "natgeo" })).await;
+    assert!(matches!(result, Err(RocketAPIError::Cancelled)), "{:?}", result);
+    assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+    // The first attempt failed, the cancellation came during the 30s backoff before the second one.
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn a_cancelled_call_leaves_the_client_state_unchanged() {
+    let transport = MockTransport::with_delay(Duration::from_millis(200), |_, payload| Ok(ok(json!({ "user": { "pk": payload["id"] } }))));
+    let mut api = instagram(&transport);
+    let token = CancellationToken::new();
+    api.api.set_cancellation_token(Some(token.clone()));
+    api.get_user_info_by_id(&1).await.unwrap();
+    let (counter, last_response, stats) = (api.counter, api.last_response.clone(), api.stats());
+    assert_eq!(counter, 1);
+
+    // Cancelled while the request is in flight...
+    cancel_after(&token, Duration::from_millis(20));
+    let result = api.get_user_info_by_id(&2).await;
+    assert!(matches!(result, Err(RocketAPIError::Cancelled)), "{:?}", result);
+    assert_eq!(transport.call_count(), 2);
+    assert_eq!(api.counter, counter);
+    assert_eq!(api.last_response, last_response);
+    assert_eq!(api.stats(), stats);
+
+    // ...and before it starts: no request is sent.
+    let result = api.get_user_info_by_id(&3).await;
+    assert!(matches!(result, Err(RocketAPIError::Cancelled)), "{:?}", result);
+    assert_eq!(transport.call_count(), 2);
+    assert_eq!(api.counter, counter);
+    assert_eq!(api.last_response, last_response);
+    assert_eq!(api.stats(), stats);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::helpers::expand_carousel;
+use serde_json::{json, Value};
+
+fn carousel(pk: u64) -> Value {
+    json!({
+        "id": format!("{}_1", pk),
+        "pk": pk,
+        "code": format!("C{}", pk),
+        "media_type": 8,
+        "taken_at": 1700000000,
+        "like_count": 10,
+        "comment_count": 2,
+        "user": { "pk": 1, "username": "natgeo" },
+        "caption": { "text": "three photos" },
+        "carousel_media": [
+            { "id": format!("{}1_1", pk), "pk": pk * 10 + 1, "media_type": 1 },
+            { "id": format!("{}2_1", pk), "pk": pk * 10 + 2, "media_type": 2, "like_count": 99 },
+            { "id": format!("{}3_1", pk), "pk": pk * 10 + 3, "media_type": 1, "caption": null },
+        ],
+    })
+}
+
+#[test]
+fn children_inherit_the_post_fields_they_lack() {
+    let children = expand_carousel(&carousel(5));
+    assert_eq!(children.len(), 3);
+    let pks: Vec<u64> = children.iter().map(|child| child["pk"].as_u64().unwrap()).collect();
+    assert_eq!(pks, vec![51, 52, 53]);
+    for child in &children {
+        assert_eq!(child["code"], "C5");
+        assert_eq!(child["taken_at"], 1700000000);
+        assert_eq!(child["comment_count"], 2);
+        assert_eq!(child["user"]["username"], "natgeo");
+        // Children keep their own media type and don't get the children list.
+        assert_ne!(child["media_type"], 8);
+        assert!(child.get("carousel_media").is_none());
+    }
+    assert_eq!(children[0]["like_count"], 10);
+    // Fields the child has are kept, even null ones.
+    assert_eq!(children[1]["like_count"], 99);
+    assert_eq!(children[0]["caption"]["text"], "three photos");
+    assert!(children[2]["caption"].is_null());
+}
+
+#[test]
+fn other_items_are_returned_as_is() {
+    let photo = json!({ "id": "1_1", "pk": 1, "media_type": 1, "code": "C1" });
+    assert_eq!(expand_carousel(&photo), vec![photo.clone()]);
+    let empty = json!({ "id": "2_1", "pk": 2, "media_type": 8, "carousel_media": [] });
+    assert_eq!(expand_carousel(&empty), vec![empty.clone()]);
+    // Missing post fields are not added as nulls.
+    let bare = json!({ "pk": 3, "carousel_media": [{ "pk": 31 }] });
+    assert_eq!(expand_carousel(&bare), vec![json!({ "pk": 31 })]);
+}
+
+#[tokio::test]
+async fn pages_are_expanded_when_expand_carousels_is_set() {
+    let page = ok(json!({ "items": [carousel(5), { "id": "6_1", "pk": 6, "media_type": 1 }], "next_max_id": null }));
+    let transport = MockTransport::always(page);
+    let mut api = instagram(&transport);
+    let page = api.get_user_media_page(&1, None, None).await.unwrap();
+    assert_eq!(page.items.iter().map(|item| item.pk).collect::<Vec<_>>(), vec![5, 6]);
+
+    api.expand_carousels = true;
+    let page = api.get_user_media_page(&1, None, None).await.unwrap();
+    assert_eq!(page.items.iter().map(|item| item.pk).collect::<Vec<_>>(), vec![51, 52, 53, 6]);
+    assert_eq!(page.items[0].code.as_deref(), Some("C5"));
+    let all = api.get_all_user_media(&1, None).await.unwrap();
+    assert_eq!(all.items.iter().map(|item| item.pk).collect::<Vec<_>>(), vec![51, 52, 53, 6]);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::instagramapi::InstagramAPI;
+use serde_json::{json, Value};
+
+fn followers() -> std::sync::Arc<MockTransport> {
+    MockTransport::with_delay(Duration::from_millis(100), |_, payload| {
+        Ok(ok(json!({ "users": [{ "pk": 1 }], "next_max_id": format!("after-{}", payload["max_id"].as_str().unwrap_or("start")) })))
+    })
+}
+
+async fn twice(api: &InstagramAPI, max_id: Option<&str>) -> (Value, Value) {
+    let (mut first, mut second) = (api.clone(), api.clone());
+    let (first, second) = tokio::join!(
+        first.get_user_followers(&1, Some(50), max_id),
+        second.get_user_followers(&1, Some(50), max_id),
+    );
+    (first.unwrap(), second.unwrap())
+}
+
+#[tokio::test]
+async fn identical_next_page_requests_in_flight_share_one_call() {
+    let transport = followers();
+    let mut api = instagram(&transport);
+    api.api.set_page_coalescing(true);
+    let (first, second) = twice(&api, Some("p2")).await;
+    assert_eq!(transport.call_count(), 1);
+    assert_eq!(first, second);
+    assert_eq!(first["next_max_id"], "after-p2");
+    // Once the call is done, the same request is sent again.
+    twice(&api, Some("p2")).await;
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn first_pages_and_disabled_clients_are_not_coalesced() {
+    let transport = followers();
+    let mut api = instagram(&transport);
+    api.api.set_page_coalescing(true);
+    twice(&api, None).await;
+    assert_eq!(transport.call_count(), 2);
+
+    let transport = followers();
+    let api = instagram(&transport);
+    twice(&api, Some("p2")).await;
+    assert_eq!(transport.call_count(), 2);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use serde_json::{json, Value};
+
+fn replies(pks: &[u64]) -> Vec<Value> {
+    pks.iter().map(|pk| json!({ "pk": pk, "text": format!("reply {}", pk) })).collect()
+}
+
+fn pks(items: &[Value]) -> Vec<u64> {
+    items.iter().map(|item| item["pk"].as_u64().unwrap()).collect()
+}
+
+// Every page also has a decoy `next_max_id`: following it would request a page that doesn't exist.
+fn reply_pages(endpoint: &str, payload: &Value) -> Result<Value, RocketAPIError> {
+    assert_eq!(endpoint, "instagram/comment/get_replies");
+    Ok(match payload["max_id"].as_str() {
+        None => ok(json!({ "child_comments": replies(&[1, 2]), "next_max_child_cursor": "c2", "next_max_id": "wrong", "has_more_tail_child_comments": true })),
+        Some("c2") => ok(json!({ "child_comments": replies(&[3, 4]), "next_max_child_cursor": "c3", "next_max_id": "wrong", "has_more_tail_child_comments": true })),
+        Some("c3") => ok(json!({ "child_comments": replies(&[5]), "next_max_id": "wrong" })),
+        Some(cursor) => panic!("unexpected cursor {}", cursor),
+    })
+}
+
+#[tokio::test]
+async fn pages_follow_next_max_child_cursor() {
+    let transport = MockTransport::new(reply_pages);
+    let mut api = instagram(&transport);
+    let all = api.get_all_comment_replies(&9, &7, 100).await.unwrap();
+    assert_eq!(pks(&all), vec![1, 2, 3, 4, 5]);
+    assert_eq!(
+        transport.payloads(),
+        vec![
+            json!({ "id": 9, "media_id": 7 }),
+            json!({ "id": 9, "media_id": 7, "max_id": "c2" }),
+            json!({ "id": 9, "media_id": 7, "max_id": "c3" }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn max_items_truncates_and_saves_requests() {
+    let transport = MockTransport::new(reply_pages);
+    let mut api = instagram(&transport);
+    assert_eq!(pks(&api.get_all_comment_replies(&9, &7, 3).await.unwrap()), vec![1, 2, 3]);
+    assert_eq!(transport.call_count(), 2);
+    assert!(api.get_all_comment_replies(&9, &7, 0).await.unwrap().is_empty());
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn the_crawl_stops_at_the_last_or_a_repeated_cursor() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "child_comments": replies(&[1]), "next_max_child_cursor": "c2", "has_more_tail_child_comments": false }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert_eq!(pks(&api.get_all_comment_replies(&9, &7, 100).await.unwrap()), vec![1]);
+
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "child_comments": replies(&[1]), "next_max_child_cursor": "c2" }))),
+        Ok(ok(json!({ "child_comments": replies(&[2]), "next_max_child_cursor": "c2" }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert_eq!(pks(&api.get_all_comment_replies(&9, &7, 100).await.unwrap()), vec![1, 2]);
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn the_error_of_a_later_page_is_returned() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "child_comments": replies(&[1]), "next_max_child_cursor": "c2" }))),
+        Ok(envelope(404, json!({ "message": "Comment not found" }))),
+    ]);
+    let mut api = instagram(&transport);
+    let result = api.get_all_comment_replies(&9, &7, 100).await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+}
@@ -0,0 +1,269 @@
+// Shared by the integration tests: a scripted `Transport` and a minimal local HTTP server.
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::threadsapi::ThreadsAPI;
+use rocketapi::transport::{Transport, TransportFuture};
+use rocketapi::RocketAPI;
+use serde_json::{json, Value};
+
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn envelope(status_code: i64, body: Value) -> Value {
+    json!({
+        "status": "done",
+        "response": { "status_code": status_code, "content_type": "application/json", "body": body },
+    })
+}
+
+pub fn ok(body: Value) -> Value {
+    envelope(200, body)
+}
+
+type Handler = Box<dyn Fn(&str, &Value) -> Result<Value, RocketAPIError> + Send + Sync>;
+
+pub struct MockTransport {
+    /*
+    Answers every call with `handler`, after the queued responses (if any) are used up,
+    and keeps the `(method, payload)` of every call.
+    */
+    calls: Mutex<Vec<(String, Value)>>,
+    queue: Mutex<VecDeque<Result<Value, RocketAPIError>>>,
+    handler: Handler,
+    delay: Duration,
+}
+
+impl MockTransport {
+    pub fn new(handler: impl Fn(&str, &Value) -> Result<Value, RocketAPIError> + Send + Sync + 'static) -> Arc<Self> {
+        Arc::new(MockTransport { calls: Mutex::new(Vec::new()), queue: Mutex::new(VecDeque::new()), handler: Box::new(handler), delay: Duration::ZERO })
+    }
+
+    pub fn with_delay(delay: Duration, handler: impl Fn(&str, &Value) -> Result<Value, RocketAPIError> + Send + Sync + 'static) -> Arc<Self> {
+        Arc::new(MockTransport { calls: Mutex::new(Vec::new()), queue: Mutex::new(VecDeque::new()), handler: Box::new(handler), delay })
+    }
+
+    pub fn always(response: Value) -> Arc<Self> {
+        Self::new(move |_, _| Ok(response.clone()))
+    }
+
+    pub fn scripted(responses: Vec<Result<Value, RocketAPIError>>) -> Arc<Self> {
+        let transport = Self::new(|method, _| panic!("unexpected call to {}", method));
+        transport.queue.lock().unwrap().extend(responses);
+        transport
+    }
+
+    pub fn calls(&self) -> Vec<(String, Value)> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.calls.lock().unwrap().len()
+    }
+
+    pub fn payloads(&self) -> Vec<Value> {
+        self.calls().into_iter().map(|(_, payload)| payload).collect()
+    }
+}
+
+impl Transport for MockTransport {
+    fn call(&self, method: &str, payload: Value) -> TransportFuture {
+        self.calls.lock().unwrap().push((method.to_string(), payload.clone()));
+        let queued = self.queue.lock().unwrap().pop_front();
+        let result = queued.unwrap_or_else(|| (self.handler)(method, &payload));
+        let delay = self.delay;
+        Box::pin(async move {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            result
+        })
+    }
+}
+
+pub fn rocket(transport: &Arc<MockTransport>) -> RocketAPI {
+    RocketAPI::with_transport("test-token".to_string(), TIMEOUT, transport.clone())
+}
+
+pub fn instagram(transport: &Arc<MockTransport>) -> InstagramAPI {
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api = rocket(transport);
+    api
+}
+
+pub fn threads(transport: &Arc<MockTransport>) -> ThreadsAPI {
+    let mut api = ThreadsAPI::new("test-token".to_string(), TIMEOUT);
+    api.api = rocket(transport);
+    api
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    // Index of the TCP connection the request came on, in accept order.
+    pub connection: usize,
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub delay: Duration,
+}
+
+impl HttpResponse {
+    pub fn json(status: u16, body: &Value) -> Self {
+        HttpResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string().into_bytes(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn text(status: u16, body: &str) -> Self {
+        HttpResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), "text/html".to_string())],
+            body: body.as_bytes().to_vec(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn redirect(location: &str) -> Self {
+        HttpResponse { status: 302, headers: vec![("Location".to_string(), location.to_string())], body: Vec::new(), delay: Duration::ZERO }
+    }
+
+    pub fn reset() -> Self {
+        // Closes the connection without answering, as a connection reset mid-request.
+        HttpResponse { status: 0, headers: Vec::new(), body: Vec::new(), delay: Duration::ZERO }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+pub struct MockServer {
+    /*
+    HTTP/1.1 server on a random local port, one request per connection (or many with `keep_alive`),
+    answering with `handler`. The server thread lives until the test process exits.
+    */
+    pub port: u16,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+}
+
+impl MockServer {
+    pub fn start(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static) -> Self {
+        Self::serve(handler, false)
+    }
+
+    pub fn keep_alive(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static) -> Self {
+        Self::serve(handler, true)
+    }
+
+    fn serve(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static, keep_alive: bool) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler = Arc::new(handler);
+        let log = requests.clone();
+        thread::spawn(move || {
+            for (connection, stream) in listener.incoming().flatten().enumerate() {
+                let handler = handler.clone();
+                let log = log.clone();
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(&stream);
+                    while let Some(request) = read_request(&mut reader, connection) {
+                        log.lock().unwrap().push(request.clone());
+                        write_response(&stream, &handler(&request), keep_alive);
+                        if !keep_alive {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        MockServer { port, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}/", self.port)
+    }
+
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(reader: &mut BufReader<&TcpStream>, connection: usize) -> Option<HttpRequest> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    Some(HttpRequest { connection, method, path, headers, body })
+}
+
+fn write_response(mut stream: &TcpStream, response: &HttpResponse, keep_alive: bool) {
+    thread::sleep(response.delay);
+    if response.status == 0 {
+        let _ = stream.shutdown(std::net::Shutdown::Both);
+        return;
+    }
+    let connection = if keep_alive { "keep-alive" } else { "close" };
+    let mut head = format!("HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: {}\r\n", response.status, response.body.len(), connection);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    // The client may have given up already (timeout tests), write errors are expected then.
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(&response.body);
+}
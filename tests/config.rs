@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::retry::RetryPolicy;
+
+// Writes `content` to a file of the temporary directory, unique to the test.
+fn config_file(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rocketapi-config-{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn config_error(result: Result<InstagramAPI, RocketAPIError>) -> String {
+    match result {
+        Err(RocketAPIError::InvalidParameter(message)) => message,
+        other => panic!("{:?}", other.map(|api| api.default_counts)),
+    }
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn the_documented_toml_sample_is_loaded() {
+    use rocketapi::config::RocketAPIConfig;
+    use rocketapi::instagramapi::DefaultCounts;
+
+    std::env::set_var("ROCKETAPI_CONFIG_TEST_TOKEN", "toml-token");
+    let path = config_file(
+        "sample.toml",
+        r#"
+        token_env = "ROCKETAPI_CONFIG_TEST_TOKEN"
+        timeout_secs = 30
+        strict = false
+
+        [retry]
+        max_attempts = 5
+        base_delay_ms = 500
+        max_delay_ms = 8000
+        max_total_delay_ms = 20000
+        jitter_ms = 250
+
+        [default_counts]
+        media = 50
+        followers = 100
+        "#,
+    );
+    let config = RocketAPIConfig::from_file(&path).unwrap();
+    assert_eq!(config.resolve_token().unwrap(), "toml-token");
+    let api = InstagramAPI::from_config(&config).unwrap();
+    assert_eq!(api.api.timeout(), Duration::from_secs(30));
+    let retry = RetryPolicy::new(5, Duration::from_millis(500), Duration::from_secs(8), Duration::from_secs(20)).unwrap();
+    assert_eq!(*api.api.retry_policy(), retry.with_jitter(Duration::from_millis(250)));
+    assert_eq!(api.default_counts, DefaultCounts { media: 50, followers: 100, ..DefaultCounts::default() });
+    assert!(!api.api.strict());
+}
+
+#[test]
+fn a_json_config_sets_the_client_options() {
+    let path = config_file(
+        "client.json",
+        r#"{
+            "token": "json-token",
+            "timeout_secs": 2.5,
+            "base_url": "http://127.0.0.1:9/",
+            "strict": true,
+            "default_counts": { "following": 150 },
+            "max_count_by_default": true,
+            "prefetch_pages": true
+        }"#,
+    );
+    let api = InstagramAPI::from_config_file(&path).unwrap();
+    assert_eq!(api.api.timeout(), Duration::from_millis(2500));
+    assert_eq!(api.api.base_url(), "http://127.0.0.1:9/");
+    assert_eq!(*api.api.retry_policy(), RetryPolicy::none());
+    assert!(api.api.strict());
+    assert_eq!(api.default_counts.following, 150);
+    assert!(api.max_count_by_default && api.prefetch_pages);
+}
+
+#[test]
+fn a_bad_timeout_is_rejected() {
+    for timeout in ["0", "-1"] {
+        let path = config_file(&format!("timeout{}.json", timeout), &format!(r#"{{ "token": "t", "timeout_secs": {} }}"#, timeout));
+        let message = config_error(InstagramAPI::from_config_file(&path));
+        assert!(message.starts_with("config: timeout_secs must be positive"), "{}", message);
+    }
+    let path = config_file("timeout-string.json", r#"{ "token": "t", "timeout_secs": "30s" }"#);
+    let message = config_error(InstagramAPI::from_config_file(&path));
+    assert!(message.starts_with("config: invalid type"), "{}", message);
+}
+
+#[test]
+fn the_token_source_must_be_given_once() {
+    let path = config_file("no-token.json", r#"{ "timeout_secs": 30 }"#);
+    assert_eq!(config_error(InstagramAPI::from_config_file(&path)), "config: token or token_env is required");
+
+    let path = config_file("both-tokens.json", r#"{ "token": "t", "token_env": "ROCKETAPI_CONFIG_TEST_TOKEN" }"#);
+    assert_eq!(config_error(InstagramAPI::from_config_file(&path)), "config: both token and token_env are set");
+
+    let path = config_file("unset-env.json", r#"{ "token_env": "ROCKETAPI_CONFIG_TEST_UNSET" }"#);
+    assert_eq!(config_error(InstagramAPI::from_config_file(&path)), "config: environment variable ROCKETAPI_CONFIG_TEST_UNSET is not set");
+}
+
+#[test]
+fn unknown_keys_and_formats_are_rejected() {
+    let path = config_file("typo.json", r#"{ "token": "t", "timeout": 30 }"#);
+    let message = config_error(InstagramAPI::from_config_file(&path));
+    assert!(message.contains("unknown field `timeout`"), "{}", message);
+
+    let path = config_file("client.yaml", "token: t");
+    let message = config_error(InstagramAPI::from_config_file(&path));
+    assert!(message.starts_with("config: unsupported config format"), "{}", message);
+}
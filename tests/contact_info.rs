@@ -0,0 +1,78 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::models::{BioLink, ContactInfo};
+use serde_json::json;
+
+fn link(url: &str, title: Option<&str>) -> BioLink {
+    BioLink { url: url.to_string(), title: title.map(str::to_string) }
+}
+
+#[tokio::test]
+async fn business_accounts_show_their_contact_details() {
+    let transport = MockTransport::always(ok(json!({
+        "data": {
+            "user": {
+                "id": "1528",
+                "username": "bluebottle",
+                "is_business_account": true,
+                "external_url": "https://bluebottlecoffee.com/",
+                "bio_links": [
+                    { "title": "Shop", "url": "https://bluebottlecoffee.com/shop", "link_type": "external" },
+                    { "title": "", "url": "https://bluebottlecoffee.com/cafes", "link_type": "external" },
+                    { "title": "broken" },
+                ],
+                "public_email": "hello@bluebottlecoffee.com",
+                "public_phone_country_code": "1",
+                "public_phone_number": 5106616161u64,
+            }
+        },
+        "status": "ok",
+    })));
+    let mut api = instagram(&transport);
+    let info = api.get_user_contact_info("bluebottle").await.unwrap();
+    assert_eq!(
+        info,
+        ContactInfo {
+            external_url: Some("https://bluebottlecoffee.com/".to_string()),
+            // Links without a url are dropped, empty titles are `None`.
+            bio_links: vec![
+                link("https://bluebottlecoffee.com/shop", Some("Shop")),
+                link("https://bluebottlecoffee.com/cafes", None),
+            ],
+            public_email: Some("hello@bluebottlecoffee.com".to_string()),
+            public_phone_number: Some("5106616161".to_string()),
+            public_phone_country_code: Some("1".to_string()),
+        }
+    );
+    assert_eq!(transport.payloads(), vec![json!({ "username": "bluebottle" })]);
+}
+
+#[tokio::test]
+async fn web_profiles_fall_back_to_the_business_fields() {
+    let transport = MockTransport::always(ok(json!({
+        "data": { "user": { "id": "1528", "public_email": "", "business_email": "press@example.com", "business_phone_number": " " } }
+    })));
+    let mut api = instagram(&transport);
+    let info = api.get_user_contact_info("bluebottle").await.unwrap();
+    assert_eq!(info.public_email.as_deref(), Some("press@example.com"));
+    assert_eq!(info.public_phone_number, None);
+}
+
+#[tokio::test]
+async fn personal_accounts_give_empty_fields() {
+    let transport = MockTransport::always(ok(json!({
+        "user": { "pk": 5, "username": "quiet", "external_url": "", "bio_links": null, "public_email": null }
+    })));
+    let mut api = instagram(&transport);
+    assert_eq!(api.get_user_contact_info("quiet").await.unwrap(), ContactInfo::default());
+}
+
+#[tokio::test]
+async fn unknown_users_are_not_found() {
+    let transport = MockTransport::always(ok(json!({ "data": { "user": null }, "status": "ok" })));
+    let mut api = instagram(&transport);
+    let result = api.get_user_contact_info("nobody").await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+}
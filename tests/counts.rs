@@ -0,0 +1,181 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::{DefaultCounts, InstagramAPI};
+use serde_json::json;
+
+fn last_count(transport: &MockTransport) -> serde_json::Value {
+    transport.payloads().last().unwrap()["count"].clone()
+}
+
+#[tokio::test]
+async fn explicit_counts_are_sent_as_is() {
+    let transport = MockTransport::always(ok(json!({ "items": [], "users": [] })));
+    let mut api = instagram(&transport);
+    api.get_user_media(&1, Some(20), None).await.unwrap();
+    assert_eq!(last_count(&transport), 20);
+    api.get_user_followers(&1, Some(100), None).await.unwrap();
+    assert_eq!(last_count(&transport), 100);
+    // Validation still applies.
+    let result = api.get_user_media(&1, Some(51), None).await;
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result);
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn omitted_counts_use_the_client_defaults() {
+    let transport = MockTransport::always(ok(json!({ "items": [], "users": [] })));
+    let mut api = instagram(&transport);
+    api.get_user_media(&1, None, None).await.unwrap();
+    assert_eq!(last_count(&transport), 12);
+    api.default_counts = DefaultCounts { media: 30, followers: 40, ..DefaultCounts::default() };
+    api.get_user_media(&1, None, None).await.unwrap();
+    assert_eq!(last_count(&transport), 30);
+    api.get_user_followers(&1, None, None).await.unwrap();
+    assert_eq!(last_count(&transport), 40);
+    api.max_count_by_default = true;
+    api.get_user_following(&1, None, None).await.unwrap();
+    assert_eq!(last_count(&transport), 200);
+}
+
+#[tokio::test]
+async fn collect_helpers_request_the_endpoint_maximum() {
+    let transport = MockTransport::always(ok(json!({ "items": [], "users": [] })));
+    let mut api = instagram(&transport);
+    api.get_user_media_all(&1, None, None).await.unwrap();
+    assert_eq!(last_count(&transport), 50);
+    api.get_all_user_followers(&1, None).await.unwrap();
+    assert_eq!(last_count(&transport), 100);
+    api.get_all_user_following(&1, None).await.unwrap();
+    assert_eq!(last_count(&transport), 200);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn stream_helpers_request_the_endpoint_maximum() {
+    use futures::TryStreamExt;
+
+    let transport = MockTransport::always(ok(json!({ "items": [], "users": [] })));
+    let mut api = instagram(&transport);
+    let _: Vec<_> = api.stream_comment_likes(&1).try_collect().await.unwrap();
+    assert_eq!(last_count(&transport), 50);
+    let _: Vec<_> = api.stream_media_since(&1, 0, None).try_collect().await.unwrap();
+    assert_eq!(last_count(&transport), 50);
+    let _: Vec<_> = api.stream_user_media(&1, 25).try_collect().await.unwrap();
+    assert_eq!(last_count(&transport), 25);
+}
+
+#[test]
+fn builder_rejects_default_counts_out_of_range() {
+    for (counts, field) in [
+        (DefaultCounts { media: 0, ..DefaultCounts::default() }, "default_counts.media"),
+        (DefaultCounts { followers: 101, ..DefaultCounts::default() }, "default_counts.followers"),
+        (DefaultCounts { following: 201, ..DefaultCounts::default() }, "default_counts.following"),
+        (DefaultCounts { likes: 51, ..DefaultCounts::default() }, "default_counts.likes"),
+    ] {
+        match InstagramAPI::builder().token("test-token").default_counts(counts).build() {
+            Err(RocketAPIError::InvalidParameter(message)) => assert!(message.starts_with(field), "{}", message),
+            other => panic!("{:?}", other.map(|api| api.default_counts)),
+        }
+    }
+    let api = InstagramAPI::builder().token("test-token").default_counts(DefaultCounts::maximum()).build().unwrap();
+    assert_eq!(api.default_counts, DefaultCounts::maximum());
+}
+
+#[tokio::test]
+async fn comment_likes_send_count_only_when_given() {
+    let transport = MockTransport::always(ok(json!({ "users": [] })));
+    let mut api = instagram(&transport);
+    api.get_comment_likes(&5, None, None).await.unwrap();
+    api.get_comment_likes(&5, Some(30), Some("QVFD")).await.unwrap();
+    // The client defaults don't apply either: the endpoint picks its own page size.
+    api.max_count_by_default = true;
+    api.get_comment_likes(&5, None, Some("QVFE")).await.unwrap();
+    assert_eq!(
+        transport.payloads(),
+        vec![json!({ "id": 5 }), json!({ "id": 5, "count": 30, "max_id": "QVFD" }), json!({ "id": 5, "max_id": "QVFE" })]
+    );
+    let result = api.get_comment_likes(&5, Some(51), None).await;
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result);
+    assert_eq!(transport.call_count(), 3);
+}
+
+#[tokio::test]
+async fn max_count_by_default_widens_every_list_endpoint() {
+    let transport = MockTransport::always(ok(json!({ "items": [], "users": [] })));
+    let mut api = instagram(&transport);
+    api.default_counts = DefaultCounts { media: 5, followers: 5, following: 5, likes: 5 };
+    api.max_count_by_default = true;
+    api.get_user_media(&1, None, None).await.unwrap();
+    api.get_user_clips(&1, None, None).await.unwrap();
+    api.get_user_tags(&1, None, None).await.unwrap();
+    api.get_user_followers(&1, None, None).await.unwrap();
+    api.get_user_following(&1, None, None).await.unwrap();
+    api.get_media_likes("Cx1", None, None).await.unwrap();
+    let sent: Vec<(String, serde_json::Value)> = transport.calls().into_iter().map(|(method, payload)| (method, payload["count"].clone())).collect();
+    assert_eq!(
+        sent,
+        vec![
+            ("instagram/user/get_media".to_string(), json!(50)),
+            ("instagram/user/get_clips".to_string(), json!(50)),
+            ("instagram/user/get_tags".to_string(), json!(50)),
+            ("instagram/user/get_followers".to_string(), json!(100)),
+            ("instagram/user/get_following".to_string(), json!(200)),
+            ("instagram/media/get_likes".to_string(), json!(50)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn max_count_by_default_keeps_explicit_counts_and_bounds() {
+    let transport = MockTransport::always(ok(json!({ "items": [], "users": [] })));
+    let mut api = instagram(&transport);
+    api.max_count_by_default = true;
+    api.get_user_followers(&1, Some(7), None).await.unwrap();
+    assert_eq!(last_count(&transport), 7);
+    let result = api.get_user_following(&1, Some(201), None).await;
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result);
+    // Turned off again, the client defaults are back.
+    api.max_count_by_default = false;
+    api.get_user_followers(&1, None, None).await.unwrap();
+    assert_eq!(last_count(&transport), DefaultCounts::default().followers);
+    assert_eq!(transport.call_count(), 2);
+}
+
+fn rejection(result: Result<serde_json::Value, RocketAPIError>) -> String {
+    match result {
+        Err(RocketAPIError::InvalidParameter(message)) => message,
+        other => panic!("{:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn out_of_range_counts_are_rejected_before_any_request() {
+    let transport = MockTransport::always(ok(json!({ "items": [], "users": [] })));
+    let mut api = instagram(&transport);
+    assert_eq!(rejection(api.get_user_followers(&1, Some(200), None).await), "count must be between 1 and 100, got 200");
+    assert_eq!(rejection(api.get_user_followers(&1, Some(0), None).await), "count must be between 1 and 100, got 0");
+    assert_eq!(rejection(api.get_user_media(&1, Some(51), None).await), "count must be between 1 and 50, got 51");
+    assert_eq!(rejection(api.get_user_clips(&1, Some(255), None).await), "count must be between 1 and 50, got 255");
+    assert_eq!(rejection(api.get_user_tags(&1, Some(0), None).await), "count must be between 1 and 50, got 0");
+    assert_eq!(rejection(api.get_user_following(&1, Some(1000), None).await), "count must be between 1 and 200, got 1000");
+    assert_eq!(rejection(api.get_media_likes("Cx1", Some(60), None).await), "count must be between 1 and 50, got 60");
+    assert_eq!(rejection(api.get_comment_likes(&1, Some(0), None).await), "count must be between 1 and 50, got 0");
+    assert_eq!(transport.call_count(), 0);
+    // The bounds themselves are accepted.
+    api.get_user_followers(&1, Some(100), None).await.unwrap();
+    api.get_user_following(&1, Some(1), None).await.unwrap();
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn overview_page_sizes_are_checked_too() {
+    let transport = MockTransport::always(ok(json!({ "items": [], "users": [] })));
+    let mut api = instagram(&transport);
+    match api.get_profile_overview(&1, 0).await {
+        Err(RocketAPIError::InvalidParameter(message)) => assert_eq!(message, "page_size must be between 1 and 200, got 0"),
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+    assert_eq!(transport.call_count(), 0);
+}
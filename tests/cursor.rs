@@ -0,0 +1,79 @@
+mod common;
+
+use common::{ok, threads, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::pagination::Cursor;
+use serde_json::json;
+
+#[tokio::test]
+async fn thread_likes_cursors_round_trip() {
+    let transport = MockTransport::always(ok(json!({ "users": [{ "pk": 1, "username": "a" }], "next_max_id": "QVFE" })));
+    let mut api = threads(&transport);
+    let first = api.get_thread_likes_typed(&7, None).await.unwrap();
+    let cursor = first.cursor.unwrap();
+    assert_eq!(cursor.value(), "QVFE");
+    api.get_thread_likes_typed(&7, Some(&cursor)).await.unwrap();
+    assert_eq!(transport.payloads()[1], json!({ "id": 7, "max_id": "QVFE" }));
+}
+
+#[tokio::test]
+async fn foreign_cursors_are_rejected_before_any_request() {
+    let transport = MockTransport::always(ok(json!({ "users": [] })));
+    let mut api = threads(&transport);
+    let followers = Cursor::new("instagram/user/get_followers", "QVFE".to_string());
+    let result = api.get_thread_likes_typed(&7, Some(&followers)).await;
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result);
+    assert_eq!(transport.call_count(), 0);
+}
+
+#[tokio::test]
+async fn raw_cursors_are_accepted_by_any_endpoint() {
+    let transport = MockTransport::always(ok(json!({ "users": [] })));
+    let mut api = threads(&transport);
+    api.get_thread_likes_typed(&7, Some(&Cursor::raw("stored"))).await.unwrap();
+    assert_eq!(transport.payloads()[0]["max_id"], "stored");
+}
+
+#[tokio::test]
+async fn thread_likes_typed_without_likes() {
+    for body in [json!({ "users": [], "user_count": 0, "next_max_id": null }), json!({ "status": "ok" })] {
+        let transport = MockTransport::always(ok(body));
+        let page = threads(&transport).get_thread_likes_typed(&7, None).await.unwrap();
+        assert!(page.users.is_empty());
+        assert_eq!((page.like_count, page.cursor), (0, None));
+    }
+}
+
+#[tokio::test]
+async fn thread_likes_typed_fixture() {
+    let transport = MockTransport::always(ok(json!({
+        "users": [
+            {
+                "pk": "63055343223",
+                "pk_id": "63055343223",
+                "username": "natgeo",
+                "full_name": "National Geographic",
+                "is_private": false,
+                "is_verified": true,
+                "profile_pic_url": "https://cdn.test/natgeo.jpg",
+                "text_post_app_is_private": false,
+            },
+            { "pk": 314216, "username": "zuck" },
+        ],
+        "user_count": 1284,
+        "next_max_id": "QVFEb2",
+        "status": "ok",
+    })));
+    let page = threads(&transport).get_thread_likes_typed(&7, None).await.unwrap();
+    assert_eq!(page.users.len(), 2);
+    let natgeo = &page.users[0];
+    assert_eq!((natgeo.pk, natgeo.username.as_str()), (63055343223, "natgeo"));
+    assert_eq!(natgeo.full_name.as_deref(), Some("National Geographic"));
+    assert_eq!((natgeo.is_private, natgeo.is_verified), (Some(false), Some(true)));
+    assert_eq!(natgeo.profile_pic_url.as_deref(), Some("https://cdn.test/natgeo.jpg"));
+    assert_eq!((page.users[1].pk, page.users[1].full_name.clone()), (314216, None));
+    // `user_count` is read as the like count.
+    assert_eq!(page.like_count, 1284);
+    let cursor = page.cursor.unwrap();
+    assert_eq!((cursor.endpoint(), cursor.value()), (Some("threads/thread/get_likes"), "QVFEb2"));
+}
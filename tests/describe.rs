@@ -0,0 +1,93 @@
+mod common;
+
+use common::{envelope, instagram, ok, threads, MockTransport};
+use rocketapi::helpers::describe_value;
+use serde_json::json;
+
+#[test]
+fn envelopes_are_described_with_status_body_cursors_and_size() {
+    let response = ok(json!({
+        "items": [{ "pk": 1 }, { "pk": 2 }, { "pk": 3 }],
+        "more_available": true,
+        "next_max_id": "QVFD",
+        "num_results": 3,
+        "user": { "pk": 1, "username": "natgeo" },
+    }));
+    assert_eq!(
+        describe_value(&response),
+        format!(
+            "status: \"done\"\n\
+             http status: 200\n\
+             content type: \"application/json\"\n\
+             body: object{{5 keys}}\n  \
+             items: array[3]\n  \
+             more_available: true\n  \
+             next_max_id: \"QVFD\"\n  \
+             num_results: 3\n  \
+             user: object{{2 keys}}\n\
+             cursors: next_max_id=\"QVFD\"\n\
+             size: {} bytes",
+            response.to_string().len()
+        )
+    );
+}
+
+#[test]
+fn long_strings_are_truncated_and_numeric_cursors_detected() {
+    let caption = "a".repeat(75);
+    let body = json!({ "caption": caption, "end_cursor": 42, "max_id": "", "page_token": null });
+    assert_eq!(
+        describe_value(&body),
+        format!(
+            "body: object{{4 keys}}\n  \
+             caption: \"{}\"... (75 chars)\n  \
+             end_cursor: 42\n  \
+             max_id: \"\"\n  \
+             page_token: null\n\
+             cursors: end_cursor=\"42\"\n\
+             size: {} bytes",
+            "a".repeat(60),
+            body.to_string().len()
+        )
+    );
+}
+
+#[test]
+fn bodies_that_are_not_objects() {
+    assert_eq!(describe_value(&json!([1, 2])), "body: array[2]\nsize: 5 bytes");
+    assert_eq!(describe_value(&json!(null)), "body: null\nsize: 4 bytes");
+    assert_eq!(
+        describe_value(&envelope(502, json!("Bad gateway"))),
+        "status: \"done\"\nhttp status: 502\ncontent type: \"application/json\"\nbody: \"Bad gateway\"\nsize: 103 bytes"
+    );
+}
+
+#[tokio::test]
+async fn describe_last_response_summarizes_the_latest_envelope() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "users": [{ "pk": 1 }], "next_max_id": "p2" }))),
+        Ok(envelope(404, json!({ "message": "Media not found" }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert_eq!(api.describe_last_response(), "no response yet");
+    api.get_user_followers(&1, None, None).await.unwrap();
+    let description = api.describe_last_response();
+    assert!(description.contains("http status: 200\n"), "{}", description);
+    assert!(description.contains("\n  users: array[1]\n"), "{}", description);
+    assert!(description.contains("\ncursors: next_max_id=\"p2\"\n"), "{}", description);
+    // Error envelopes are kept as the last response too.
+    api.get_media_info(&7).await.unwrap_err();
+    let description = api.describe_last_response();
+    assert!(description.contains("http status: 404\n"), "{}", description);
+    assert!(description.contains("\n  message: \"Media not found\"\ncursors: none\n"), "{}", description);
+}
+
+#[tokio::test]
+async fn threads_describe_last_response() {
+    let transport = MockTransport::always(ok(json!({ "threads": [], "paging_tokens": { "downwards": "t2" } })));
+    let mut api = threads(&transport);
+    assert_eq!(api.describe_last_response(), "no response yet");
+    api.get_user_feed(&1, None).await.unwrap();
+    assert_eq!(api.describe_last_response(), describe_value(&api.last_response));
+    assert!(api.describe_last_response().contains("\n  threads: array[0]\n"));
+}
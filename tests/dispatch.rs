@@ -0,0 +1,112 @@
+mod common;
+
+use common::{envelope, instagram, ok, HttpResponse, MockServer, MockTransport, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use serde_json::{json, Value};
+
+// Envelope fixtures run through both modes: (name, envelope, lenient outcome is Ok, strict error pointer).
+fn fixtures() -> Vec<(&'static str, Value, bool, Option<&'static str>)> {
+    vec![
+        ("valid", ok(json!({ "items": [], "next_max_id": "abc" })), true, None),
+        ("numeric cursor", ok(json!({ "next_max_id": 123 })), true, None),
+        ("object cursor", ok(json!({ "next_max_id": { "id": 1 } })), true, Some("/response/body/next_max_id")),
+        (
+            "missing content type",
+            json!({ "status": "done", "response": { "status_code": 200, "body": {} } }),
+            false,
+            Some("/response/content_type"),
+        ),
+        (
+            "string status code",
+            json!({ "status": "done", "response": { "status_code": "200", "content_type": "application/json", "body": {} } }),
+            false,
+            Some("/response/status_code"),
+        ),
+        ("unknown status", json!({ "status": "pending" }), false, Some("/status")),
+        ("missing status", json!({ "response": {} }), false, Some("/status")),
+    ]
+}
+
+async fn dispatch(strict: bool, fixture: Value) -> Result<Value, RocketAPIError> {
+    let transport = MockTransport::always(fixture);
+    let mut api = instagram(&transport);
+    api.api.set_strict(strict);
+    api.call("instagram/user/get_media", json!({ "id": 1 })).await
+}
+
+#[tokio::test]
+async fn lenient_mode_defaults_envelope_drift() {
+    for (name, fixture, lenient_ok, _) in fixtures() {
+        let result = dispatch(false, fixture).await;
+        assert_eq!(result.is_ok(), lenient_ok, "{}: {:?}", name, result);
+        if let Err(err) = result {
+            assert!(matches!(err, RocketAPIError::BadResponse { .. }), "{}: {:?}", name, err);
+        }
+    }
+}
+
+#[tokio::test]
+async fn strict_mode_points_at_the_offending_field() {
+    for (name, fixture, _, pointer) in fixtures() {
+        match (dispatch(true, fixture).await, pointer) {
+            (Ok(_), None) => {}
+            (Err(RocketAPIError::InvalidResponse { pointer: actual, .. }), Some(expected)) => assert_eq!(actual, expected, "{}", name),
+            (result, _) => panic!("{}: unexpected {:?}", name, result),
+        }
+    }
+}
+
+#[tokio::test]
+async fn strict_mode_keeps_status_errors() {
+    let transport = MockTransport::always(envelope(404, json!({})));
+    let mut api = instagram(&transport);
+    api.api.set_strict(true);
+    let result = api.get_user_info("natgeo").await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+}
+
+fn large_page() -> Value {
+    let users: Vec<Value> = (0..2000)
+        .map(|pk| json!({ "pk": pk, "username": format!("user_{}", pk), "full_name": "Name \u{e9}\\n", "is_private": pk % 2 == 0 }))
+        .collect();
+    ok(json!({ "users": users, "next_max_id": "QVFE" }))
+}
+
+fn client(server: &MockServer, streaming: bool) -> InstagramAPI {
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api.set_base_url(&server.url()).unwrap();
+    api.api.set_streaming_parse(streaming);
+    api
+}
+
+#[tokio::test]
+async fn buffered_and_streaming_parse_give_the_same_body() {
+    let fixture = large_page();
+    let body = fixture.clone();
+    let server = MockServer::start(move |_| HttpResponse::json(200, &body));
+    let buffered = client(&server, false).get_user_followers(&1, Some(100), None).await.unwrap();
+    let streamed = client(&server, true).get_user_followers(&1, Some(100), None).await.unwrap();
+    assert_eq!(buffered, fixture["response"]["body"]);
+    assert_eq!(streamed, buffered);
+}
+
+#[tokio::test]
+async fn non_json_bodies_are_reported_in_both_modes() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/instagram/user/get_info" => HttpResponse::text(502, "<html>502 Bad Gateway</html>"),
+        _ => HttpResponse::text(503, ""),
+    });
+    for streaming in [false, true] {
+        let mut api = client(&server, streaming);
+        match api.get_user_info("natgeo").await {
+            Err(RocketAPIError::NonJsonResponse { http_status: 502, body }) => assert_eq!(body, "<html>502 Bad Gateway</html>"),
+            other => panic!("streaming {}: {:?}", streaming, other),
+        }
+        match api.get_user_info_by_id(&1).await {
+            Err(RocketAPIError::NonJsonResponse { http_status: 503, body }) => assert!(body.is_empty()),
+            other => panic!("streaming {}: {:?}", streaming, other),
+        }
+        assert!(api.last_response.is_null());
+    }
+}
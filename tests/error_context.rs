@@ -0,0 +1,59 @@
+mod common;
+
+use common::{envelope, instagram, HttpResponse, MockServer, MockTransport};
+use rocketapi::errors::{RequestContext, RocketAPIError};
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::retry::RetryPolicy;
+use serde_json::json;
+
+fn context(method: &str, payload: serde_json::Value) -> RequestContext {
+    RequestContext { method: method.to_string(), payload }
+}
+
+#[tokio::test]
+async fn envelope_errors_carry_the_request_they_come_from() {
+    let transport = MockTransport::new(|method, _| {
+        Ok(match method {
+            "instagram/media/get_info" => envelope(404, json!({ "message": "Media not found" })),
+            _ => envelope(500, json!({ "message": "Please wait a few minutes" })),
+        })
+    });
+    let mut api = instagram(&transport);
+    api.api.set_error_context(true);
+
+    let err = api.get_media_info(&7).await.unwrap_err();
+    assert!(matches!(err.kind(), RocketAPIError::NotFound(_)), "{:?}", err);
+    assert_eq!(err.context(), Some(&context("instagram/media/get_info", json!({ "id": 7 }))));
+    assert!(err.to_string().ends_with("(method instagram/media/get_info, payload {\"id\":7})"), "{}", err);
+
+    let err = api.get_location_info(&3).await.unwrap_err();
+    assert!(matches!(err.kind(), RocketAPIError::BadResponse { status_code: 500, .. }), "{:?}", err);
+    assert_eq!(err.context(), Some(&context("instagram/location/get_info", json!({ "id": 3 }))));
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[tokio::test]
+async fn request_errors_carry_the_request_they_come_from() {
+    let server = MockServer::start(|_| HttpResponse::reset());
+    let mut api = InstagramAPI::builder().token("test-token").base_url(server.url()).build().unwrap();
+    api.api.set_retry_policy(RetryPolicy::none()).unwrap();
+    api.api.set_error_context(true);
+    let err = api.get_user_info("natgeo").await.unwrap_err();
+    assert!(matches!(err.kind(), RocketAPIError::RequestError(_)), "{:?}", err);
+    assert_eq!(err.context(), Some(&context("instagram/user/get_info", json!({ "username": "natgeo" }))));
+}
+
+#[tokio::test]
+async fn errors_have_no_context_by_default_or_before_a_request() {
+    let transport = MockTransport::always(envelope(404, json!({})));
+    let mut api = instagram(&transport);
+    let err = api.get_media_info(&7).await.unwrap_err();
+    assert!(matches!(err, RocketAPIError::NotFound(_)), "{:?}", err);
+    assert!(err.context().is_none());
+
+    api.api.set_error_context(true);
+    let err = api.get_user_media(&1, Some(51), None).await.unwrap_err();
+    assert!(matches!(err, RocketAPIError::InvalidParameter(_)), "{:?}", err);
+    assert!(err.context().is_none());
+    assert_eq!(transport.call_count(), 1);
+}
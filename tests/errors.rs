@@ -0,0 +1,86 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{envelope, instagram, HttpResponse, MockServer, MockTransport, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::RocketAPI;
+use serde_json::json;
+
+fn retry_after(result: Result<serde_json::Value, RocketAPIError>) -> Option<Duration> {
+    match result {
+        Err(RocketAPIError::RateLimited { retry_after, .. }) => retry_after,
+        other => panic!("{:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn bad_responses_carry_the_status_code_and_upstream_message() {
+    let transport = MockTransport::scripted(vec![
+        Ok(envelope(400, json!({ "message": "Invalid target user.", "status": "fail" }))),
+        Ok(envelope(500, json!({ "error_message": "Please wait a few minutes before you try again." }))),
+        Ok(json!({ "status": "error", "detail": "Unknown method" })),
+        Ok(envelope(403, json!({ "message": "  " }))),
+    ]);
+    let mut api = instagram(&transport);
+    let expected = [
+        (400, Some("Invalid target user.")),
+        (500, Some("Please wait a few minutes before you try again.")),
+        (0, Some("Unknown method")),
+        (403, None),
+    ];
+    for (expected_status, expected_message) in expected {
+        match api.get_user_info("natgeo").await {
+            Err(RocketAPIError::BadResponse { status_code, message, body }) => {
+                assert_eq!((status_code, message.as_deref()), (expected_status, expected_message));
+                assert_eq!(body, api.last_response);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn bad_responses_display_the_status_and_message() {
+    let transport = MockTransport::scripted(vec![
+        Ok(envelope(400, json!({ "message": "Invalid target user." }))),
+        Ok(envelope(400, json!({}))),
+    ]);
+    let mut api = instagram(&transport);
+    let err = api.get_user_info("natgeo").await.unwrap_err();
+    assert_eq!(err.to_string(), "BadResponse: status 400: Invalid target user.");
+    let err = api.get_user_info("natgeo").await.unwrap_err();
+    assert_eq!(err.to_string(), "BadResponse: status 400");
+}
+
+#[tokio::test]
+async fn gateway_rate_limits_read_retry_after_from_the_header_then_the_body() {
+    let server = MockServer::start(|request| match request.json()["case"].as_str().unwrap() {
+        "header" => HttpResponse::json(429, &json!({ "detail": "Too many requests" })).header("Retry-After", "7"),
+        "both" => HttpResponse::json(429, &json!({ "retry_after": 30 })).header("Retry-After", "7"),
+        "body" => HttpResponse::json(429, &json!({ "detail": "Too many requests", "retry_after": 2.5 })),
+        _ => HttpResponse::json(429, &json!({ "detail": "Too many requests" })).header("Retry-After", "Wed, 21 Oct 2026 07:28:00 GMT"),
+    });
+    let api = RocketAPI::with_base_url("test-token".to_string(), TIMEOUT, &server.url()).unwrap();
+    let request = |case: &str| api.request("instagram/user/get_info", json!({ "case": case }));
+    assert_eq!(retry_after(request("header").await), Some(Duration::from_secs(7)));
+    assert_eq!(retry_after(request("both").await), Some(Duration::from_secs(7)));
+    assert_eq!(retry_after(request("body").await), Some(Duration::from_millis(2500)));
+    // HTTP dates aren't supported.
+    assert_eq!(retry_after(request("date").await), None);
+}
+
+#[tokio::test]
+async fn upstream_rate_limits_read_retry_after_from_the_envelope() {
+    let mut header = envelope(429, json!({ "message": "Please wait a few minutes", "retry_after": 60 }));
+    header["response"]["headers"] = json!({ "retry-after": "12" });
+    let transport = MockTransport::scripted(vec![
+        Ok(header),
+        Ok(envelope(429, json!({ "message": "Please wait a few minutes", "retry_after": "45" }))),
+        Ok(envelope(429, json!({ "message": "Please wait a few minutes" }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert_eq!(retry_after(api.get_user_info("natgeo").await), Some(Duration::from_secs(12)));
+    assert_eq!(retry_after(api.get_user_info("natgeo").await), Some(Duration::from_secs(45)));
+    assert_eq!(retry_after(api.get_user_info("natgeo").await), None);
+}
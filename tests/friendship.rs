@@ -0,0 +1,44 @@
+mod common;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::models::FriendshipStatus;
+use serde_json::{json, Value};
+
+fn accounts(method: &str, payload: &Value, private_target: bool) -> Value {
+    let username = |id: u64| if id == 1 { "alice" } else { "bob" };
+    match (method, payload["id"].as_u64().unwrap()) {
+        ("instagram/user/get_info_by_id", id) => ok(json!({ "user": { "pk": id, "username": username(id) } })),
+        ("instagram/user/get_following", 2) if private_target => envelope(403, json!({ "message": "Not authorized to view user" })),
+        // alice follows bob, bob doesn't follow alice back.
+        ("instagram/user/get_following", 1) => ok(json!({ "users": [{ "pk": 2, "username": "bob" }] })),
+        ("instagram/user/get_following", _) => ok(json!({ "users": [] })),
+        (method, _) => panic!("unexpected call to {}", method),
+    }
+}
+
+#[tokio::test]
+async fn friendship_status_payloads() {
+    let transport = MockTransport::new(|method, payload| Ok(accounts(method, payload, false)));
+    let mut api = instagram(&transport);
+    let status = api.get_friendship_status(&1, &2).await.unwrap();
+    assert_eq!(status, FriendshipStatus { following: true, followed_by: false });
+    assert_eq!(
+        transport.calls(),
+        vec![
+            ("instagram/user/get_info_by_id".to_string(), json!({ "id": 1 })),
+            ("instagram/user/get_info_by_id".to_string(), json!({ "id": 2 })),
+            ("instagram/user/get_following".to_string(), json!({ "id": 1, "query": "bob" })),
+            ("instagram/user/get_following".to_string(), json!({ "id": 2, "query": "alice" })),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn private_following_lists_are_errors() {
+    let transport = MockTransport::new(|method, payload| Ok(accounts(method, payload, true)));
+    let mut api = instagram(&transport);
+    let result = api.get_friendship_status(&1, &2).await;
+    assert!(matches!(result, Err(RocketAPIError::BadResponse { status_code: 403, .. })), "{:?}", result);
+    assert_eq!(transport.call_count(), 4);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{ok, HttpResponse, MockServer, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::RocketAPI;
+use serde_json::json;
+
+fn server() -> MockServer {
+    MockServer::start(|_| HttpResponse::json(200, &ok(json!({}))))
+}
+
+fn last_header(server: &MockServer, name: &str) -> String {
+    server.requests().last().unwrap().header(name).unwrap().to_string()
+}
+
+#[tokio::test]
+async fn the_user_agent_names_the_crate_and_the_application() {
+    let server = server();
+    let default_agent = format!("rocketapi-rust/{}", env!("CARGO_PKG_VERSION"));
+    let mut api = RocketAPI::with_base_url("test-token".to_string(), TIMEOUT, &server.url()).unwrap();
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap();
+    assert_eq!(last_header(&server, "user-agent"), default_agent);
+
+    api.set_user_agent_suffix(Some(" my-scraper/2.1 ")).unwrap();
+    assert_eq!(api.user_agent(), format!("{} my-scraper/2.1", default_agent));
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap();
+    assert_eq!(last_header(&server, "user-agent"), format!("{} my-scraper/2.1", default_agent));
+
+    api.set_user_agent_suffix(None).unwrap();
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap();
+    assert_eq!(last_header(&server, "user-agent"), default_agent);
+}
+
+#[tokio::test]
+async fn the_builder_suffix_is_sent_and_invalid_suffixes_are_rejected() {
+    let server = server();
+    let mut api = InstagramAPI::builder().token("test-token").base_url(server.url()).user_agent_suffix("crawler/1.0").build().unwrap();
+    api.get_media_info(&1).await.unwrap();
+    assert!(last_header(&server, "user-agent").ends_with(" crawler/1.0"));
+
+    let mut api = RocketAPI::new("test-token".to_string(), TIMEOUT);
+    let result = api.set_user_agent_suffix(Some("bad\nsuffix"));
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result);
+    assert!(!api.user_agent().contains("bad"));
+}
+
+#[tokio::test]
+async fn the_authorization_header_uses_the_configured_scheme() {
+    let server = server();
+    let mut api = RocketAPI::with_base_url("test-token".to_string(), TIMEOUT, &server.url()).unwrap();
+    assert_eq!(api.auth_scheme(), "Token");
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap();
+    assert_eq!(last_header(&server, "authorization"), "Token test-token");
+
+    api.set_auth_scheme("Bearer").unwrap();
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap();
+    assert_eq!(last_header(&server, "authorization"), "Bearer test-token");
+
+    let mut api = InstagramAPI::builder().token("builder-token").base_url(server.url()).auth_scheme("Api-Key").build().unwrap();
+    api.get_media_info(&1).await.unwrap();
+    assert_eq!(last_header(&server, "authorization"), "Api-Key builder-token");
+}
+
+#[test]
+fn invalid_auth_schemes_are_rejected() {
+    let mut api = RocketAPI::new("test-token".to_string(), TIMEOUT);
+    for scheme in ["", "Two words", "Bad\r\n"] {
+        let result = api.set_auth_scheme(scheme);
+        assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}: {:?}", scheme, result);
+    }
+    assert_eq!(api.auth_scheme(), "Token");
+}
@@ -0,0 +1,106 @@
+use rocketapi::errors::RocketAPIError;
+use rocketapi::helpers::{media_tagged_users, normalize_username, response_warnings};
+use rocketapi::models::MediaInfo;
+use serde_json::{json, Value};
+
+fn tag(pk: u64, username: &str) -> Value {
+    json!({ "user": { "pk": pk.to_string(), "username": username, "is_verified": true }, "position": [0.5, 0.5] })
+}
+
+fn usernames(media: &Value) -> Vec<String> {
+    media_tagged_users(media).into_iter().map(|user| user.username).collect()
+}
+
+#[test]
+fn response_warnings_reads_advisory_fields_only() {
+    let envelope = json!({
+        "status": "done",
+        "warning": "partial data",
+        "deprecation": ["use get_info_by_id"],
+        "response": { "status_code": 200, "body": {} },
+    });
+    assert_eq!(response_warnings(&envelope), vec!["partial data", "use get_info_by_id"]);
+    let error = json!({ "status": "error", "message": "Invalid token" });
+    assert!(response_warnings(&error).is_empty());
+}
+
+#[test]
+fn media_tagged_users_reads_usertags() {
+    let media = json!({ "id": "1_2", "pk": 1, "usertags": { "in": [tag(10, "brand"), tag(11, "friend")] } });
+    assert_eq!(usernames(&media), vec!["brand", "friend"]);
+    let users = media_tagged_users(&media);
+    assert_eq!((users[0].pk, users[0].is_verified), (10, Some(true)));
+
+    let typed: MediaInfo = serde_json::from_value(media).unwrap();
+    let tagged: Vec<&str> = typed.tagged_users().iter().map(|user| user.username.as_str()).collect();
+    assert_eq!(tagged, vec!["brand", "friend"]);
+}
+
+#[test]
+fn media_without_tags_has_no_tagged_users() {
+    for media in [
+        json!({ "id": "1_2", "pk": 1 }),
+        json!({ "id": "1_2", "pk": 1, "usertags": null }),
+        json!({ "id": "1_2", "pk": 1, "usertags": { "in": [] } }),
+        json!({ "id": "1_2", "pk": 1, "usertags": { "in": [{ "user": { "username": "no_pk" } }, "garbage"] } }),
+    ] {
+        assert!(media_tagged_users(&media).is_empty(), "{}", media);
+    }
+    let typed: MediaInfo = serde_json::from_value(json!({ "id": "1_2", "pk": 1 })).unwrap();
+    assert!(typed.tagged_users().is_empty());
+}
+
+#[test]
+fn carousel_tags_of_every_child_are_merged_once() {
+    let carousel = json!({
+        "id": "1_2",
+        "pk": 1,
+        "media_type": 8,
+        "usertags": { "in": [tag(10, "brand")] },
+        "carousel_media": [
+            { "id": "2_2", "usertags": { "in": [tag(11, "friend"), tag(10, "brand")] } },
+            { "id": "3_2" },
+            { "id": "4_2", "usertags": { "in": [tag(12, "photographer"), tag(11, "friend")] } },
+        ],
+    });
+    assert_eq!(usernames(&carousel), vec!["brand", "friend", "photographer"]);
+    // Tags only on the children, none on the post itself.
+    let children_only = json!({ "carousel_media": [{ "usertags": { "in": [tag(11, "friend")] } }, { "usertags": { "in": [tag(12, "photographer")] } }] });
+    assert_eq!(usernames(&children_only), vec!["friend", "photographer"]);
+}
+
+#[test]
+fn normalize_username_accepts_handles_and_profile_urls() {
+    for input in [
+        "natgeo",
+        "  NatGeo ",
+        "@natgeo",
+        "@@NatGeo",
+        "https://www.instagram.com/natgeo/",
+        "https://instagram.com/NatGeo",
+        "instagram.com/natgeo/",
+        "https://www.instagram.com/natgeo/?hl=en&igsh=MWQ1ZGUxMzBkMA==",
+        "https://www.instagram.com/natgeo?utm_source=ig_web_copy_link#top",
+    ] {
+        assert_eq!(normalize_username(input).unwrap(), "natgeo", "{:?}", input);
+    }
+    assert_eq!(normalize_username("nat.geo_travel").unwrap(), "nat.geo_travel");
+}
+
+#[test]
+fn normalize_username_rejects_invalid_input() {
+    for input in [
+        "",
+        "   ",
+        "@",
+        "nat geo",
+        "natgeo!",
+        "ñatgeo",
+        "a_username_longer_than_thirty_c",
+        "https://www.instagram.com/p/Cx1a2b3/",
+        "https://example.com/natgeo/",
+    ] {
+        let result = normalize_username(input);
+        assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}: {:?}", input, result);
+    }
+}
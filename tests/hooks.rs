@@ -0,0 +1,84 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::{envelope, ok, rocket, HttpResponse, MockServer, MockTransport, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::retry::RetryPolicy;
+use rocketapi::RocketAPI;
+use serde_json::{json, Value};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+// Registers the three hooks, each one appending a line to the returned log.
+fn hooked(api: &mut RocketAPI) -> Log {
+    let log: Log = Arc::default();
+    let (requests, responses, errors) = (log.clone(), log.clone(), log.clone());
+    api.on_request(move |method, payload| requests.lock().unwrap().push(format!("request {} {}", method, payload)));
+    api.on_response(move |method, body: &Value, _| {
+        responses.lock().unwrap().push(format!("response {} {}", method, body["response"]["status_code"]));
+    });
+    api.on_error(move |method, err, _| errors.lock().unwrap().push(format!("error {} {}", method, err)));
+    log
+}
+
+#[tokio::test]
+async fn hooks_see_every_request_and_response_whatever_the_status() {
+    let transport = MockTransport::scripted(vec![Ok(ok(json!({}))), Ok(envelope(404, json!({})))]);
+    let mut api = rocket(&transport);
+    let log = hooked(&mut api);
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap();
+    api.request("instagram/media/get_info", json!({ "id": 2 })).await.unwrap();
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            r#"request instagram/media/get_info {"id":1}"#,
+            "response instagram/media/get_info 200",
+            r#"request instagram/media/get_info {"id":2}"#,
+            "response instagram/media/get_info 404",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn on_error_is_called_when_no_response_is_received() {
+    let transport = MockTransport::scripted(vec![Err(RocketAPIError::InvalidParameter("transport down".to_string()))]);
+    let mut api = rocket(&transport);
+    let log = hooked(&mut api);
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap_err();
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![r#"request instagram/media/get_info {"id":1}"#, "error instagram/media/get_info InvalidParameter: transport down"]
+    );
+}
+
+#[tokio::test]
+async fn hooks_run_for_every_attempt_of_a_retried_call() {
+    let served = AtomicUsize::new(0);
+    let server = MockServer::start(move |_| match served.fetch_add(1, Ordering::SeqCst) {
+        0 => HttpResponse::reset(),
+        _ => HttpResponse::json(200, &ok(json!({}))),
+    });
+    let mut api = RocketAPI::with_base_url("test-token".to_string(), TIMEOUT, &server.url()).unwrap();
+    api.set_retry_policy(RetryPolicy::new(2, Duration::from_millis(1), Duration::from_millis(1), Duration::from_secs(1)).unwrap()).unwrap();
+    let log = hooked(&mut api);
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap();
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 4, "{:?}", log);
+    assert_eq!(log[0], r#"request instagram/media/get_info {"id":1}"#);
+    assert!(log[1].starts_with("error instagram/media/get_info RequestError"), "{:?}", log);
+    assert_eq!(log[2], log[0]);
+    assert_eq!(log[3], "response instagram/media/get_info 200");
+}
+
+#[tokio::test]
+async fn clear_hooks_removes_them() {
+    let transport = MockTransport::always(ok(json!({})));
+    let mut api = rocket(&transport);
+    let log = hooked(&mut api);
+    api.clear_hooks();
+    api.request("instagram/media/get_info", json!({ "id": 1 })).await.unwrap();
+    assert!(log.lock().unwrap().is_empty());
+}
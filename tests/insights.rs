@@ -0,0 +1,44 @@
+mod common;
+
+use common::{instagram, ok, HttpResponse, MockServer, MockTransport, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use serde_json::json;
+
+#[tokio::test]
+async fn insights_request_shape() {
+    let transport = MockTransport::always(ok(json!({ "reach": 1200, "impressions": 1800, "saved": 31 })));
+    let mut api = instagram(&transport);
+    let insights = api.get_media_insights(&3141592653589793238).await.unwrap();
+    assert_eq!(insights["reach"], 1200);
+    api.media().insights(&7).await.unwrap();
+    assert_eq!(
+        transport.calls(),
+        vec![
+            ("instagram/media/get_insights".to_string(), json!({ "id": 3141592653589793238u64 })),
+            ("instagram/media/get_insights".to_string(), json!({ "id": 7 })),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn plans_without_insights_are_unauthorized() {
+    let server = MockServer::start(|request| match request.json()["id"].as_u64() {
+        Some(1) => HttpResponse::json(403, &json!({ "detail": "This method is not available on your plan." })),
+        Some(2) => HttpResponse::text(403, "<html>403 Forbidden</html>"),
+        _ => HttpResponse::json(500, &json!({ "detail": "Internal error" })),
+    });
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api.set_base_url(&server.url()).unwrap();
+    match api.get_media_insights(&1).await {
+        Err(RocketAPIError::Unauthorized(body)) => assert_eq!(body["detail"], "This method is not available on your plan."),
+        other => panic!("{:?}", other),
+    }
+    match api.get_media_insights(&2).await {
+        Err(RocketAPIError::Unauthorized(body)) => assert_eq!(body, "<html>403 Forbidden</html>"),
+        other => panic!("{:?}", other),
+    }
+    // Other failures are returned as is.
+    let result = api.get_media_insights(&3).await;
+    assert!(matches!(result, Err(RocketAPIError::BadResponse { .. })), "{:?}", result);
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::{envelope, instagram, ok, MockTransport, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::transport::{Transport, TransportFuture};
+use rocketapi::RocketAPI;
+use serde_json::{json, Value};
+
+// Answers every call with `response(call index)` and keeps the time of each call.
+fn timed_transport(response: impl Fn(usize) -> Value + Send + Sync + 'static) -> (Arc<MockTransport>, Arc<Mutex<Vec<Instant>>>) {
+    let times = Arc::new(Mutex::new(Vec::new()));
+    let sent = times.clone();
+    let transport = MockTransport::new(move |_, _| {
+        let mut sent = sent.lock().unwrap();
+        sent.push(Instant::now());
+        Ok(response(sent.len() - 1))
+    });
+    (transport, times)
+}
+
+#[tokio::test]
+async fn a_429_holds_the_other_requests_of_the_client() {
+    let (transport, times) = timed_transport(|call| match call {
+        0 => envelope(429, json!({ "message": "Please wait a few minutes", "retry_after": 0.3 })),
+        _ => ok(json!({ "user": { "pk": 1 } })),
+    });
+    let mut api = instagram(&transport);
+    api.api.set_retry_after_gate(true);
+    let (mut first, mut second) = (api.clone(), api.clone());
+    let result = api.get_user_info("natgeo").await;
+    assert!(matches!(result, Err(RocketAPIError::RateLimited { .. })), "{:?}", result);
+    let (a, b) = tokio::join!(first.get_user_info_by_id(&1), second.get_media_info(&2));
+    a.unwrap();
+    b.unwrap();
+    let times = times.lock().unwrap();
+    assert_eq!(times.len(), 3);
+    for held in &times[1..] {
+        assert!(held.duration_since(times[0]) >= Duration::from_millis(290), "{:?}", held.duration_since(times[0]));
+    }
+}
+
+#[tokio::test]
+async fn without_the_gate_requests_are_sent_right_away() {
+    let (transport, times) = timed_transport(|call| match call {
+        0 => envelope(429, json!({ "retry_after": 5 })),
+        _ => ok(json!({})),
+    });
+    let mut api = instagram(&transport);
+    api.get_user_info("natgeo").await.unwrap_err();
+    api.get_user_info("natgeo").await.unwrap();
+    let times = times.lock().unwrap();
+    assert!(times[1].duration_since(times[0]) < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn the_rate_limit_spaces_out_requests_after_the_burst() {
+    let (transport, times) = timed_transport(|_| ok(json!({})));
+    let mut api = instagram(&transport);
+    api.api.set_rate_limit(Some(20.0), 2).unwrap();
+    assert_eq!(api.api.rate_limit(), Some((20.0, 2)));
+    let start = Instant::now();
+    for id in 0..6 {
+        api.get_media_info(&id).await.unwrap();
+    }
+    // Two requests at once, then one every 50ms.
+    let offsets: Vec<Duration> = times.lock().unwrap().iter().map(|time| time.duration_since(start)).collect();
+    assert!(offsets[1] < Duration::from_millis(40), "{:?}", offsets);
+    for (index, offset) in offsets.iter().enumerate().skip(2) {
+        let expected = Duration::from_millis(50 * (index as u64 - 1));
+        assert!(*offset >= expected - Duration::from_millis(5), "{:?}", offsets);
+    }
+    assert!(offsets[5] < Duration::from_millis(400), "{:?}", offsets);
+}
+
+#[test]
+fn invalid_rates_are_rejected() {
+    let transport = MockTransport::always(ok(json!({})));
+    let mut api = instagram(&transport);
+    for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let result = api.api.set_rate_limit(Some(rate), 1);
+        assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{}: {:?}", rate, result);
+    }
+    api.api.set_rate_limit(None, 1).unwrap();
+    assert_eq!(api.api.rate_limit(), None);
+}
+
+// Answers after 30ms, counting the calls in flight at the same time.
+#[derive(Default)]
+struct Concurrency {
+    in_flight: Arc<AtomicUsize>,
+    max_seen: Arc<AtomicUsize>,
+}
+
+impl Transport for Concurrency {
+    fn call(&self, _: &str, _: Value) -> TransportFuture {
+        let (in_flight, max_seen) = (self.in_flight.clone(), self.max_seen.clone());
+        Box::pin(async move {
+            max_seen.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(ok(json!({})))
+        })
+    }
+}
+
+async fn max_concurrency(max_in_flight: Option<usize>) -> usize {
+    let transport = Arc::new(Concurrency::default());
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api = RocketAPI::with_transport("test-token".to_string(), TIMEOUT, transport.clone());
+    api.api.set_max_in_flight(max_in_flight).unwrap();
+    assert_eq!(api.api.max_in_flight(), max_in_flight);
+    let calls: Vec<_> = (0..6)
+        .map(|id| {
+            let mut api = api.clone();
+            tokio::spawn(async move { api.get_media_info(&id).await.unwrap() })
+        })
+        .collect();
+    for call in calls {
+        call.await.unwrap();
+    }
+    assert_eq!(api.api.in_flight(), 0);
+    transport.max_seen.load(Ordering::SeqCst)
+}
+
+#[tokio::test]
+async fn max_in_flight_bounds_the_concurrent_requests() {
+    assert_eq!(max_concurrency(Some(2)).await, 2);
+    assert_eq!(max_concurrency(Some(1)).await, 1);
+    assert_eq!(max_concurrency(None).await, 6);
+}
+
+#[test]
+fn max_in_flight_must_be_positive() {
+    let transport = MockTransport::always(ok(json!({})));
+    let mut api = instagram(&transport);
+    let result = api.api.set_max_in_flight(Some(0));
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result);
+    assert_eq!(api.api.max_in_flight(), None);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use serde_json::{json, Value};
+
+fn post(pk: u64, taken_at: Option<u64>) -> Value {
+    match taken_at {
+        Some(taken_at) => json!({ "pk": pk, "taken_at": taken_at }),
+        None => json!({ "pk": pk }),
+    }
+}
+
+fn pages() -> Vec<Value> {
+    vec![
+        ok(json!({ "items": [post(1, Some(300)), post(2, None), post(3, Some(250))], "next_max_id": "a", "more_available": true })),
+        // The API keeps answering with the same cursor: this page is the last one.
+        ok(json!({ "items": [post(4, Some(200)), post(5, None)], "next_max_id": "a", "more_available": true })),
+    ]
+}
+
+fn pks(items: &[Value]) -> Vec<u64> {
+    items.iter().map(|item| item["pk"].as_u64().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn get_media_since_stops_on_a_repeated_cursor_and_skips_undated_items() {
+    let transport = MockTransport::scripted(pages().into_iter().map(Ok).collect());
+    let mut api = instagram(&transport);
+    let media = api.get_media_since(&1, 100, None).await.unwrap();
+    assert_eq!(pks(&media), vec![1, 3, 4]);
+    assert_eq!(transport.call_count(), 2);
+    assert_eq!(transport.payloads()[1]["max_id"], "a");
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn stream_media_since_matches_get_media_since() {
+    use futures::TryStreamExt;
+
+    let transport = MockTransport::scripted(pages().into_iter().map(Ok).collect());
+    let mut api = instagram(&transport);
+    let media: Vec<Value> = api.stream_media_since(&1, 100, None).try_collect().await.unwrap();
+    assert_eq!(pks(&media), vec![1, 3, 4]);
+    assert_eq!(transport.call_count(), 2);
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "stream")]
+
+mod common;
+
+use common::{envelope, instagram, ok, MockTransport};
+use futures::{StreamExt, TryStreamExt};
+use rocketapi::errors::RocketAPIError;
+use serde_json::{json, Value};
+
+fn items(pks: &[u64]) -> Vec<Value> {
+    pks.iter().map(|pk| json!({ "id": format!("{}_1", pk), "pk": pk, "media_type": 1 })).collect()
+}
+
+fn pks(items: &[Value]) -> Vec<u64> {
+    items.iter().map(|item| item["pk"].as_u64().unwrap()).collect()
+}
+
+// Three pages, the last one still carries a cursor but `more_available: false`.
+fn media_pages(_: &str, payload: &Value) -> Result<Value, RocketAPIError> {
+    Ok(match payload["max_id"].as_str() {
+        None => ok(json!({ "items": items(&[1, 2]), "next_max_id": "m2", "more_available": true })),
+        Some("m2") => ok(json!({ "items": items(&[3, 4]), "next_max_id": "m3", "more_available": true })),
+        Some("m3") => ok(json!({ "items": items(&[5]), "next_max_id": "m4", "more_available": false })),
+        Some(cursor) => panic!("unexpected cursor {}", cursor),
+    })
+}
+
+#[tokio::test]
+async fn stream_user_media_follows_next_max_id() {
+    let transport = MockTransport::new(media_pages);
+    let mut api = instagram(&transport);
+    let media: Vec<Value> = api.stream_user_media(&1, 20).try_collect().await.unwrap();
+    assert_eq!(pks(&media), vec![1, 2, 3, 4, 5]);
+    assert_eq!(
+        transport.payloads(),
+        vec![
+            json!({ "id": 1, "count": 20 }),
+            json!({ "id": 1, "count": 20, "max_id": "m2" }),
+            json!({ "id": 1, "count": 20, "max_id": "m3" }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stream_user_media_only_fetches_the_pages_it_needs() {
+    let transport = MockTransport::new(media_pages);
+    let mut api = instagram(&transport);
+    let first: Vec<Value> = api.stream_user_media(&1, 20).take(3).try_collect().await.unwrap();
+    assert_eq!(pks(&first), vec![1, 2, 3]);
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn stream_user_media_ends_with_the_error_of_a_later_page() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "items": items(&[1, 2]), "next_max_id": "m2" }))),
+        Ok(envelope(404, json!({ "message": "User not found" }))),
+    ]);
+    let mut api = instagram(&transport);
+    let results: Vec<Result<Value, RocketAPIError>> = api.stream_user_media(&1, 20).collect().await;
+    assert_eq!(results.len(), 3);
+    assert!(results[..2].iter().all(Result::is_ok));
+    assert!(matches!(results[2], Err(RocketAPIError::NotFound(_))), "{:?}", results[2]);
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn stream_user_media_expands_carousels_on_every_page() {
+    let carousel = json!({ "id": "3_1", "pk": 3, "media_type": 8, "code": "C3", "carousel_media": [{ "pk": 31 }, { "pk": 32 }] });
+    let transport = MockTransport::new(move |_, payload| {
+        Ok(match payload["max_id"].as_str() {
+            None => ok(json!({ "items": items(&[1]), "next_max_id": "m2" })),
+            Some("m2") => ok(json!({ "items": [carousel.clone()], "next_max_id": null })),
+            Some(cursor) => panic!("unexpected cursor {}", cursor),
+        })
+    });
+    let mut api = instagram(&transport);
+    api.expand_carousels = true;
+    let media: Vec<Value> = api.stream_user_media(&1, 20).try_collect().await.unwrap();
+    assert_eq!(pks(&media), vec![1, 31, 32]);
+    assert_eq!(media[2]["code"], "C3");
+}
@@ -0,0 +1,77 @@
+use rocketapi::helpers::{best_media_url, best_media_url_with_fallback, find_any_media_url};
+use serde_json::json;
+
+fn candidate(url: &str, width: u64, height: u64) -> serde_json::Value {
+    json!({ "url": url, "width": width, "height": height })
+}
+
+#[test]
+fn the_largest_candidate_wins_and_videos_come_first() {
+    let image = json!({
+        "media_type": 1,
+        "image_versions2": { "candidates": [
+            candidate("https://cdn.test/s640.jpg", 640, 800),
+            candidate("https://cdn.test/s1080.jpg", 1080, 1350),
+            candidate("https://cdn.test/s150.jpg", 150, 150),
+        ] },
+    });
+    assert_eq!(best_media_url(&image).as_deref(), Some("https://cdn.test/s1080.jpg"));
+
+    let video = json!({
+        "media_type": 2,
+        "image_versions2": { "candidates": [candidate("https://cdn.test/cover.jpg", 4000, 4000)] },
+        "video_versions": [candidate("https://cdn.test/480.mp4", 480, 854), candidate("https://cdn.test/720.mp4", 720, 1280)],
+    });
+    assert_eq!(best_media_url(&video).as_deref(), Some("https://cdn.test/720.mp4"));
+    assert_eq!(best_media_url_with_fallback(&video), best_media_url(&video));
+}
+
+#[test]
+fn candidates_without_url_or_size_are_handled() {
+    let media = json!({
+        "image_versions2": { "candidates": [
+            { "width": 2000, "height": 2000 },
+            { "url": "https://cdn.test/unsized.jpg" },
+        ] },
+    });
+    assert_eq!(best_media_url(&media).as_deref(), Some("https://cdn.test/unsized.jpg"));
+    // Empty video versions fall through to the images.
+    let media = json!({ "video_versions": [], "image_versions2": { "candidates": [candidate("https://cdn.test/a.jpg", 1, 1)] } });
+    assert_eq!(best_media_url(&media).as_deref(), Some("https://cdn.test/a.jpg"));
+}
+
+#[test]
+fn renamed_candidate_fields_fall_back_to_any_media_url() {
+    let media = json!({
+        "pk": 1,
+        "caption": { "text": "see https://example.com/about" },
+        "image_versions3": { "items": [{ "src": "https://cdn.test/renamed.webp?stp=dst-jpg&_nc_ht=cdn#frag" }] },
+    });
+    assert_eq!(best_media_url(&media), None);
+    assert_eq!(best_media_url_with_fallback(&media).as_deref(), Some("https://cdn.test/renamed.webp?stp=dst-jpg&_nc_ht=cdn#frag"));
+}
+
+#[test]
+fn find_any_media_url_matches_extensions_on_the_path_only() {
+    assert_eq!(find_any_media_url(&json!("https://cdn.test/CLIP.MP4")).as_deref(), Some("https://cdn.test/CLIP.MP4"));
+    assert_eq!(find_any_media_url(&json!("http://cdn.test/a.heic#x")).as_deref(), Some("http://cdn.test/a.heic#x"));
+    for value in [
+        json!("https://cdn.test/page?file=a.jpg"),
+        json!("https://cdn.test/page#a.png"),
+        json!("cdn.test/a.jpg"),
+        json!("ftp://cdn.test/a.jpg"),
+        json!("https://cdn.test/a.gif"),
+        json!(["not a url", 3, null, { "nested": "https://cdn.test/index.html" }]),
+        json!(null),
+    ] {
+        assert_eq!(find_any_media_url(&value), None, "{}", value);
+    }
+    let nested = json!({ "a": [{ "b": "https://cdn.test/x.txt" }, { "c": { "d": "https://cdn.test/found.jpeg" } }] });
+    assert_eq!(find_any_media_url(&nested).as_deref(), Some("https://cdn.test/found.jpeg"));
+}
+
+#[test]
+fn media_without_any_url_has_none() {
+    let media = json!({ "pk": 1, "image_versions2": { "candidates": [] }, "code": "Cx1" });
+    assert_eq!(best_media_url_with_fallback(&media), None);
+}
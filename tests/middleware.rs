@@ -0,0 +1,76 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::{ok, rocket, MockTransport};
+use rocketapi::middleware::{InterceptorFuture, Next, RequestInterceptor};
+use serde_json::{json, Value};
+
+// Appends its name to `payload.chain` on the way in and to the `trail` of the envelope body on the way out.
+struct Tag(&'static str);
+
+impl RequestInterceptor for Tag {
+    fn handle<'a>(&'a self, method: &'a str, mut payload: Value, next: Next<'a>) -> InterceptorFuture<'a> {
+        Box::pin(async move {
+            payload["chain"].as_array_mut().unwrap().push(json!(self.0));
+            let mut envelope = next.run(method, payload).await?;
+            if let Some(trail) = envelope["response"]["body"]["trail"].as_array_mut() {
+                trail.push(json!(self.0));
+            }
+            Ok(envelope)
+        })
+    }
+}
+
+// Answers cached methods itself, counting the requests it saw.
+struct Cache {
+    seen: Arc<Mutex<Vec<String>>>,
+}
+
+impl RequestInterceptor for Cache {
+    fn handle<'a>(&'a self, method: &'a str, payload: Value, next: Next<'a>) -> InterceptorFuture<'a> {
+        self.seen.lock().unwrap().push(method.to_string());
+        if method == "instagram/user/get_info" {
+            return Box::pin(async { Ok(ok(json!({ "cached": true, "trail": [] }))) });
+        }
+        next.run(method, payload)
+    }
+}
+
+fn echo() -> Arc<MockTransport> {
+    MockTransport::new(|_, payload| Ok(ok(json!({ "chain": payload["chain"], "trail": [] }))))
+}
+
+#[tokio::test]
+async fn interceptors_run_in_the_order_they_were_added() {
+    let transport = echo();
+    let mut api = rocket(&transport);
+    api.add_interceptor(Tag("outer"));
+    api.add_interceptor(Tag("inner"));
+    let body = api.request("instagram/media/get_info", json!({ "id": 1, "chain": [] })).await.unwrap();
+    // Outermost first on the way in, last on the way out.
+    assert_eq!(transport.payloads(), vec![json!({ "id": 1, "chain": ["outer", "inner"] })]);
+    assert_eq!(body["response"]["body"]["trail"], json!(["inner", "outer"]));
+
+    api.clear_interceptors();
+    let body = api.request("instagram/media/get_info", json!({ "id": 1, "chain": [] })).await.unwrap();
+    assert_eq!(body["response"]["body"], json!({ "chain": [], "trail": [] }));
+}
+
+#[tokio::test]
+async fn an_interceptor_can_answer_without_calling_the_rest_of_the_chain() {
+    let transport = echo();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut api = rocket(&transport);
+    api.add_interceptor(Tag("outer"));
+    api.add_interceptor(Cache { seen: seen.clone() });
+    api.add_interceptor(Tag("inner"));
+
+    let body = api.request("instagram/user/get_info", json!({ "username": "natgeo", "chain": [] })).await.unwrap();
+    assert_eq!(body["response"]["body"], json!({ "cached": true, "trail": ["outer"] }));
+    assert_eq!(transport.call_count(), 0);
+
+    api.request("instagram/media/get_info", json!({ "id": 1, "chain": [] })).await.unwrap();
+    assert_eq!(transport.payloads(), vec![json!({ "id": 1, "chain": ["outer", "inner"] })]);
+    assert_eq!(*seen.lock().unwrap(), vec!["instagram/user/get_info", "instagram/media/get_info"]);
+}
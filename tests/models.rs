@@ -0,0 +1,23 @@
+use rocketapi::models::{MediaInfo, StoryItem};
+use serde_json::json;
+
+#[test]
+fn taken_at_is_seconds_whatever_the_encoding() {
+    let media: MediaInfo = serde_json::from_value(json!({ "id": "1_2", "pk": "1", "taken_at": 1700000000123u64 })).unwrap();
+    assert_eq!(media.taken_at, Some(1700000000));
+    let story: StoryItem = serde_json::from_value(json!({ "id": "3_2", "pk": 3, "taken_at": "1700000000" })).unwrap();
+    assert_eq!(story.taken_at, Some(1700000000));
+    let undated: MediaInfo = serde_json::from_value(json!({ "id": "4_2", "pk": 4 })).unwrap();
+    assert_eq!(undated.taken_at, None);
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn taken_at_datetime_is_utc() {
+    let media: MediaInfo = serde_json::from_value(json!({ "id": "1_2", "pk": 1, "taken_at": 1700000000 })).unwrap();
+    let datetime = media.taken_at_datetime().unwrap();
+    assert_eq!(datetime.unix_timestamp(), 1700000000);
+    assert_eq!(datetime.offset(), time::UtcOffset::UTC);
+    let story: StoryItem = serde_json::from_value(json!({ "id": "3_2", "pk": 3 })).unwrap();
+    assert_eq!(story.taken_at_datetime(), None);
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use serde_json::{json, Value};
+
+fn calls_of(mut sorted: Vec<(String, Value)>) -> Vec<(String, Value)> {
+    // Concurrent helpers (e.g. `overview`) may send their requests in any order.
+    sorted.sort_by_key(|(method, payload)| format!("{} {}", method, payload));
+    sorted
+}
+
+// Runs the namespaced call and the flat call on fresh clients and checks they send the same requests,
+// including one to `$method`.
+macro_rules! same_calls {
+    ($method:expr, |$api:ident| $namespaced:expr, $flat:expr) => {{
+        let namespaced = MockTransport::always(ok(json!({})));
+        {
+            let mut $api = instagram(&namespaced);
+            let _ = $namespaced.await;
+        }
+        let flat = MockTransport::always(ok(json!({})));
+        {
+            let mut $api = instagram(&flat);
+            let _ = $flat.await;
+        }
+        assert!(namespaced.calls().iter().any(|(method, _)| method == $method), "{}", stringify!($namespaced));
+        assert_eq!(calls_of(namespaced.calls()), calls_of(flat.calls()), "{}", stringify!($namespaced));
+    }};
+}
+
+#[tokio::test]
+async fn user_namespace_delegates_to_the_flat_methods() {
+    same_calls!("instagram/user/get_info", |api| api.user().info("natgeo"), api.get_user_info("natgeo"));
+    same_calls!("instagram/user/get_info", |api| api.user().info_typed("natgeo"), api.get_user_info_typed("natgeo"));
+    same_calls!("instagram/user/get_info", |api| api.user().normalized("natgeo"), api.get_normalized_profile("natgeo"));
+    same_calls!("instagram/user/get_info_by_id", |api| api.user().normalized_by_id(&1), api.get_normalized_profile_by_id(&1));
+    same_calls!("instagram/user/get_info_by_id", |api| api.user().friendship(&1, &2), api.get_friendship_status(&1, &2));
+    same_calls!("instagram/user/get_info", |api| api.user().contact_info("natgeo"), api.get_user_contact_info("natgeo"));
+    same_calls!("instagram/user/get_info", |api| api.user().info_timed("natgeo"), api.get_user_info_timed("natgeo"));
+    same_calls!("instagram/user/get_info_by_id", |api| api.user().info_by_id(&1), api.get_user_info_by_id(&1));
+    same_calls!("instagram/user/get_info", |api| api.user().is_verified("natgeo"), api.is_verified("natgeo"));
+    same_calls!("instagram/user/get_info", |api| api.user().canonical_username("NatGeo"), api.canonical_username("NatGeo"));
+    same_calls!("instagram/user/get_info_by_id", |api| api.user().is_private(&1), api.is_private(&1));
+    same_calls!("instagram/user/get_info_by_id", |api| api.user().overview(&1, 5), api.get_profile_overview(&1, 5));
+    same_calls!("instagram/user/get_about", |api| api.user().about(&1), api.get_user_about(&1));
+    same_calls!("instagram/user/get_media", |api| api.user().media(&1, Some(5), Some("m")), api.get_user_media(&1, Some(5), Some("m")));
+    same_calls!("instagram/user/get_media", |api| api.user().media_page(&1, Some(5), None), api.get_user_media_page(&1, Some(5), None));
+    same_calls!("instagram/user/get_media", |api| api.user().all_media(&1, Some(2)), api.get_all_user_media(&1, Some(2)));
+    same_calls!("instagram/user/get_media", |api| api.user().pinned_media(&1), api.get_user_pinned_media(&1));
+    same_calls!("instagram/user/get_media", |api| api.user().media_since(&1, 100, Some(200)), api.get_media_since(&1, 100, Some(200)));
+    same_calls!("instagram/user/get_clips", |api| api.user().clips(&1, Some(5), Some("c")), api.get_user_clips(&1, Some(5), Some("c")));
+    same_calls!("instagram/user/get_clips", |api| api.user().clips_page(&1, None, None), api.get_user_clips_page(&1, None, None));
+    same_calls!("instagram/user/get_guides", |api| api.user().guides(&1, Some("g")), api.get_user_guides(&1, Some("g")));
+    same_calls!("instagram/user/get_tags", |api| api.user().tags(&1, Some(5), None), api.get_user_tags(&1, Some(5), None));
+    same_calls!("instagram/user/get_following", |api| api.user().following(&1, Some(150), Some("f")), api.get_user_following(&1, Some(150), Some("f")));
+    same_calls!("instagram/user/get_following", |api| api.user().following_page(&1, None, None), api.get_user_following_page(&1, None, None));
+    same_calls!("instagram/user/get_following", |api| api.user().all_following(&1, Some(2)), api.get_all_user_following(&1, Some(2)));
+    same_calls!("instagram/user/get_following", |api| api.user().search_following(&1, "nat"), api.search_user_following(&1, "nat"));
+    same_calls!("instagram/user/get_followers", |api| api.user().followers(&1, Some(50), Some("f")), api.get_user_followers(&1, Some(50), Some("f")));
+    same_calls!("instagram/user/get_followers", |api| api.user().followers_page(&1, None, None), api.get_user_followers_page(&1, None, None));
+    same_calls!("instagram/user/get_followers", |api| api.user().all_followers(&1, Some(2)), api.get_all_user_followers(&1, Some(2)));
+    same_calls!("instagram/user/get_followers", |api| api.user().search_followers(&1, "nat"), api.search_user_followers(&1, "nat"));
+    same_calls!("instagram/user/get_stories", |api| api.user().stories(&1), api.get_user_stories(&1));
+    same_calls!("instagram/user/get_stories", |api| api.user().stories_bulk(vec![&1, &2]), api.get_user_stories_bulk(vec![&1, &2]));
+    same_calls!("instagram/user/get_highlights", |api| api.user().highlights(&1), api.get_user_highlights(&1));
+    same_calls!("instagram/user/get_highlights", |api| api.user().all_highlight_stories(&1), api.get_all_user_highlight_stories(&1));
+    same_calls!("instagram/user/get_live", |api| api.user().live(&1), api.get_user_live(&1));
+    same_calls!("instagram/user/get_similar_accounts", |api| api.user().similar_accounts(&1), api.get_user_similar_accounts(&1));
+}
+
+#[tokio::test]
+async fn other_namespaces_delegate_to_the_flat_methods() {
+    same_calls!("instagram/media/get_info", |api| api.media().info(&7), api.get_media_info(&7));
+    same_calls!("instagram/media/get_info_by_shortcode", |api| api.media().info_by_shortcode("Cx1"), api.get_media_info_by_shortcode("Cx1"));
+    same_calls!("instagram/media/get_likes", |api| api.media().likes("Cx1", Some(5), Some("l")), api.get_media_likes("Cx1", Some(5), Some("l")));
+    same_calls!("instagram/media/get_comments", |api| api.media().comments(&7, Some(true), Some("c")), api.get_media_comments(&7, Some(true), Some("c")));
+    same_calls!("instagram/media/get_shortcode_by_id", |api| api.media().shortcode_by_id(&7), api.get_media_shortcode_by_id(&7));
+    same_calls!("instagram/media/get_id_by_shortcode", |api| api.media().id_by_shortcode("Cx1"), api.get_media_id_by_shortcode("Cx1"));
+    same_calls!("instagram/comment/get_likes", |api| api.comment().likes(&9, Some(5), Some("l")), api.get_comment_likes(&9, Some(5), Some("l")));
+    same_calls!("instagram/comment/get_replies", |api| api.comment().replies(&9, &7, Some("r")), api.get_comment_replies(&9, &7, Some("r")));
+    same_calls!("instagram/comment/get_replies", |api| api.comment().all_replies(&9, &7, 10), api.get_all_comment_replies(&9, &7, 10));
+    same_calls!("instagram/hashtag/get_info", |api| api.hashtag().info("rust"), api.get_hashtag_info("rust"));
+    same_calls!("instagram/hashtag/get_media", |api| api.hashtag().media("rust", Some(&2), Some("h")), api.get_hashtag_media("rust", Some(&2), Some("h")));
+    same_calls!("instagram/location/get_info", |api| api.location().info(&3), api.get_location_info(&3));
+    same_calls!("instagram/location/get_media", |api| api.location().media(&3, Some(&2), Some("l")), api.get_location_media(&3, Some(&2), Some("l")));
+    same_calls!("instagram/highlight/get_stories", |api| api.highlight().stories(&4), api.get_highlight_stories(&4));
+    same_calls!("instagram/highlight/get_stories", |api| api.highlight().stories_bulk(vec![&4, &5]), api.get_highlight_stories_bulk(vec![&4, &5]));
+    same_calls!("instagram/guide/get_info", |api| api.guide().info(&6), api.get_guide_info(&6));
+    same_calls!("instagram/audio/get_media", |api| api.audio().media(&8, Some("a")), api.get_audio_media(&8, Some("a")));
+}
+
+#[tokio::test]
+async fn namespaces_share_the_state_of_the_client() {
+    let transport = MockTransport::always(ok(json!({ "user": { "pk": 1, "username": "natgeo" } })));
+    let mut api = instagram(&transport);
+    api.user().info("natgeo").await.unwrap();
+    api.media().info(&7).await.unwrap();
+    assert_eq!(api.counter, 2);
+    assert_eq!(api.last_response["response"]["body"]["user"]["username"], "natgeo");
+    assert_eq!(transport.payloads(), vec![json!({ "username": "natgeo" }), json!({ "id": 7 })]);
+}
+
+#[cfg(feature = "stream")]
+mod streams {
+    use super::*;
+    use futures::StreamExt;
+
+    fn pages(method: &str, payload: &Value) -> Result<Value, rocketapi::errors::RocketAPIError> {
+        let key = if method.ends_with("get_media") { "items" } else { "users" };
+        Ok(match payload["max_id"].as_str() {
+            None => ok(json!({ key: [{ "pk": 1, "username": "a" }], "next_max_id": "p2" })),
+            Some(_) => ok(json!({ key: [{ "pk": 2, "username": "b" }] })),
+        })
+    }
+
+    macro_rules! same_stream {
+        ($method:expr, |$api:ident| $namespaced:expr, $flat:expr) => {{
+            let namespaced = MockTransport::new(pages);
+            {
+                let mut $api = instagram(&namespaced);
+                assert_eq!($namespaced.collect::<Vec<_>>().await.len(), 2);
+            }
+            let flat = MockTransport::new(pages);
+            {
+                let mut $api = instagram(&flat);
+                assert_eq!($flat.collect::<Vec<_>>().await.len(), 2);
+            }
+            assert_eq!(namespaced.calls()[0].0, $method);
+            assert_eq!(namespaced.calls(), flat.calls());
+        }};
+    }
+
+    #[tokio::test]
+    async fn stream_namespaces_delegate_to_the_flat_streams() {
+        same_stream!("instagram/user/get_followers", |api| api.user().stream_followers(&1), api.stream_user_followers(&1));
+        same_stream!("instagram/user/get_media", |api| api.user().stream_media(&1, 20), api.stream_user_media(&1, 20));
+        same_stream!("instagram/comment/get_likes", |api| api.comment().stream_likes(&9), api.stream_comment_likes(&9));
+    }
+
+    #[tokio::test]
+    async fn followers_stream_hands_the_cursor_to_the_next_page() {
+        let transport = MockTransport::new(pages);
+        let mut api = instagram(&transport);
+        let followers: Vec<Value> = api.user().stream_followers(&1).map(Result::unwrap).collect().await;
+        assert_eq!(followers.iter().map(|user| user["pk"].as_u64().unwrap()).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(
+            transport.payloads(),
+            vec![json!({ "id": 1, "count": 100 }), json!({ "id": 1, "count": 100, "max_id": "p2" })]
+        );
+    }
+
+    #[tokio::test]
+    async fn readme_example_compiles_and_runs() {
+        let transport = MockTransport::new(pages);
+        let mut instagram_api = instagram(&transport);
+        let user_id = 1u64;
+        // Same lines as the README.
+        let user = instagram_api.user().info("natgeo").await;
+        let post = instagram_api.media().info_by_shortcode("Cx1a2b3").await;
+        let tagged = instagram_api.hashtag().media("rust", None, None).await;
+        let mut followers = instagram_api.user().stream_followers(&user_id);
+        let mut count = 0;
+        while let Some(follower) = followers.next().await {
+            follower.unwrap();
+            count += 1;
+        }
+        assert!(user.is_ok() && post.is_ok() && tagged.is_ok());
+        assert_eq!(count, 2);
+    }
+}
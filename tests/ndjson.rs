@@ -0,0 +1,80 @@
+#![cfg(feature = "stream")]
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use rocketapi::errors::RocketAPIError;
+use rocketapi::models::UserSummary;
+use rocketapi::ndjson::{export_ndjson, import_ndjson, LineError};
+use serde_json::{json, Value};
+
+fn archive_path(name: &str) -> PathBuf {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    std::env::temp_dir().join(format!("rocketapi-{}-{}-{}.ndjson", name, std::process::id(), nanos))
+}
+
+fn users() -> Vec<Value> {
+    vec![
+        json!({ "pk": 1, "username": "natgeo", "full_name": "National Geographic", "is_verified": true }),
+        json!({ "pk": "2", "username": "nasa", "is_private": false, "friendship_status": { "following": true } }),
+        json!({ "pk": 3, "username": "quiet" }),
+    ]
+}
+
+#[tokio::test]
+async fn exported_items_import_into_typed_models() {
+    let path = archive_path("round-trip");
+    assert_eq!(export_ndjson(&path, users()).unwrap(), 3);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+    let imported: Vec<Result<UserSummary, LineError>> = import_ndjson(&path).unwrap().collect().await;
+    let imported: Vec<UserSummary> = imported.into_iter().map(Result::unwrap).collect();
+    let expected: Vec<UserSummary> = users().into_iter().map(|user| serde_json::from_value(user).unwrap()).collect();
+    assert_eq!(imported, expected);
+    assert_eq!(imported[0].full_name.as_deref(), Some("National Geographic"));
+
+    // Raw values come back unchanged, and a second export replaces the file.
+    let raw: Vec<Value> = import_ndjson(&path).unwrap().map(Result::unwrap).collect().await;
+    assert_eq!(raw, users());
+    assert_eq!(export_ndjson(&path, &raw[..1]).unwrap(), 1);
+    assert_eq!(import_ndjson::<Value>(&path).unwrap().count().await, 1);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn malformed_lines_are_reported_and_skipped() {
+    let path = archive_path("malformed");
+    let lines = [
+        r#"{"pk": 1, "username": "natgeo"}"#,
+        "",
+        r#"{"pk": 2, "username": "#,
+        r#"{"pk": 3}"#,
+        "   ",
+        r#"{"pk": 4, "username": "last"}"#,
+    ];
+    std::fs::write(&path, lines.join("\n")).unwrap();
+
+    let results: Vec<Result<UserSummary, LineError>> = import_ndjson(&path).unwrap().collect().await;
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].as_ref().unwrap().pk, 1);
+    assert_eq!(results[3].as_ref().unwrap().username, "last");
+    // Line numbers count the blank lines too.
+    let errors: Vec<&LineError> = results.iter().filter_map(|result| result.as_ref().err()).collect();
+    assert_eq!(errors.iter().map(|error| error.line).collect::<Vec<_>>(), vec![3, 4]);
+    assert!(errors.iter().all(|error| matches!(error.error, RocketAPIError::ParseError(_))));
+    assert!(errors[1].to_string().starts_with("line 4: "), "{}", errors[1]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn missing_files_fail_right_away() {
+    let path = archive_path("missing");
+    match import_ndjson::<Value>(&path) {
+        Err(RocketAPIError::InvalidParameter(message)) => assert!(message.starts_with(&path.display().to_string()), "{}", message),
+        Err(err) => panic!("unexpected error {:?}", err),
+        Ok(_) => panic!("{} should not exist", path.display()),
+    }
+    let result = export_ndjson(path.join("nested"), users());
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::{ok, HttpResponse, MockServer, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use serde_json::json;
+
+fn client(server: &MockServer, streaming: bool) -> InstagramAPI {
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api.set_base_url(&server.url()).unwrap();
+    api.api.set_streaming_parse(streaming);
+    api
+}
+
+fn error_page() -> String {
+    format!("<html><head><title>502 Bad Gateway</title></head><body>{}</body></html>", "<p>upstream unavailable</p>".repeat(40))
+}
+
+#[tokio::test]
+async fn html_error_pages_keep_the_status_and_the_start_of_the_page() {
+    let page = error_page();
+    assert!(page.len() > 512);
+    let body = page.clone();
+    let server = MockServer::start(move |_| HttpResponse::text(502, &body).header("content-type", "text/html"));
+    for streaming in [false, true] {
+        let mut api = client(&server, streaming);
+        let err = api.get_user_info("natgeo").await.unwrap_err();
+        match &err {
+            RocketAPIError::NonJsonResponse { http_status: 502, body } => assert_eq!(body, &page[..512]),
+            other => panic!("streaming {}: {:?}", streaming, other),
+        }
+        assert!(err.to_string().starts_with("NonJsonResponse: HTTP 502: <html><head><title>502 Bad Gateway"), "{}", err);
+    }
+    // The page answered a regular request of this client.
+    let agent = server.requests().last().unwrap().header("user-agent").unwrap().to_string();
+    assert!(agent.starts_with("rocketapi-rust/"), "{}", agent);
+}
+
+#[tokio::test]
+async fn empty_bodies_are_described_as_such() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/instagram/user/get_info" => HttpResponse::text(200, ""),
+        _ => HttpResponse::text(503, "  \n"),
+    });
+    for streaming in [false, true] {
+        let mut api = client(&server, streaming);
+        let err = api.get_user_info("natgeo").await.unwrap_err();
+        assert!(matches!(&err, RocketAPIError::NonJsonResponse { http_status: 200, body } if body.is_empty()), "{:?}", err);
+        assert_eq!(err.to_string(), "NonJsonResponse: HTTP 200 with an empty body");
+        let err = api.get_user_info_by_id(&1).await.unwrap_err();
+        assert_eq!(err.to_string(), "NonJsonResponse: HTTP 503 with an empty body");
+    }
+}
+
+#[tokio::test]
+async fn last_response_keeps_the_previous_envelope() {
+    let envelope = ok(json!({ "user": { "pk": 1 } }));
+    let body = envelope.clone();
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/instagram/user/get_info_by_id" => HttpResponse::json(200, &body),
+        _ => HttpResponse::text(502, "<html>502 Bad Gateway</html>"),
+    });
+    let mut api = client(&server, false);
+    api.get_user_info_by_id(&1).await.unwrap();
+    assert_eq!(api.last_response, envelope);
+    let result = api.get_user_info("natgeo").await;
+    assert!(matches!(result, Err(RocketAPIError::NonJsonResponse { http_status: 502, .. })), "{:?}", result);
+    assert_eq!(api.last_response, envelope);
+}
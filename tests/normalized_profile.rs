@@ -0,0 +1,76 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::models::NormalizedProfile;
+use serde_json::{json, Value};
+
+fn web_profile() -> Value {
+    serde_json::from_str(include_str!("fixtures/user_info.json")).unwrap()
+}
+
+fn mobile_profile() -> Value {
+    serde_json::from_str(include_str!("fixtures/user_info_by_id.json")).unwrap()
+}
+
+fn natgeo() -> NormalizedProfile {
+    NormalizedProfile {
+        pk: 787132,
+        username: "natgeo".to_string(),
+        full_name: Some("National Geographic".to_string()),
+        biography: Some("Experience the world through the eyes of National Geographic photographers.".to_string()),
+        external_url: Some("https://on.natgeo.com/instagram".to_string()),
+        follower_count: Some(283496412),
+        following_count: Some(164),
+        media_count: Some(30142),
+        is_private: Some(false),
+        is_verified: Some(true),
+        is_business: Some(true),
+        category: Some("Media/news company".to_string()),
+        profile_pic_url: Some("https://scontent.cdninstagram.com/v/t51.2885-19/s320x320/natgeo.jpg".to_string()),
+    }
+}
+
+#[test]
+fn both_endpoints_give_the_same_profile() {
+    let web = NormalizedProfile::from_response(&web_profile()).unwrap();
+    let mobile = NormalizedProfile::from_response(&mobile_profile()).unwrap();
+    assert_eq!(web, natgeo());
+    assert_eq!(mobile, web);
+    // The older `graphql.user` web profile and a bare user object too.
+    let graphql = json!({ "graphql": { "user": web_profile()["data"]["user"] } });
+    assert_eq!(NormalizedProfile::from_response(&graphql).unwrap(), web);
+    assert_eq!(NormalizedProfile::from_response(&mobile_profile()["user"]).unwrap(), web);
+}
+
+#[tokio::test]
+async fn both_methods_give_the_same_profile() {
+    let transport = MockTransport::new(|endpoint, _| {
+        Ok(ok(match endpoint {
+            "instagram/user/get_info" => web_profile(),
+            "instagram/user/get_info_by_id" => mobile_profile(),
+            other => panic!("unexpected endpoint {}", other),
+        }))
+    });
+    let mut api = instagram(&transport);
+    let by_username = api.get_normalized_profile("natgeo").await.unwrap();
+    let by_id = api.get_normalized_profile_by_id(&787132).await.unwrap();
+    assert_eq!(by_username, by_id);
+    assert_eq!(by_id, natgeo());
+}
+
+#[test]
+fn missing_fields_are_none() {
+    let profile = NormalizedProfile::from_response(&json!({ "user": { "pk": "5", "username": "quiet", "biography": "" } })).unwrap();
+    assert_eq!(profile, NormalizedProfile { pk: 5, username: "quiet".to_string(), ..Default::default() });
+    assert_eq!(NormalizedProfile::from_response(&json!({ "user": { "pk": 5 } })), None);
+    assert_eq!(NormalizedProfile::from_response(&json!({ "data": { "user": null } })), None);
+}
+
+#[tokio::test]
+async fn unknown_users_are_not_found() {
+    let transport = MockTransport::always(ok(json!({ "data": { "user": null }, "status": "ok" })));
+    let mut api = instagram(&transport);
+    let result = api.get_normalized_profile("nobody").await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{instagram, ok, MockTransport};
+use serde_json::{json, Value};
+
+fn followers(pks: &[u64], next_max_id: Option<&str>) -> Value {
+    let users: Vec<Value> = pks.iter().map(|pk| json!({ "pk": pk, "username": format!("user{}", pk) })).collect();
+    ok(json!({ "users": users, "next_max_id": next_max_id }))
+}
+
+// User 1: followers 1-4 over two pages. User 2: 3-6. User 3: 1-4 as strings. User 4: none.
+// User 5: 7 and 8, with a cursor that never changes.
+fn audiences() -> Arc<MockTransport> {
+    MockTransport::new(|_, payload| {
+        Ok(match (payload["id"].as_u64().unwrap(), payload["max_id"].as_str()) {
+            (1, None) => followers(&[1, 2], Some("p2")),
+            (1, Some("p2")) => followers(&[3, 4], None),
+            (2, None) => followers(&[3, 4, 5, 6], None),
+            (3, None) => ok(json!({ "users": [{ "pk": "1" }, { "pk": "2" }, { "pk": "3" }, { "pk": "4" }, { "pk": "4" }] })),
+            (4, None) => followers(&[], None),
+            (5, _) => followers(&[7, 8], Some("same")),
+            (id, cursor) => panic!("unexpected page {:?} of {}", cursor, id),
+        })
+    })
+}
+
+#[tokio::test]
+async fn overlap_is_the_jaccard_index_in_percent() {
+    let transport = audiences();
+    let mut api = instagram(&transport);
+    // {3, 4} shared out of {1..6}.
+    let overlap = api.audience_overlap(&1, &2, 100).await.unwrap();
+    assert!((overlap - 100.0 / 3.0).abs() < 1e-9, "{}", overlap);
+    // Same audience, string pks and duplicates included.
+    assert_eq!(api.audience_overlap(&1, &3, 100).await.unwrap(), 100.0);
+    assert_eq!(api.audience_overlap(&2, &5, 100).await.unwrap(), 0.0);
+    // The maximum page size is requested.
+    assert!(transport.payloads().iter().all(|payload| payload["count"] == 100));
+}
+
+#[tokio::test]
+async fn empty_audiences_give_zero_instead_of_nan() {
+    let transport = audiences();
+    let mut api = instagram(&transport);
+    assert_eq!(api.audience_overlap(&4, &4, 100).await.unwrap(), 0.0);
+    assert_eq!(api.audience_overlap(&1, &4, 100).await.unwrap(), 0.0);
+    // A zero sample makes no request at all.
+    let calls = transport.call_count();
+    assert_eq!(api.audience_overlap(&1, &2, 0).await.unwrap(), 0.0);
+    assert_eq!(transport.call_count(), calls);
+}
+
+#[tokio::test]
+async fn samples_stop_at_the_limit_or_a_repeated_cursor() {
+    let transport = audiences();
+    let mut api = instagram(&transport);
+    // Followers 1 and 2 of user 1 against 3-6: nothing shared, and the second page is not needed.
+    assert_eq!(api.audience_overlap(&1, &2, 2).await.unwrap(), 0.0);
+    assert_eq!(transport.call_count(), 2);
+    // {1, 2, 3} against {3, 4, 5}.
+    let overlap = api.audience_overlap(&1, &2, 3).await.unwrap();
+    assert!((overlap - 20.0).abs() < 1e-9, "{}", overlap);
+    // A cursor returned twice ends the sample instead of looping.
+    assert_eq!(api.audience_overlap(&5, &5, 100).await.unwrap(), 100.0);
+    assert_eq!(transport.payloads().iter().filter(|payload| payload["id"] == 5).count(), 4);
+}
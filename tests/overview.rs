@@ -0,0 +1,30 @@
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::retry::RetryPolicy;
+use serde_json::json;
+
+#[tokio::test]
+async fn profile_overview_reports_the_attempts_of_its_four_requests() {
+    // The media request fails once before succeeding.
+    let media_failed = AtomicBool::new(false);
+    let transport = MockTransport::new(move |method, _| {
+        Ok(match method {
+            "instagram/user/get_media" if !media_failed.swap(true, Ordering::SeqCst) => envelope(502, json!({})),
+            "instagram/user/get_info_by_id" => ok(json!({ "user": { "pk": 1 } })),
+            _ => ok(json!({ "items": [], "users": [] })),
+        })
+    });
+    let mut api = instagram(&transport);
+    let policy = RetryPolicy::new(2, Duration::from_millis(1), Duration::from_millis(1), Duration::from_secs(1)).unwrap();
+    api.api.set_retry_policy(policy).unwrap();
+    let overview = api.get_profile_overview(&1, 12).await.unwrap();
+    assert!(overview.info.is_ok() && overview.media.is_ok() && overview.followers.is_ok() && overview.following.is_ok());
+    assert_eq!(transport.call_count(), 5);
+    assert_eq!(api.last_retries, 1);
+    assert_eq!(api.last_attempts().len(), 5);
+    assert_eq!(api.requests_made(), 4);
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::pagination::{find_in_pages, page_fetcher};
+use serde_json::json;
+
+#[tokio::test]
+async fn find_in_pages_stops_on_a_repeated_cursor() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "users": [{ "pk": 1 }], "next_max_id": "a" }))),
+        Ok(ok(json!({ "users": [{ "pk": 2 }], "next_max_id": "a" }))),
+    ]);
+    let mut api = instagram(&transport);
+    let fetch = page_fetcher(|api: &mut InstagramAPI, max_id: Option<String>| {
+        Box::pin(async move { api.get_user_followers(&1, None, max_id.as_deref()).await })
+    });
+    let found = find_in_pages(&mut api, fetch, "users", "next_max_id", |user| user["pk"] == 3, 10).await.unwrap();
+    assert_eq!(found, None);
+    assert_eq!(transport.call_count(), 2);
+}
+
+fn follower_pages() -> Vec<serde_json::Value> {
+    vec![
+        ok(json!({ "users": [{ "pk": 1, "username": "a" }], "next_max_id": "1" })),
+        ok(json!({ "users": [{ "pk": 2, "username": "b" }], "next_max_id": "2" })),
+        ok(json!({ "users": [{ "pk": 3, "username": "c" }], "next_max_id": "3" })),
+    ]
+}
+
+#[tokio::test]
+async fn collect_result_counts_the_page_requests() {
+    for prefetch in [false, true] {
+        let transport = MockTransport::scripted(follower_pages().into_iter().map(Ok).collect());
+        let mut api = instagram(&transport);
+        api.prefetch_pages = prefetch;
+        let result = api.get_all_user_followers(&1, Some(2)).await.unwrap();
+        assert_eq!(result.items.iter().map(|user| user.pk).collect::<Vec<_>>(), vec![1, 2], "prefetch {}", prefetch);
+        assert_eq!((result.pages_fetched, result.requests_made, result.truncated), (2, 2, true), "prefetch {}", prefetch);
+        assert_eq!(transport.call_count(), 2);
+    }
+}
+
+#[test]
+fn is_last_page_follows_the_exhaustion_rules() {
+    use rocketapi::pagination::{is_last_page, CursorKind};
+
+    let next_max_id = [
+        (json!({ "next_max_id": "a" }), false),
+        (json!({ "next_max_id": 42 }), false),
+        (json!({ "next_max_id": "a", "more_available": true }), false),
+        (json!({}), true),
+        (json!({ "next_max_id": null }), true),
+        (json!({ "next_max_id": "" }), true),
+        // A `false` flag wins over a leftover cursor, a missing cursor over a `true` flag.
+        (json!({ "next_max_id": "a", "more_available": false }), true),
+        (json!({ "next_max_id": "a", "has_next_page": false }), true),
+        (json!({ "more_available": true }), true),
+    ];
+    for (response, last) in next_max_id {
+        assert_eq!(is_last_page(&response, CursorKind::NextMaxId), last, "{}", response);
+    }
+
+    let end_cursor = [
+        (json!({ "data": { "user": { "edges": [], "page_info": { "end_cursor": "e", "has_next_page": true } } } }), false),
+        (json!({ "page_info": { "end_cursor": "e" } }), false),
+        (json!({ "data": { "page_info": { "end_cursor": "e", "has_next_page": false } } }), true),
+        (json!({ "data": { "page_info": { "end_cursor": null, "has_next_page": true } } }), true),
+        (json!({ "data": { "page_info": null }, "end_cursor": "e" }), true),
+    ];
+    for (response, last) in end_cursor {
+        assert_eq!(is_last_page(&response, CursorKind::EndCursor), last, "{}", response);
+    }
+
+    assert!(!is_last_page(&json!({ "paging_tokens": { "downwards": "d" } }), CursorKind::Downwards));
+    assert!(is_last_page(&json!({ "paging_tokens": { "downwards": null, "upwards": "u" } }), CursorKind::Downwards));
+    assert!(is_last_page(&json!({ "paging_tokens": {} }), CursorKind::Downwards));
+    assert!(!is_last_page(&json!({ "page_token": "t", "has_more": true }), CursorKind::PageToken));
+    assert!(is_last_page(&json!({ "page_token": "t", "has_more": false }), CursorKind::PageToken));
+    assert!(is_last_page(&json!({ "has_more": true }), CursorKind::PageToken));
+    // Cursors of another convention don't count.
+    assert!(is_last_page(&json!({ "next_max_id": "a" }), CursorKind::PageToken));
+}
+
+#[tokio::test]
+async fn paging_params_round_trip_to_the_next_request() {
+    use rocketapi::pagination::{cursor_to_params, params_to_cursor};
+
+    let transport = MockTransport::always(ok(json!({ "sections": [], "next_max_id": "QVFB", "next_page": 3, "more_available": true })));
+    let mut api = instagram(&transport);
+    let response = api.get_location_media(&7, None, None).await.unwrap();
+    let params = cursor_to_params("instagram/location/get_media", &response);
+    assert_eq!(params.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>(), vec!["max_id=QVFB", "page=3"]);
+
+    // As if echoed in a query string and sent back with unrelated parameters.
+    let query: Vec<(String, String)> = params.into_iter().chain([("lang".to_string(), "en".to_string())]).collect();
+    let state = params_to_cursor("instagram/location/get_media", query).unwrap().unwrap();
+    assert_eq!(state.cursor.endpoint(), Some("instagram/location/get_media"));
+    assert_eq!((state.cursor.value(), state.page), ("QVFB", Some(3)));
+    api.get_location_media(&7, state.page.as_ref(), Some(state.cursor.value())).await.unwrap();
+    assert_eq!(transport.payloads()[1], json!({ "id": 7, "page": 3, "max_id": "QVFB" }));
+}
+
+#[test]
+fn paging_params_of_endpoints_without_page_numbers() {
+    use rocketapi::pagination::{cursor_to_params, params_to_cursor};
+
+    let response = json!({ "users": [], "next_max_id": "25", "next_page": 2 });
+    let params = cursor_to_params("instagram/user/get_followers", &response);
+    assert_eq!(params.len(), 1);
+    let state = params_to_cursor("instagram/user/get_followers", &params).unwrap().unwrap();
+    assert_eq!((state.cursor.value(), state.page), ("25", None));
+    // Last pages have no params, and no params mean the first page.
+    let last = cursor_to_params("instagram/user/get_followers", &json!({ "users": [], "next_max_id": "25", "more_available": false }));
+    assert!(last.is_empty());
+    assert_eq!(params_to_cursor("instagram/user/get_followers", &last).unwrap(), None);
+    assert_eq!(params_to_cursor("instagram/user/get_followers", [("max_id", "")]).unwrap(), None);
+}
+
+#[test]
+fn invalid_paging_params_are_rejected() {
+    use rocketapi::errors::RocketAPIError;
+    use rocketapi::pagination::params_to_cursor;
+
+    for (endpoint, params, message) in [
+        ("instagram/hashtag/get_media", vec![("max_id", "a"), ("page", "two")], "invalid page number: two"),
+        ("instagram/hashtag/get_media", vec![("max_id", "a")], "instagram/hashtag/get_media needs both max_id and page"),
+        ("instagram/hashtag/get_media", vec![("page", "2")], "instagram/hashtag/get_media needs both max_id and page"),
+        ("instagram/user/get_media", vec![("max_id", "a"), ("page", "2")], "instagram/user/get_media is not paginated by page number"),
+    ] {
+        match params_to_cursor(endpoint, params) {
+            Err(RocketAPIError::InvalidParameter(error)) => assert_eq!(error, message),
+            other => panic!("{:?}", other),
+        }
+    }
+}
+
+#[test]
+fn split_page_reads_every_list_endpoint_shape() {
+    use rocketapi::pagination::{split_page, CursorKind};
+
+    let pks = |items: Vec<serde_json::Value>| items.iter().map(|item| item["pk"].as_u64().unwrap_or(0)).collect::<Vec<_>>();
+    for (fixture, kind, expected, cursor) in [
+        // User media, clips, tagged media.
+        (json!({ "items": [{ "pk": 1 }, { "pk": 2 }], "next_max_id": "m2", "more_available": true }), CursorKind::NextMaxId, vec![1, 2], Some("m2")),
+        // Followers, following, likers.
+        (json!({ "users": [{ "pk": 3 }], "next_max_id": 100 }), CursorKind::NextMaxId, vec![3], Some("100")),
+        // Comments.
+        (json!({ "comments": [{ "pk": 4 }, { "pk": 5 }], "next_max_id": "{\"cached_comments_cursor\": \"5\"}" }), CursorKind::NextMaxId, vec![4, 5], Some("{\"cached_comments_cursor\": \"5\"}")),
+        // Threads replies.
+        (json!({ "reply_threads": [{ "pk": 6 }], "paging_tokens": { "downwards": "d2" } }), CursorKind::Downwards, vec![6], Some("d2")),
+        // Threads search.
+        (json!({ "data": [{ "pk": 7 }], "page_token": "t2", "has_more": true }), CursorKind::PageToken, vec![7], Some("t2")),
+        // GraphQL connection nested in the response.
+        (
+            json!({ "data": { "user": { "edge_followed_by": {
+                "edges": [{ "node": { "id": "8" } }, { "node": { "id": "9" } }],
+                "page_info": { "end_cursor": "g2", "has_next_page": true },
+            } } } }),
+            CursorKind::EndCursor,
+            vec![0, 0],
+            Some("g2"),
+        ),
+        // Last pages.
+        (json!({ "users": [{ "pk": 10 }], "next_max_id": null }), CursorKind::NextMaxId, vec![10], None),
+        (json!({ "items": [], "more_available": false }), CursorKind::NextMaxId, vec![], None),
+    ] {
+        let (items, next) = split_page(&fixture, kind);
+        assert_eq!(pks(items), expected, "{}", fixture);
+        assert_eq!(next.as_deref(), cursor, "{}", fixture);
+    }
+}
+
+#[test]
+fn split_page_picks_the_first_items_array() {
+    use rocketapi::pagination::{split_page, CursorKind};
+
+    // `items` wins over `users`, non-array fields are skipped.
+    let (items, _) = split_page(&json!({ "users": [{ "pk": 2 }], "items": [{ "pk": 1 }] }), CursorKind::NextMaxId);
+    assert_eq!(items, vec![json!({ "pk": 1 })]);
+    let (items, _) = split_page(&json!({ "items": null, "comments": [{ "pk": 3 }] }), CursorKind::NextMaxId);
+    assert_eq!(items, vec![json!({ "pk": 3 })]);
+    let (items, _) = split_page(&json!({ "data": { "page_info": { "end_cursor": "e" }, "edges": [{ "node": {} }] } }), CursorKind::EndCursor);
+    assert_eq!(items, vec![json!({ "node": {} })]);
+    // Responses without a list.
+    for response in [json!({ "user": { "pk": 1 } }), json!([]), json!(null)] {
+        assert_eq!(split_page(&response, CursorKind::NextMaxId), (vec![], None), "{}", response);
+    }
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::helpers::is_pinned;
+use serde_json::{json, Value};
+
+fn media(pk: u64, extra: Value) -> Value {
+    let mut media = json!({ "id": format!("{}_1", pk), "pk": pk, "taken_at": 1700000000 - pk });
+    if let (Some(media), Some(extra)) = (media.as_object_mut(), extra.as_object()) {
+        media.extend(extra.clone());
+    }
+    media
+}
+
+#[tokio::test]
+async fn pinned_posts_come_from_the_first_page_only() {
+    let transport = MockTransport::always(ok(json!({
+        "items": [
+            media(1, json!({ "timeline_pinned_user_ids": [25025320] })),
+            media(2, json!({ "clips_tab_pinned_user_ids": [25025320], "timeline_pinned_user_ids": [] })),
+            media(3, json!({ "timeline_pinned_user_ids": [] })),
+            media(4, json!({})),
+        ],
+        "next_max_id": "p2",
+        "more_available": true,
+    })));
+    let mut api = instagram(&transport);
+    let pinned = api.get_user_pinned_media(&25025320).await.unwrap();
+    assert_eq!(pinned.iter().map(|item| item["pk"].as_u64().unwrap()).collect::<Vec<_>>(), vec![1, 2]);
+    // One request for the largest page, the cursor is not followed.
+    assert_eq!(transport.calls(), vec![("instagram/user/get_media".to_string(), json!({ "id": 25025320, "count": 50 }))]);
+}
+
+#[tokio::test]
+async fn profiles_without_pins() {
+    for body in [json!({ "items": [media(1, json!({})), media(2, json!({ "timeline_pinned_user_ids": null }))] }), json!({ "items": [] }), json!({})] {
+        let transport = MockTransport::always(ok(body));
+        assert!(instagram(&transport).get_user_pinned_media(&1).await.unwrap().is_empty());
+    }
+}
+
+#[test]
+fn pins_of_the_grid_and_the_reels_tab() {
+    assert!(is_pinned(&media(1, json!({ "timeline_pinned_user_ids": [1] }))));
+    assert!(is_pinned(&media(1, json!({ "clips_tab_pinned_user_ids": [1] }))));
+    assert!(!is_pinned(&media(1, json!({ "timeline_pinned_user_ids": [], "clips_tab_pinned_user_ids": [] }))));
+    assert!(!is_pinned(&media(1, json!({ "timeline_pinned_user_ids": "1" }))));
+}
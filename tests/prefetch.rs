@@ -0,0 +1,66 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::{ok, TIMEOUT};
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::transport::{Transport, TransportFuture};
+use rocketapi::RocketAPI;
+use serde_json::{json, Value};
+
+// Five follower pages answered after 20ms each, counting the requests in flight at the same time.
+#[derive(Default)]
+struct SlowPages {
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+    cursors: Mutex<Vec<Option<String>>>,
+}
+
+impl Transport for SlowPages {
+    fn call(&self, _: &str, payload: Value) -> TransportFuture {
+        let cursor = payload["max_id"].as_str().map(str::to_string);
+        self.cursors.lock().unwrap().push(cursor.clone());
+        let (in_flight, max_in_flight) = (self.in_flight.clone(), self.max_in_flight.clone());
+        Box::pin(async move {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            let page: u64 = cursor.map_or(1, |cursor| cursor.parse().unwrap());
+            let next = (page < 5).then(|| (page + 1).to_string());
+            Ok(ok(json!({ "users": [{ "pk": page, "username": format!("u{}", page) }], "next_max_id": next })))
+        })
+    }
+}
+
+fn prefetching(transport: &Arc<SlowPages>) -> InstagramAPI {
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api = RocketAPI::with_transport("test-token".to_string(), TIMEOUT, transport.clone());
+    api.prefetch_pages = true;
+    api
+}
+
+#[tokio::test]
+async fn at_most_one_page_is_requested_ahead() {
+    let transport = Arc::new(SlowPages::default());
+    let mut api = prefetching(&transport);
+    let result = api.get_all_user_followers(&1, None).await.unwrap();
+    assert_eq!(result.items.iter().map(|user| user.pk).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 1);
+    let expected: Vec<Option<String>> = std::iter::once(None).chain((2..=5).map(|page| Some(page.to_string()))).collect();
+    assert_eq!(*transport.cursors.lock().unwrap(), expected);
+    assert_eq!(api.counter, 5);
+}
+
+#[tokio::test]
+async fn no_page_is_prefetched_past_max_pages() {
+    let transport = Arc::new(SlowPages::default());
+    let mut api = prefetching(&transport);
+    let result = api.get_all_user_following(&1, Some(3)).await.unwrap();
+    assert_eq!(result.items.len(), 3);
+    assert!(result.truncated);
+    assert_eq!(transport.cursors.lock().unwrap().len(), 3);
+    assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use serde_json::json;
+
+#[tokio::test]
+async fn is_verified_reads_the_badge_of_the_profile() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "data": { "user": { "username": "natgeo", "is_verified": true } } }))),
+        Ok(ok(json!({ "data": { "user": { "username": "someone", "is_verified": false } } }))),
+        // The field is missing from some profiles.
+        Ok(ok(json!({ "data": { "user": { "username": "someone" } } }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert!(api.is_verified("natgeo").await.unwrap());
+    assert!(!api.is_verified("someone").await.unwrap());
+    assert!(!api.is_verified("someone").await.unwrap());
+    assert_eq!(transport.calls()[0], ("instagram/user/get_info".to_string(), json!({ "username": "natgeo" })));
+}
+
+#[tokio::test]
+async fn is_verified_of_a_missing_user() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "data": { "user": null }, "status": "ok" }))),
+        Ok(envelope(404, json!({ "message": "User not found" }))),
+    ]);
+    let mut api = instagram(&transport);
+    for _ in 0..2 {
+        let result = api.is_verified("deleted.account").await;
+        assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+    }
+}
+
+#[tokio::test]
+async fn canonical_username_follows_renamed_accounts() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "data": { "user": { "pk": "1", "username": "natgeo" } } }))),
+        // An old handle resolving to the renamed account.
+        Ok(ok(json!({ "data": { "user": { "pk": "2", "username": "new.handle" } } }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert_eq!(api.canonical_username("NatGeo").await.unwrap(), "natgeo");
+    assert_eq!(api.canonical_username("old_handle").await.unwrap(), "new.handle");
+    let payloads = transport.payloads();
+    assert_eq!(payloads, vec![json!({ "username": "natgeo" }), json!({ "username": "old_handle" })]);
+}
+
+#[tokio::test]
+async fn canonical_username_of_a_missing_user() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "data": { "user": null } }))),
+        Ok(ok(json!({ "data": { "user": { "pk": "1" } } }))),
+        Ok(envelope(404, json!({ "message": "User not found" }))),
+    ]);
+    let mut api = instagram(&transport);
+    for _ in 0..3 {
+        let result = api.canonical_username("deleted.account").await;
+        assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+    }
+}
+
+#[tokio::test]
+async fn is_private_reads_the_profile_by_id() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "user": { "pk": 1, "username": "locked", "is_private": true } }))),
+        Ok(ok(json!({ "user": { "pk": 2, "username": "open", "is_private": false } }))),
+        Ok(ok(json!({ "user": { "pk": 3, "username": "unknown" } }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert!(api.is_private(&1).await.unwrap());
+    assert!(!api.is_private(&2).await.unwrap());
+    assert!(!api.is_private(&3).await.unwrap());
+    assert_eq!(transport.calls()[0], ("instagram/user/get_info_by_id".to_string(), json!({ "id": 1 })));
+}
+
+#[tokio::test]
+async fn is_private_of_a_missing_user() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "user": null, "status": "ok" }))),
+        Ok(envelope(404, json!({ "message": "User not found" }))),
+    ]);
+    let mut api = instagram(&transport);
+    for _ in 0..2 {
+        let result = api.is_private(&404).await;
+        assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+    }
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::{ok, HttpResponse, MockServer, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::replay::ReplayTransport;
+use rocketapi::RocketAPI;
+use serde_json::json;
+
+fn fixture_path(name: &str) -> PathBuf {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    std::env::temp_dir().join(format!("rocketapi-{}-{}-{}.json", name, std::process::id(), nanos))
+}
+
+fn client(transport: ReplayTransport) -> InstagramAPI {
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api = RocketAPI::with_transport("test-token".to_string(), TIMEOUT, Arc::new(transport));
+    api
+}
+
+#[tokio::test]
+async fn recorded_responses_replay_without_the_network() {
+    let path = fixture_path("round-trip");
+    let server = MockServer::start(|request| {
+        let username = request.json()["username"].as_str().unwrap_or_default().to_string();
+        HttpResponse::json(200, &ok(json!({ "data": { "user": { "username": username } } })))
+    });
+    let mut live = RocketAPI::new("test-token".to_string(), TIMEOUT);
+    live.set_base_url(&server.url()).unwrap();
+
+    let mut recording = client(ReplayTransport::record(&path, live));
+    let natgeo = recording.get_user_info("natgeo").await.unwrap();
+    let nasa = recording.get_user_info("nasa").await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+
+    let replay = ReplayTransport::replay(&path).unwrap();
+    assert_eq!(replay.len(), 2);
+    let mut replaying = client(replay);
+    assert_eq!(replaying.get_user_info("nasa").await.unwrap(), nasa);
+    assert_eq!(replaying.get_user_info("natgeo").await.unwrap(), natgeo);
+    assert!(matches!(replaying.get_user_info("nike").await, Err(RocketAPIError::InvalidParameter(_))));
+    assert_eq!(server.requests().len(), 2);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn unreadable_and_malformed_recordings() {
+    let missing = fixture_path("missing");
+    assert!(matches!(ReplayTransport::replay(&missing), Err(RocketAPIError::IoError { path, .. }) if path == missing));
+    let malformed = fixture_path("malformed");
+    std::fs::write(&malformed, "[{").unwrap();
+    assert!(matches!(ReplayTransport::replay(&malformed), Err(RocketAPIError::ParseError(_))));
+    std::fs::remove_file(malformed).unwrap();
+}
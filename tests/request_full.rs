@@ -0,0 +1,30 @@
+mod common;
+
+use common::{ok, rocket, HttpResponse, MockServer, MockTransport, TIMEOUT};
+use reqwest::StatusCode;
+use rocketapi::RocketAPI;
+use serde_json::json;
+
+#[tokio::test]
+async fn the_gateway_status_and_headers_come_with_the_body() {
+    let server = MockServer::start(|_| {
+        HttpResponse::json(200, &ok(json!({ "user": { "pk": 1 } })))
+            .header("X-RateLimit-Remaining", "41")
+            .header("X-Request-Id", "req-7")
+    });
+    let api = RocketAPI::with_base_url("test-token".to_string(), TIMEOUT, &server.url()).unwrap();
+    let raw = api.request_full("instagram/user/get_info", json!({ "username": "natgeo" })).await.unwrap();
+    assert_eq!(raw.status, StatusCode::OK);
+    assert_eq!(raw.headers["x-ratelimit-remaining"], "41");
+    assert_eq!(raw.headers["x-request-id"], "req-7");
+    assert_eq!(raw.body, ok(json!({ "user": { "pk": 1 } })));
+}
+
+#[tokio::test]
+async fn a_custom_transport_reports_200_without_headers() {
+    let transport = MockTransport::always(ok(json!({ "user": { "pk": 1 } })));
+    let raw = rocket(&transport).request_full("instagram/user/get_info", json!({ "username": "natgeo" })).await.unwrap();
+    assert_eq!(raw.status, StatusCode::OK);
+    assert!(raw.headers.is_empty());
+    assert_eq!(raw.body["response"]["body"]["user"]["pk"], 1);
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{instagram, ok, HttpResponse, MockServer, MockTransport, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::retry::RetryPolicy;
+use serde_json::json;
+
+#[tokio::test]
+async fn get_user_media_recovers_from_connection_blips() {
+    // Every third connection is dropped without an answer, every fifth gets a load balancer error page.
+    let served = AtomicUsize::new(0);
+    let server = MockServer::start(move |_| match served.fetch_add(1, Ordering::SeqCst) + 1 {
+        n if n % 3 == 0 => HttpResponse::reset(),
+        n if n % 5 == 0 => HttpResponse::text(502, "<html>502 Bad Gateway</html>"),
+        _ => HttpResponse::json(200, &ok(json!({ "items": [], "more_available": false }))),
+    });
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api.set_base_url(&server.url()).unwrap();
+    let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(5), Duration::from_secs(1)).unwrap();
+    api.api.set_retry_policy(policy).unwrap();
+    for _ in 0..30 {
+        api.get_user_media(&1, Some(12), None).await.unwrap();
+    }
+    assert!(server.requests().len() > 30);
+}
+
+#[tokio::test]
+async fn last_attempts_log_the_failed_attempt_and_its_backoff() {
+    let served = AtomicUsize::new(0);
+    let server = MockServer::start(move |_| match served.fetch_add(1, Ordering::SeqCst) {
+        0 => HttpResponse::text(502, "<html>502 Bad Gateway</html>"),
+        _ => HttpResponse::json(200, &ok(json!({ "user": { "pk": 1 } }))).header("X-Request-Id", "req-2"),
+    });
+    let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+    api.api.set_base_url(&server.url()).unwrap();
+    let policy = RetryPolicy::new(3, Duration::from_millis(20), Duration::from_millis(20), Duration::from_secs(1)).unwrap();
+    api.api.set_retry_policy(policy).unwrap();
+    api.get_user_info("natgeo").await.unwrap();
+
+    assert_eq!(api.last_retries, 1);
+    let attempts = api.last_attempts();
+    assert_eq!(attempts.len(), 2);
+    let (failed, succeeded) = (&attempts[0], &attempts[1]);
+    assert_eq!((failed.attempt, failed.http_status, failed.status_code), (1, Some(502), None));
+    assert!(failed.error.as_deref().unwrap().starts_with("NonJsonResponse: HTTP 502"), "{:?}", failed);
+    assert_eq!(failed.backoff, Some(Duration::from_millis(20)));
+    assert_eq!((succeeded.attempt, succeeded.http_status, succeeded.status_code), (2, Some(200), Some(200)));
+    assert_eq!((succeeded.error.as_deref(), succeeded.backoff), (None, None));
+    assert_eq!(succeeded.headers["x-request-id"], "req-2");
+    assert_eq!(api.last_status(), Some(200));
+}
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+// Every call fails with a load balancer error page, which is retried.
+fn failing_api() -> (Arc<MockTransport>, InstagramAPI) {
+    let transport = MockTransport::new(|_, _| {
+        Err(RocketAPIError::NonJsonResponse { http_status: 502, body: "<html>502 Bad Gateway</html>".to_string() })
+    });
+    let api = instagram(&transport);
+    (transport, api)
+}
+
+#[test]
+fn validate_rejects_inconsistent_policies() {
+    for (policy, message) in [
+        (RetryPolicy { max_attempts: 0, ..RetryPolicy::none() }, "max_attempts must be at least 1"),
+        (RetryPolicy { max_attempts: 3, base_delay: ms(20), max_delay: ms(10), max_total_delay: ms(100), jitter: ms(0) }, "base_delay (20ms) is greater than max_delay (10ms)"),
+        (RetryPolicy { max_attempts: 3, base_delay: ms(10), max_delay: ms(200), max_total_delay: ms(100), jitter: ms(0) }, "max_delay (200ms) is greater than max_total_delay (100ms)"),
+    ] {
+        match policy.validate() {
+            Err(RocketAPIError::InvalidParameter(error)) => assert_eq!(error, message),
+            other => panic!("{:?}", other),
+        }
+        let mut api = InstagramAPI::new("test-token".to_string(), TIMEOUT);
+        assert!(api.api.set_retry_policy(policy).is_err());
+        assert_eq!(*api.api.retry_policy(), RetryPolicy::none());
+    }
+    RetryPolicy::none().validate().unwrap();
+    assert!(RetryPolicy::new(1, ms(5), ms(5), ms(5)).is_ok());
+}
+
+#[test]
+fn delay_doubles_up_to_max_delay() {
+    let policy = RetryPolicy::new(10, ms(10), ms(50), ms(1000)).unwrap();
+    let delays: Vec<Duration> = (1..=5).map(|retry| policy.delay(retry)).collect();
+    assert_eq!(delays, vec![ms(10), ms(20), ms(40), ms(50), ms(50)]);
+    assert_eq!(policy.delay(0), ms(10));
+    // Large retry numbers saturate instead of overflowing.
+    assert_eq!(policy.delay(40), ms(50));
+    assert_eq!(policy.delay(u32::MAX), ms(50));
+}
+
+#[tokio::test]
+async fn the_attempt_ceiling_stops_retries_before_the_backoff_cap() {
+    let (transport, mut api) = failing_api();
+    api.api.set_retry_policy(RetryPolicy::new(3, ms(1), ms(100), Duration::from_secs(10)).unwrap()).unwrap();
+    let result = api.get_user_info("natgeo").await;
+    assert!(matches!(result, Err(RocketAPIError::NonJsonResponse { http_status: 502, .. })), "{:?}", result);
+    assert_eq!(transport.call_count(), 3);
+    assert_eq!(api.last_retries, 2);
+    let backoffs: Vec<Option<Duration>> = api.last_attempts().iter().map(|attempt| attempt.backoff).collect();
+    assert_eq!(backoffs, vec![Some(ms(1)), Some(ms(2)), None]);
+}
+
+#[tokio::test]
+async fn the_backoff_cap_stops_retries_before_the_attempt_ceiling() {
+    let (transport, mut api) = failing_api();
+    // 2 + 4 + 4 = 10ms of backoff, a fourth retry would wait past max_total_delay.
+    api.api.set_retry_policy(RetryPolicy::new(100, ms(2), ms(4), ms(10)).unwrap()).unwrap();
+    api.get_user_info("natgeo").await.unwrap_err();
+    assert_eq!(transport.call_count(), 4);
+    let backoffs: Vec<Option<Duration>> = api.last_attempts().iter().map(|attempt| attempt.backoff).collect();
+    assert_eq!(backoffs, vec![Some(ms(2)), Some(ms(4)), Some(ms(4)), None]);
+    assert!(backoffs.iter().flatten().all(|delay| *delay <= ms(4)));
+}
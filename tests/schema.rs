@@ -0,0 +1,54 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::helpers::schema_fingerprint;
+use serde_json::json;
+
+fn user_info() -> serde_json::Value {
+    json!({ "user": { "pk": 1, "username": "natgeo" }, "status": "ok" })
+}
+
+#[test]
+fn fingerprints_depend_on_top_level_keys_only() {
+    let fingerprint = schema_fingerprint(&user_info());
+    assert_ne!(fingerprint, 0);
+    // Values, nested keys and key order don't matter.
+    assert_eq!(schema_fingerprint(&json!({ "status": "fail", "user": null })), fingerprint);
+    assert_eq!(schema_fingerprint(&json!({ "user": { "pk": 2, "is_private": true }, "status": "ok" })), fingerprint);
+    // An added, removed or renamed key does.
+    assert_ne!(schema_fingerprint(&json!({ "user": {}, "status": "ok", "seo": {} })), fingerprint);
+    assert_ne!(schema_fingerprint(&json!({ "user": {} })), fingerprint);
+    assert_ne!(schema_fingerprint(&json!({ "users": {}, "status": "ok" })), fingerprint);
+}
+
+#[test]
+fn fingerprints_are_stable_across_builds() {
+    // FNV-1a of "status\nuser": baselines stored by an earlier build keep matching.
+    assert_eq!(schema_fingerprint(&user_info()), 0x895c_eada_f0bb_80ec);
+}
+
+#[test]
+fn non_objects_and_empty_objects_have_no_fingerprint() {
+    for body in [json!({}), json!([1, 2]), json!("text"), json!(null), json!(3)] {
+        assert_eq!(schema_fingerprint(&body), 0, "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn schema_changed_compares_with_the_baseline_of_the_endpoint() {
+    let transport = MockTransport::always(ok(user_info()));
+    let mut api = instagram(&transport);
+    assert!(!api.api.schema_changed("instagram/user/get_info", &json!({ "anything": 1 })));
+    api.api.set_schema_baselines(HashMap::from([("instagram/user/get_info".to_string(), schema_fingerprint(&user_info()))]));
+    assert_eq!(api.api.schema_baselines().len(), 1);
+    assert!(!api.api.schema_changed("instagram/user/get_info", &user_info()));
+    assert!(api.api.schema_changed("instagram/user/get_info", &json!({ "user": {} })));
+    assert!(api.api.schema_changed("instagram/user/get_info", &json!([])));
+    // Endpoints without a baseline are never reported.
+    assert!(!api.api.schema_changed("instagram/media/get_info", &json!({ "user": {} })));
+    // A changed schema is only reported, the body is still returned.
+    api.api.set_schema_baselines(HashMap::from([("instagram/user/get_info".to_string(), 1)]));
+    assert_eq!(api.get_user_info("natgeo").await.unwrap(), user_info());
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::models::{SearchContext, SearchRequest};
+use serde_json::json;
+
+#[test]
+fn search_requests_serialize_as_the_payload() {
+    assert_eq!(serde_json::to_value(SearchRequest::new("rust")).unwrap(), json!({ "query": "rust" }));
+    for (context, name) in [
+        (SearchContext::Blended, "blended"),
+        (SearchContext::User, "user"),
+        (SearchContext::Hashtag, "hashtag"),
+        (SearchContext::Place, "place"),
+    ] {
+        let request = SearchRequest::new("rust").context(context);
+        assert_eq!(request.context, Some(context));
+        assert_eq!(serde_json::to_value(&request).unwrap(), json!({ "query": "rust", "context": name }));
+    }
+}
+
+#[tokio::test]
+async fn search_with_posts_the_request() {
+    let transport = MockTransport::always(ok(json!({ "users": [], "hashtags": [], "places": [] })));
+    let mut api = instagram(&transport);
+    api.search_with(&SearchRequest::new("café").context(SearchContext::Place)).await.unwrap();
+    // `search` is `search_with` without a context.
+    api.search("natgeo").await.unwrap();
+    assert_eq!(
+        transport.calls(),
+        vec![
+            ("instagram/search".to_string(), json!({ "query": "café", "context": "place" })),
+            ("instagram/search".to_string(), json!({ "query": "natgeo" })),
+        ]
+    );
+}
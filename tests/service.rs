@@ -0,0 +1,60 @@
+#![cfg(feature = "tower")]
+
+mod common;
+
+use std::future::{ready, Ready};
+
+use common::{ok, rocket, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::service::RocketRequest;
+use serde_json::{json, Value};
+use tower::retry::{Policy, RetryLayer};
+use tower::ServiceBuilder;
+
+// Retries gateway error pages, at most `.0` more times.
+#[derive(Clone)]
+struct RetryGatewayErrors(usize);
+
+impl Policy<RocketRequest, Value, RocketAPIError> for RetryGatewayErrors {
+    type Future = Ready<()>;
+
+    fn retry(&mut self, _request: &mut RocketRequest, result: &mut Result<Value, RocketAPIError>) -> Option<Self::Future> {
+        match result {
+            Err(RocketAPIError::NonJsonResponse { http_status, .. }) if *http_status >= 500 && self.0 > 0 => {
+                self.0 -= 1;
+                Some(ready(()))
+            }
+            _ => None,
+        }
+    }
+
+    fn clone_request(&mut self, request: &RocketRequest) -> Option<RocketRequest> {
+        Some(request.clone())
+    }
+}
+
+fn bad_gateway() -> Result<Value, RocketAPIError> {
+    Err(RocketAPIError::NonJsonResponse { http_status: 502, body: "<html>502 Bad Gateway</html>".to_string() })
+}
+
+#[tokio::test]
+async fn tower_retry_layer_drives_the_client() {
+    let transport = MockTransport::scripted(vec![bad_gateway(), bad_gateway(), Ok(ok(json!({ "data": { "user": { "username": "natgeo" } } })))]);
+    let service = ServiceBuilder::new().layer(RetryLayer::new(RetryGatewayErrors(2))).service(rocket(&transport));
+    let mut api = InstagramAPI::from_service(service);
+    let body = api.get_user_info("natgeo").await.unwrap();
+    assert_eq!(body["data"]["user"]["username"], "natgeo");
+    assert_eq!(transport.call_count(), 3);
+    assert!(transport.calls().iter().all(|(method, payload)| method == "instagram/user/get_info" && payload["username"] == "natgeo"));
+}
+
+#[tokio::test]
+async fn tower_retry_layer_gives_up_with_the_last_error() {
+    let transport = MockTransport::scripted(vec![bad_gateway(), bad_gateway()]);
+    let service = ServiceBuilder::new().layer(RetryLayer::new(RetryGatewayErrors(1))).service(rocket(&transport));
+    let mut api = InstagramAPI::from_service(service);
+    let result = api.get_user_info("natgeo").await;
+    assert!(matches!(result, Err(RocketAPIError::NonJsonResponse { http_status: 502, .. })), "{:?}", result);
+    assert_eq!(transport.call_count(), 2);
+}
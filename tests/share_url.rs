@@ -0,0 +1,71 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use std::net::SocketAddr;
+
+use common::{HttpRequest, HttpResponse, MockServer, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::urls::{resolve_share_url_at, InstagramUrl};
+
+// `/hops/<k>/<n>` redirects to `/hops/<k>/<n + 1>` until `n == k`, then to the post: `k + 1` redirects in total.
+fn share_server() -> MockServer {
+    MockServer::start(|request: &HttpRequest| {
+        let port = request.header("host").and_then(|host| host.rsplit(':').next()).unwrap_or("80").to_string();
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        match segments.as_slice() {
+            ["share", "abc"] => HttpResponse::redirect(&format!("http://l.instagram.com:{}/hops/0/0", port)),
+            ["share", "evil"] => HttpResponse::redirect(&format!("http://evil.example.com:{}/p/EVIL/", port)),
+            ["hops", limit, n] => {
+                let (limit, n): (u32, u32) = (limit.parse().unwrap(), n.parse().unwrap());
+                match n < limit {
+                    true => HttpResponse::redirect(&format!("/hops/{}/{}", limit, n + 1)),
+                    false => HttpResponse::redirect(&format!("http://www.instagram.com:{}/p/CODE123/", port)),
+                }
+            }
+            ["p", _] => HttpResponse::text(200, "<html>post</html>"),
+            _ => HttpResponse::text(404, ""),
+        }
+    })
+}
+
+fn addr(server: &MockServer) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], server.port))
+}
+
+#[tokio::test]
+async fn share_links_follow_instagram_redirects() {
+    let server = share_server();
+    let url = format!("http://www.instagram.com:{}/share/abc", server.port);
+    let resolved = resolve_share_url_at(&url, TIMEOUT, addr(&server)).await.unwrap();
+    assert_eq!(resolved, InstagramUrl::Post("CODE123".to_string()));
+    let hosts: Vec<String> = server.requests().iter().map(|request| request.header("host").unwrap().to_string()).collect();
+    assert_eq!(hosts.len(), 3);
+    assert!(hosts[1].starts_with("l.instagram.com:") && hosts[2].starts_with("www.instagram.com:"), "{:?}", hosts);
+}
+
+#[tokio::test]
+async fn off_host_redirects_are_refused() {
+    let server = share_server();
+    let url = format!("http://www.instagram.com:{}/share/evil", server.port);
+    match resolve_share_url_at(&url, TIMEOUT, addr(&server)).await {
+        Err(RocketAPIError::InvalidParameter(message)) => assert!(message.contains("evil.example.com"), "{}", message),
+        other => panic!("{:?}", other),
+    }
+    // The other host is never contacted.
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn redirects_are_followed_at_most_ten_times() {
+    let server = share_server();
+    let ten = format!("http://www.instagram.com:{}/hops/9/0", server.port);
+    assert!(resolve_share_url_at(&ten, TIMEOUT, addr(&server)).await.is_ok());
+    let eleven = format!("http://www.instagram.com:{}/hops/10/0", server.port);
+    match resolve_share_url_at(&eleven, TIMEOUT, addr(&server)).await {
+        Err(RocketAPIError::RequestError(err)) => assert!(err.is_redirect(), "{:?}", err),
+        other => panic!("{:?}", other),
+    }
+    // 11 requests for the first link, 11 before giving up on the second one.
+    assert_eq!(server.requests().len(), 22);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::helpers::is_sponsored;
+use rocketapi::instagramapi::SponsoredFilter;
+use serde_json::{json, Value};
+
+fn media(pk: u64, extra: Value) -> Value {
+    let mut media = json!({ "id": format!("{}_1", pk), "pk": pk, "media_type": 1 });
+    if let (Some(media), Some(extra)) = (media.as_object_mut(), extra.as_object()) {
+        media.extend(extra.clone());
+    }
+    media
+}
+
+// Two pages: 1 and 3 are paid partnerships on the first one, the second one has no ads.
+fn feed_with_ads(_: &str, payload: &Value) -> Result<Value, RocketAPIError> {
+    Ok(match payload["max_id"].as_str() {
+        None => ok(json!({
+            "items": [
+                media(1, json!({ "is_paid_partnership": true })),
+                media(2, json!({ "is_paid_partnership": false, "sponsor_tags": [] })),
+                media(3, json!({ "sponsor_tags": [{ "sponsor": { "pk": 9, "username": "brand" } }] })),
+                media(4, json!({})),
+            ],
+            "next_max_id": "p2",
+        })),
+        Some("p2") => ok(json!({ "items": [media(5, json!({})), media(6, json!({ "sponsor_tags": null }))], "next_max_id": null })),
+        Some(cursor) => panic!("unexpected cursor {}", cursor),
+    })
+}
+
+async fn collected(transport: &std::sync::Arc<MockTransport>, filter: SponsoredFilter) -> Vec<u64> {
+    let mut api = instagram(transport);
+    api.sponsored = filter;
+    api.get_all_user_media(&1, None).await.unwrap().items.iter().map(|item| item.pk).collect()
+}
+
+#[test]
+fn paid_partnerships_are_detected() {
+    assert!(is_sponsored(&media(1, json!({ "is_paid_partnership": true }))));
+    assert!(is_sponsored(&media(1, json!({ "is_paid_partnership": false, "sponsor_tags": [{ "sponsor": {} }] }))));
+    for extra in [
+        json!({}),
+        json!({ "is_paid_partnership": false }),
+        json!({ "is_paid_partnership": "true" }),
+        json!({ "sponsor_tags": [] }),
+        json!({ "sponsor_tags": null }),
+    ] {
+        assert!(!is_sponsored(&media(1, extra.clone())), "{}", extra);
+    }
+}
+
+#[tokio::test]
+async fn the_filter_applies_to_every_page() {
+    let transport = MockTransport::new(feed_with_ads);
+    assert_eq!(collected(&transport, SponsoredFilter::Include).await, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(collected(&transport, SponsoredFilter::Exclude).await, vec![2, 4, 5, 6]);
+    assert_eq!(collected(&transport, SponsoredFilter::Only).await, vec![1, 3]);
+    // Filtered pages are still fetched: 3 crawls of 2 pages.
+    assert_eq!(transport.call_count(), 6);
+}
+
+#[tokio::test]
+async fn feeds_without_ads() {
+    let transport = MockTransport::always(ok(json!({ "items": [media(7, json!({})), media(8, json!({}))] })));
+    assert_eq!(collected(&transport, SponsoredFilter::Exclude).await, vec![7, 8]);
+    assert!(collected(&transport, SponsoredFilter::Only).await.is_empty());
+}
+
+#[test]
+fn filters_deserialize_from_lowercase_names() {
+    let filters: Vec<SponsoredFilter> = serde_json::from_value(json!(["include", "exclude", "only"])).unwrap();
+    assert_eq!(filters, vec![SponsoredFilter::Include, SponsoredFilter::Exclude, SponsoredFilter::Only]);
+    assert_eq!(SponsoredFilter::default(), SponsoredFilter::Include);
+    assert!(serde_json::from_value::<SponsoredFilter>(json!("Exclude")).is_err());
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{envelope, instagram, ok, MockTransport};
+use serde_json::{json, Value};
+
+// Answers after `payload["id"]` milliseconds, with a 404 envelope for the id 404.
+fn slow(_: &str, payload: &Value) -> Result<Value, rocketapi::errors::RocketAPIError> {
+    let id = payload["id"].as_u64().unwrap();
+    std::thread::sleep(Duration::from_millis(id % 100));
+    Ok(if id == 404 { envelope(404, json!({ "message": "not found" })) } else { ok(json!({ "items": [] })) })
+}
+
+#[tokio::test]
+async fn calls_and_outcomes_are_counted_per_endpoint() {
+    let transport = MockTransport::new(slow);
+    let mut api = instagram(&transport);
+    api.get_media_info(&1).await.unwrap();
+    api.get_media_info(&2).await.unwrap();
+    api.get_media_info(&404).await.unwrap_err();
+    api.get_location_info(&1).await.unwrap();
+    let stats = api.stats();
+    assert_eq!(stats.len(), 2);
+    let media = stats["instagram/media/get_info"];
+    assert_eq!((media.calls, media.successes, media.errors), (3, 2, 1));
+    let location = stats["instagram/location/get_info"];
+    assert_eq!((location.calls, location.successes, location.errors), (1, 1, 0));
+}
+
+#[tokio::test]
+async fn min_avg_and_max_latency() {
+    let transport = MockTransport::new(slow);
+    let mut api = instagram(&transport);
+    for delay in [10, 30, 80] {
+        api.get_media_info(&delay).await.unwrap();
+    }
+    let media = api.stats()["instagram/media/get_info"];
+    assert!(media.min_latency >= Duration::from_millis(10) && media.min_latency < Duration::from_millis(30), "{:?}", media);
+    assert!(media.max_latency >= Duration::from_millis(80), "{:?}", media);
+    assert!(media.total_latency >= Duration::from_millis(120), "{:?}", media);
+    assert_eq!(media.avg_latency(), media.total_latency / 3);
+    assert!(media.min_latency <= media.avg_latency() && media.avg_latency() <= media.max_latency);
+}
+
+#[tokio::test]
+async fn reset_stats_starts_over_and_is_shared_by_clones() {
+    let transport = MockTransport::new(slow);
+    let mut api = instagram(&transport);
+    api.get_media_info(&50).await.unwrap();
+    let clone = api.clone();
+    clone.reset_stats();
+    assert!(api.stats().is_empty());
+    // The minimum starts over too instead of keeping the 50ms call.
+    api.get_media_info(&1).await.unwrap();
+    let media = clone.stats()["instagram/media/get_info"];
+    assert_eq!((media.calls, media.successes, media.errors), (1, 1, 0));
+    assert!(media.max_latency < Duration::from_millis(50), "{:?}", media);
+    assert_eq!(media.min_latency, media.max_latency);
+}
+
+#[test]
+fn concurrent_records_are_not_lost() {
+    let transport = MockTransport::always(ok(json!({})));
+    let api = instagram(&transport);
+    std::thread::scope(|scope| {
+        for thread in 0..8u64 {
+            let mut api = api.clone();
+            scope.spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+                for id in 0..50 {
+                    runtime.block_on(api.get_media_info(&(thread * 100 + id))).unwrap();
+                    runtime.block_on(api.get_location_info(&id)).unwrap();
+                }
+            });
+        }
+    });
+    let stats = api.stats();
+    assert_eq!(stats["instagram/media/get_info"].calls, 400);
+    assert_eq!(stats["instagram/location/get_info"].successes, 400);
+}
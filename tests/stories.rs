@@ -0,0 +1,88 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use serde_json::{json, Value};
+
+fn story(pk: u64) -> Value {
+    json!({ "id": format!("{}_1", pk), "pk": pk, "taken_at": 1700000000 })
+}
+
+#[tokio::test]
+async fn highlight_reels_sent_in_both_shapes_are_read_once() {
+    let transport = MockTransport::new(|method, _| {
+        Ok(match method {
+            "instagram/user/get_highlights" => ok(json!({ "tray": [{ "id": "highlight:1" }, { "id": "highlight:2" }] })),
+            _ => {
+                let first = json!({ "id": "highlight:1", "items": [story(10), story(11)] });
+                let second = json!({ "id": "highlight:2", "items": [story(20)] });
+                ok(json!({
+                    "reels": { "highlight:1": first, "highlight:2": second },
+                    "reels_media": [first, second],
+                }))
+            }
+        })
+    });
+    let mut api = instagram(&transport);
+    let stories = api.get_all_user_highlight_stories(&1).await.unwrap();
+    let pks: Vec<u64> = stories.iter().map(|story| story.pk).collect();
+    assert_eq!(pks, vec![10, 11, 20]);
+}
+
+#[tokio::test]
+async fn highlight_reels_fall_back_to_the_list() {
+    let transport = MockTransport::new(|method, _| {
+        Ok(match method {
+            "instagram/user/get_highlights" => ok(json!({ "tray": [{ "id": "highlight:1" }] })),
+            _ => ok(json!({ "reels_media": [{ "id": "highlight:1", "items": [story(10)] }] })),
+        })
+    });
+    let mut api = instagram(&transport);
+    let stories = api.get_all_user_highlight_stories(&1).await.unwrap();
+    assert_eq!(stories.iter().map(|story| story.pk).collect::<Vec<_>>(), vec![10]);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn user_reels_sent_in_both_shapes_are_read_once() {
+    use futures::StreamExt;
+
+    let transport = MockTransport::new(|_, _| {
+        let first = json!({ "user": { "pk": 1 }, "items": [story(10), story(11)] });
+        let second = json!({ "user": { "pk": 2 }, "items": [story(20)] });
+        Ok(ok(json!({
+            "reels": { "1": first, "2": second },
+            "reels_media": [first, second, { "user": { "pk": 3 }, "items": [story(30)] }],
+        })))
+    });
+    let mut api = instagram(&transport);
+    let mut stories: Vec<(u64, u64)> = api
+        .stream_users_stories(&[1, 2, 3], 1, std::time::Duration::ZERO)
+        .map(|result| result.map(|(user_id, story)| (user_id, story.pk)).unwrap())
+        .collect()
+        .await;
+    stories.sort();
+    assert_eq!(stories, vec![(1, 10), (1, 11), (2, 20), (3, 30)]);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn a_malformed_story_is_yielded_as_an_error() {
+    use futures::StreamExt;
+    use rocketapi::errors::RocketAPIError;
+
+    let transport = MockTransport::always(ok(json!({
+        "reels_media": [
+            { "user": { "pk": 1 }, "items": [story(10), { "id": "11_1", "pk": "not a number" }, story(12)] },
+            { "user": { "pk": 2 }, "items": [story(20)] },
+        ],
+    })));
+    let mut api = instagram(&transport);
+    let results: Vec<_> = api.stream_users_stories(&[1, 2], 1, std::time::Duration::ZERO).collect().await;
+    let mut stories: Vec<(u64, u64)> = results.iter().filter_map(|result| result.as_ref().ok()).map(|(user_id, story)| (*user_id, story.pk)).collect();
+    stories.sort();
+    assert_eq!(stories, vec![(1, 10), (1, 12), (2, 20)]);
+    let errors: Vec<_> = results.iter().filter_map(|result| result.as_ref().err()).collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].ids, vec![1]);
+    assert!(matches!(errors[0].error, RocketAPIError::ParseError(_)), "{}", errors[0]);
+}
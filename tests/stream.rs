@@ -0,0 +1,50 @@
+#![cfg(feature = "stream")]
+
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use futures::TryStreamExt;
+use rocketapi::models::MediaType;
+use serde_json::{json, Value};
+
+fn media(pk: u64, media_type: u64) -> Value {
+    json!({ "pk": pk, "media_type": media_type, "taken_at": 1700000000 - pk })
+}
+
+fn pages() -> Vec<Value> {
+    vec![
+        ok(json!({ "items": [media(1, 1), media(2, 2), media(3, 8)], "next_max_id": "a", "more_available": true })),
+        ok(json!({ "items": [media(4, 2), media(5, 1), media(6, 2)], "next_max_id": "b", "more_available": true })),
+        ok(json!({ "items": [media(7, 1)], "next_max_id": null, "more_available": false })),
+    ]
+}
+
+fn pks(items: &[Value]) -> Vec<u64> {
+    items.iter().map(|item| item["pk"].as_u64().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn take_until_makes_no_request_after_the_match() {
+    let transport = MockTransport::new({
+        let pages = pages();
+        move |_, payload| Ok(match payload["max_id"].as_str() {
+            None => pages[0].clone(),
+            Some("a") => pages[1].clone(),
+            _ => pages[2].clone(),
+        })
+    });
+    let mut api = instagram(&transport);
+    // The match is in the middle of the second page.
+    let items: Vec<Value> = api.stream_user_media(&1, 3).take_until(|item| item["pk"] == 5).try_collect().await.unwrap();
+    assert_eq!(pks(&items), vec![1, 2, 3, 4]);
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn filter_media_type_keeps_fetching_the_following_pages() {
+    let transport = MockTransport::scripted(pages().into_iter().map(Ok).collect());
+    let mut api = instagram(&transport);
+    let videos: Vec<Value> = api.stream_user_media(&1, 3).filter_media_type(MediaType::Video).try_collect().await.unwrap();
+    assert_eq!(pks(&videos), vec![2, 4, 6]);
+    assert_eq!(transport.call_count(), 3);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{ok, HttpResponse, MockServer, TIMEOUT};
+use rocketapi::RocketAPI;
+use serde_json::{json, Value};
+
+fn followers_page(users: u64) -> Value {
+    let users: Vec<Value> = (0..users)
+        .map(|pk| {
+            json!({
+                "pk": pk,
+                "pk_id": pk.to_string(),
+                "username": format!("user_{}", pk),
+                "full_name": "N\u{e4}me \u{1F600} \"quoted\" \\ \n",
+                "is_private": pk % 2 == 0,
+                "profile_pic_url": format!("https://scontent.cdninstagram.com/v/{}.jpg?stp=dst-jpg_s150x150&_nc_ht=x", pk),
+                "friendship_status": { "following": false, "outgoing_request": null },
+            })
+        })
+        .collect();
+    ok(json!({ "users": users, "next_max_id": "QVFE", "big_count": u64::MAX, "ratio": 0.1 }))
+}
+
+fn client(server: &MockServer, streaming: bool) -> RocketAPI {
+    let mut api = RocketAPI::new("test-token".to_string(), TIMEOUT);
+    api.set_base_url(&server.url()).unwrap();
+    api.set_streaming_parse(streaming);
+    api
+}
+
+#[tokio::test]
+async fn streaming_and_buffered_parsing_are_equivalent() {
+    let fixtures = vec![
+        ok(json!({})),
+        ok(json!({ "nested": [[[[{ "a": [1, 2.5, -3, null, true] }]]]] })),
+        followers_page(5),
+        // Larger than the reqwest chunks, so the streaming parser gets the body in many pieces.
+        followers_page(5000),
+    ];
+    for (index, fixture) in fixtures.into_iter().enumerate() {
+        let body = fixture.clone();
+        let server = MockServer::start(move |_| HttpResponse::json(200, &body));
+        let buffered = client(&server, false).request("instagram/user/get_followers", json!({ "id": 1 })).await.unwrap();
+        let streamed = client(&server, true).request("instagram/user/get_followers", json!({ "id": 1 })).await.unwrap();
+        assert_eq!(buffered, fixture, "fixture {}", index);
+        assert_eq!(streamed, buffered, "fixture {}", index);
+    }
+}
+
+// cargo test --release --test streaming_parse -- --ignored --nocapture
+#[tokio::test]
+#[ignore]
+async fn bench_streaming_against_buffered_parsing() {
+    const RUNS: u32 = 20;
+    let page = followers_page(20_000);
+    let size = page.to_string().len();
+    let server = MockServer::start(move |_| HttpResponse::json(200, &page));
+    for streaming in [false, true] {
+        let api = client(&server, streaming);
+        let mut total = Duration::ZERO;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            api.request("instagram/user/get_followers", json!({ "id": 1 })).await.unwrap();
+            total += start.elapsed();
+        }
+        println!("{} body, streaming {}: {:?} per request", size, streaming, total / RUNS);
+    }
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::{ok, threads, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::models::ThreadNode;
+use serde_json::{json, Value};
+
+fn post(pk: u64, extra: Value) -> Value {
+    let mut post = json!({ "pk": pk.to_string(), "code": format!("C{}", pk), "caption": { "text": format!("post {}", pk) } });
+    if let (Some(post), Some(extra)) = (post.as_object_mut(), extra.as_object()) {
+        post.extend(extra.clone());
+    }
+    post
+}
+
+fn reply_thread(posts: Vec<Value>) -> Value {
+    json!({ "thread_items": posts.into_iter().map(|post| json!({ "post": post })).collect::<Vec<_>>() })
+}
+
+// Thread 100: 201 -> 202 -> 203 -> 206 (three levels on the first page, a fourth on the second), 204 answers
+// the root, 205 answers the deleted post 999.
+fn conversation(method: &str, payload: &Value) -> Result<Value, RocketAPIError> {
+    Ok(match (method, payload["max_id"].as_str()) {
+        ("threads/thread/get_info", _) => ok(json!({ "containing_thread": { "thread_items": [{ "post": post(100, json!({})) }] } })),
+        ("threads/thread/get_replies", None) => ok(json!({
+            "reply_threads": [
+                reply_thread(vec![post(201, json!({})), post(202, json!({})), post(203, json!({}))]),
+                reply_thread(vec![post(204, json!({}))]),
+            ],
+            "paging_tokens": { "downwards": "p2" },
+        })),
+        ("threads/thread/get_replies", Some("p2")) => ok(json!({
+            "reply_threads": [
+                reply_thread(vec![post(205, json!({ "text_post_app_info": { "reply_to_id": "999_1" } }))]),
+                reply_thread(vec![post(206, json!({ "reply_to": 203 }))]),
+            ],
+            "paging_tokens": { "downwards": null },
+        })),
+        (method, cursor) => panic!("unexpected call to {} with {:?}", method, cursor),
+    })
+}
+
+fn shape(node: &ThreadNode) -> Value {
+    // `[pk, [replies...]]`, with unavailable nodes as negative pks.
+    let pk = if node.is_unavailable() { -(node.pk as i64) } else { node.pk as i64 };
+    json!([pk, node.replies.iter().map(shape).collect::<Vec<_>>()])
+}
+
+#[tokio::test]
+async fn replies_are_nested_under_the_root_from_thread_info() {
+    let transport = MockTransport::new(conversation);
+    let mut api = threads(&transport);
+    let conversation = api.get_thread_conversation(&100, 50).await.unwrap();
+    assert_eq!(
+        shape(&conversation.root),
+        json!([100, [[201, [[202, [[203, [[206, []]]]]]]], [204, []], [-999, [[205, []]]]]])
+    );
+    assert_eq!(conversation.root.post.as_ref().unwrap().caption.as_ref().unwrap().text, "post 100");
+    assert_eq!(conversation.root.len(), 7);
+    assert_eq!(conversation.missing_parents, vec![999]);
+    assert!(conversation.detached.is_empty());
+    let calls: Vec<(String, Value)> = transport.calls();
+    assert_eq!(
+        calls,
+        vec![
+            ("threads/thread/get_info".to_string(), json!({ "id": 100 })),
+            ("threads/thread/get_replies".to_string(), json!({ "id": 100 })),
+            ("threads/thread/get_replies".to_string(), json!({ "id": 100, "max_id": "p2" })),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn max_items_stops_the_pagination() {
+    let transport = MockTransport::new(conversation);
+    let mut api = threads(&transport);
+    let conversation = api.get_thread_conversation(&100, 2).await.unwrap();
+    assert_eq!(shape(&conversation.root), json!([100, [[201, [[202, []]]]]]));
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn replies_answering_each_other_are_detached() {
+    let transport = MockTransport::new(|method, _| {
+        Ok(match method {
+            "threads/thread/get_info" => ok(json!({ "thread_items": [{ "post": post(100, json!({})) }] })),
+            _ => ok(json!({
+                "reply_threads": [
+                    reply_thread(vec![post(302, json!({ "reply_to": 301 }))]),
+                    reply_thread(vec![post(301, json!({ "reply_to": 302 }))]),
+                    reply_thread(vec![post(303, json!({}))]),
+                ],
+            })),
+        })
+    });
+    let conversation = threads(&transport).get_thread_conversation(&100, 50).await.unwrap();
+    assert_eq!(shape(&conversation.root), json!([100, [[303, []]]]));
+    assert!(conversation.missing_parents.is_empty());
+    assert_eq!(conversation.detached.iter().map(|post| post.pk).collect::<Vec<_>>(), vec![301, 302]);
+}
+
+#[tokio::test]
+async fn a_thread_without_post_is_not_found() {
+    let transport = MockTransport::always(ok(json!({ "containing_thread": { "thread_items": [] } })));
+    let result = threads(&transport).get_thread_conversation(&100, 50).await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+    // No reply page is requested without a root.
+    assert_eq!(transport.call_count(), 1);
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "stream")]
+
+mod common;
+
+use common::{envelope, ok, threads, MockTransport};
+use futures::TryStreamExt;
+use rocketapi::errors::RocketAPIError;
+use serde_json::{json, Value};
+
+fn users(pks: &[u64]) -> Vec<Value> {
+    pks.iter().map(|pk| json!({ "pk": pk, "username": format!("user{}", pk) })).collect()
+}
+
+fn pks(items: &[Value]) -> Vec<u64> {
+    items.iter().map(|item| item["pk"].as_u64().unwrap()).collect()
+}
+
+// Three search pages. The second one returns another rank token, which must not replace the first one; the
+// last one still has a page token but `has_more: false`.
+fn search_pages(_: &str, payload: &Value) -> Result<Value, RocketAPIError> {
+    Ok(match payload["page_token"].as_str() {
+        None => ok(json!({ "users": users(&[1, 2]), "rank_token": "r1", "page_token": "p2", "has_more": true })),
+        Some("p2") => ok(json!({ "users": users(&[3]), "rank_token": "r2", "page_token": "p3", "has_more": true })),
+        Some("p3") => ok(json!({ "users": users(&[4, 5]), "page_token": "p4", "has_more": false })),
+        Some(token) => panic!("unexpected page token {}", token),
+    })
+}
+
+#[tokio::test]
+async fn stream_search_users_hands_the_tokens_to_the_next_page() {
+    let transport = MockTransport::new(search_pages);
+    let mut api = threads(&transport);
+    let found: Vec<Value> = api.stream_search_users("nat").try_collect().await.unwrap();
+    assert_eq!(pks(&found), vec![1, 2, 3, 4, 5]);
+    assert_eq!(
+        transport.payloads(),
+        vec![
+            json!({ "query": "nat" }),
+            json!({ "query": "nat", "rank_token": "r1", "page_token": "p2" }),
+            json!({ "query": "nat", "rank_token": "r1", "page_token": "p3" }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stream_search_users_stops_without_a_page_token() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "users": users(&[1]), "page_token": "p2" }))),
+        Ok(ok(json!({ "users": users(&[2]), "page_token": "", "has_more": true }))),
+    ]);
+    let mut api = threads(&transport);
+    let found: Vec<Value> = api.stream_search_users("nat").try_collect().await.unwrap();
+    assert_eq!(pks(&found), vec![1, 2]);
+    // Without a rank token on the first page, only the page token is sent.
+    assert_eq!(transport.payloads()[1], json!({ "query": "nat", "page_token": "p2" }));
+}
+
+#[tokio::test]
+async fn stream_thread_likes_follows_next_max_id() {
+    let transport = MockTransport::new(|_, payload| {
+        Ok(match payload["max_id"].as_str() {
+            None => ok(json!({ "users": users(&[1, 2]), "next_max_id": "m2" })),
+            Some("m2") => ok(json!({ "users": users(&[3]), "next_max_id": "m3" })),
+            Some("m3") => ok(json!({ "users": users(&[4]), "next_max_id": null })),
+            Some(cursor) => panic!("unexpected cursor {}", cursor),
+        })
+    });
+    let mut api = threads(&transport);
+    let likers: Vec<Value> = api.stream_thread_likes(&100).try_collect().await.unwrap();
+    assert_eq!(pks(&likers), vec![1, 2, 3, 4]);
+    assert_eq!(
+        transport.payloads(),
+        vec![json!({ "id": 100 }), json!({ "id": 100, "max_id": "m2" }), json!({ "id": 100, "max_id": "m3" })]
+    );
+}
+
+#[tokio::test]
+async fn stream_thread_likes_returns_the_error_of_a_later_page() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "users": users(&[1]), "next_max_id": "m2" }))),
+        Ok(envelope(404, json!({ "message": "Thread not found" }))),
+    ]);
+    let mut api = threads(&transport);
+    let result: Result<Vec<Value>, RocketAPIError> = api.stream_thread_likes(&100).try_collect().await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+    assert_eq!(transport.call_count(), 2);
+}
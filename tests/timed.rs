@@ -0,0 +1,50 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::retry::RetryPolicy;
+use serde_json::json;
+
+#[tokio::test]
+async fn latency_is_returned_with_the_body() {
+    let transport = MockTransport::with_delay(Duration::from_millis(40), |_, _| Ok(ok(json!({ "data": { "user": { "username": "natgeo" } } }))));
+    let mut api = instagram(&transport);
+    let (body, latency) = api.get_user_info_timed("@NatGeo").await.unwrap();
+    assert_eq!(body["data"]["user"]["username"], "natgeo");
+    assert!(latency >= Duration::from_millis(40) && latency < Duration::from_secs(2), "{:?}", latency);
+    // Same bookkeeping as `get_user_info`.
+    assert_eq!(transport.calls(), vec![("instagram/user/get_info".to_string(), json!({ "username": "natgeo" }))]);
+    assert_eq!(api.requests_made(), 1);
+    assert_eq!(api.last_response["response"]["status_code"], 200);
+    assert_eq!(api.stats()["instagram/user/get_info"].calls, 1);
+}
+
+#[tokio::test]
+async fn retries_and_backoff_are_part_of_the_latency() {
+    let attempts = AtomicUsize::new(0);
+    let transport = MockTransport::new(move |_, _| match attempts.fetch_add(1, Ordering::SeqCst) {
+        0 => Err(RocketAPIError::NonJsonResponse { http_status: 502, body: "Bad Gateway".to_string() }),
+        _ => Ok(ok(json!({ "data": { "user": {} } }))),
+    });
+    let mut api = instagram(&transport);
+    api.api.set_retry_policy(RetryPolicy::new(2, Duration::from_millis(60), Duration::from_millis(60), Duration::from_secs(1)).unwrap()).unwrap();
+    let (_, latency) = api.get_user_info_timed("natgeo").await.unwrap();
+    assert!(latency >= Duration::from_millis(60), "{:?}", latency);
+    assert_eq!(api.last_retries, 1);
+}
+
+#[tokio::test]
+async fn errors_are_returned_without_latency() {
+    let transport = MockTransport::always(envelope(404, json!({ "message": "User not found" })));
+    let mut api = instagram(&transport);
+    let result = api.get_user_info_timed("deleted.account").await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+    assert_eq!(api.stats()["instagram/user/get_info"].errors, 1);
+    // Invalid usernames fail before any request.
+    let result = api.get_user_info_timed("not a username").await;
+    assert!(matches!(result, Err(RocketAPIError::InvalidParameter(_))), "{:?}", result);
+    assert_eq!(transport.call_count(), 1);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{ok, HttpResponse, MockServer, TIMEOUT};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::instagramapi::InstagramAPI;
+use rocketapi::RocketAPI;
+use serde_json::json;
+
+#[tokio::test]
+async fn a_short_override_times_out_against_a_slow_server() {
+    let server = MockServer::start(|_| HttpResponse::json(200, &ok(json!({}))).delayed(Duration::from_millis(500)));
+    let mut api = RocketAPI::new("test-token".to_string(), TIMEOUT);
+    api.set_base_url(&server.url()).unwrap();
+
+    let start = Instant::now();
+    let result = api.request_with_timeout("instagram/user/get_info", json!({ "username": "natgeo" }), Duration::from_millis(100)).await;
+    match result {
+        Err(RocketAPIError::RequestError(err)) => assert!(err.is_timeout(), "{:?}", err),
+        other => panic!("{:?}", other),
+    }
+    assert!(start.elapsed() < Duration::from_millis(450));
+    // The client timeout is back once the call is done.
+    assert_eq!(api.timeout(), TIMEOUT);
+    api.request("instagram/user/get_info", json!({ "username": "natgeo" })).await.unwrap();
+}
+
+fn is_timeout<T: std::fmt::Debug>(result: &Result<T, RocketAPIError>) -> bool {
+    matches!(result, Err(RocketAPIError::RequestError(err)) if err.is_timeout())
+}
+
+#[tokio::test]
+async fn a_slow_endpoint_gets_a_longer_timeout() {
+    // Followers take 300ms, every other endpoint answers right away.
+    let server = MockServer::keep_alive(|request| {
+        let response = HttpResponse::json(200, &ok(json!({ "users": [], "user": { "pk": 1 } })));
+        if request.path == "/instagram/user/get_followers" { response.delayed(Duration::from_millis(300)) } else { response }
+    });
+    let mut api = InstagramAPI::new("test-token".to_string(), Duration::from_millis(100));
+    api.api.set_base_url(&server.url()).unwrap();
+
+    let result = api.get_user_followers(&1, None, None).await;
+    assert!(is_timeout(&result), "{:?}", result);
+    api.get_user_info("natgeo").await.unwrap();
+
+    // The override only applies to the calls made through the scope.
+    api.with_timeout(Duration::from_secs(2)).get_user_followers(&1, None, None).await.unwrap();
+    assert_eq!(api.api.timeout(), Duration::from_millis(100));
+    let result = api.get_user_followers(&1, None, None).await;
+    assert!(is_timeout(&result), "{:?}", result);
+
+    // Several calls may go through one scope; a short override still leaves room for the fast endpoint.
+    let mut scope = api.with_timeout(Duration::from_millis(50));
+    scope.get_user_info("natgeo").await.unwrap();
+    let result = scope.get_user_followers(&1, None, None).await;
+    assert!(is_timeout(&result), "{:?}", result);
+    drop(scope);
+
+    // `set_timeout` changes the client default for the following calls.
+    api.api.set_timeout(Duration::from_secs(2));
+    api.get_user_followers(&1, None, None).await.unwrap();
+    assert_eq!(api.api.timeout(), Duration::from_secs(2));
+}
@@ -0,0 +1,159 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use common::{envelope, instagram, ok, MockTransport};
+use serde_json::json;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+type Fields = HashMap<String, String>;
+
+#[derive(Debug, Clone)]
+struct RecordedEvent {
+    level: Level,
+    fields: Fields,
+    // Name and fields of the span the event was logged in.
+    span: Option<(String, Fields)>,
+}
+
+#[derive(Default)]
+struct Recorded {
+    spans: HashMap<u64, (String, Fields)>,
+    stack: Vec<u64>,
+    events: Vec<RecordedEvent>,
+}
+
+// Keeps the spans (with the fields recorded later on) and the events, in place of a test subscriber crate.
+#[derive(Clone, Default)]
+struct Recorder {
+    next_id: Arc<AtomicU64>,
+    recorded: Arc<Mutex<Recorded>>,
+}
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+impl Recorder {
+    fn spans(&self, name: &str) -> Vec<Fields> {
+        let recorded = self.recorded.lock().unwrap();
+        let mut spans: Vec<(&u64, &(String, Fields))> = recorded.spans.iter().filter(|(_, (span, _))| span == name).collect();
+        spans.sort_by_key(|(id, _)| **id);
+        spans.into_iter().map(|(_, (_, fields))| fields.clone()).collect()
+    }
+
+    fn events(&self) -> Vec<RecordedEvent> {
+        self.recorded.lock().unwrap().events.clone()
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut fields = Fields::new();
+        attributes.record(&mut FieldVisitor(&mut fields));
+        self.recorded.lock().unwrap().spans.insert(id, (attributes.metadata().name().to_string(), fields));
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some((_, fields)) = self.recorded.lock().unwrap().spans.get_mut(&span.into_u64()) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let mut recorded = self.recorded.lock().unwrap();
+        let span = recorded.stack.last().and_then(|id| recorded.spans.get(id)).cloned();
+        recorded.events.push(RecordedEvent { level: *event.metadata().level(), fields, span });
+    }
+
+    fn enter(&self, span: &Id) {
+        self.recorded.lock().unwrap().stack.push(span.into_u64());
+    }
+
+    fn exit(&self, span: &Id) {
+        let mut recorded = self.recorded.lock().unwrap();
+        if let Some(position) = recorded.stack.iter().rposition(|id| *id == span.into_u64()) {
+            recorded.stack.remove(position);
+        }
+    }
+}
+
+#[tokio::test]
+async fn calls_open_a_span_with_the_method_counter_and_status_code() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let transport = MockTransport::always(ok(json!({ "user": { "pk": 1 } })));
+    let mut api = instagram(&transport);
+    api.get_user_info("natgeo").await.unwrap();
+    api.get_media_info(&7).await.unwrap();
+
+    let spans = recorder.spans("rocketapi_request");
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[0]["method"], "instagram/user/get_info");
+    assert_eq!(spans[0]["counter"], "0");
+    assert_eq!(spans[0]["status_code"], "200");
+    assert!(spans[0].contains_key("elapsed_ms"));
+    assert_eq!(spans[1]["method"], "instagram/media/get_info");
+    assert_eq!(spans[1]["counter"], "1");
+    assert!(recorder.events().iter().all(|event| event.level != Level::WARN));
+}
+
+#[tokio::test]
+async fn errors_are_logged_at_warn_in_the_request_span() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let transport = MockTransport::always(envelope(404, json!({ "message": "Media not found" })));
+    let mut api = instagram(&transport);
+    api.get_media_info(&7).await.unwrap_err();
+
+    let warnings: Vec<RecordedEvent> = recorder.events().into_iter().filter(|event| event.level == Level::WARN).collect();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert_eq!(warnings[0].fields["error"], "NotFound");
+    assert!(warnings[0].fields["message"].starts_with("NotFound"), "{:?}", warnings[0]);
+    let (span, fields) = warnings[0].span.clone().unwrap();
+    assert_eq!(span, "rocketapi_request");
+    assert_eq!(fields["method"], "instagram/media/get_info");
+    assert_eq!(fields["status_code"], "404");
+}
+
+#[tokio::test]
+async fn schema_changes_are_logged_at_warn() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let transport = MockTransport::always(ok(json!({ "user": { "pk": 1 }, "status": "ok" })));
+    let mut api = instagram(&transport);
+    let baseline = rocketapi::helpers::schema_fingerprint(&json!({ "user": {} }));
+    api.api.set_schema_baselines(HashMap::from([("instagram/user/get_info".to_string(), baseline)]));
+    api.get_user_info("natgeo").await.unwrap();
+
+    let warnings: Vec<RecordedEvent> = recorder.events().into_iter().filter(|event| event.level == Level::WARN).collect();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert_eq!(warnings[0].fields["method"], "instagram/user/get_info");
+    assert_eq!(warnings[0].fields["expected"], baseline.to_string());
+    assert_ne!(warnings[0].fields["actual"], baseline.to_string());
+}
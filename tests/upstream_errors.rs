@@ -0,0 +1,83 @@
+mod common;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use serde_json::{json, Value};
+
+async fn error_of(response: Value) -> RocketAPIError {
+    let transport = MockTransport::always(response);
+    let mut api = instagram(&transport);
+    api.api.set_upstream_errors(true);
+    api.get_user_info("natgeo").await.unwrap_err()
+}
+
+#[tokio::test]
+async fn challenge_and_login_codes_are_typed_errors() {
+    let challenge = envelope(400, json!({
+        "message": "challenge_required",
+        "challenge": { "url": "https://i.instagram.com/challenge/", "api_path": "/challenge/" },
+        "status": "fail",
+    }));
+    let checkpoint = ok(json!({ "message": "checkpoint_required", "checkpoint_url": "https://i.instagram.com/challenge/", "status": "fail" }));
+    let checkpoint_type = envelope(400, json!({ "message": "", "error_type": "checkpoint_challenge_required", "status": "fail" }));
+    let login = envelope(403, json!({ "message": "login_required", "logout_reason": 2, "status": "fail" }));
+    for (name, fixture) in [("challenge", &challenge), ("checkpoint", &checkpoint), ("checkpoint_type", &checkpoint_type)] {
+        match error_of(fixture.clone()).await {
+            RocketAPIError::ChallengeRequired(response) => assert_eq!(&response, fixture, "{}", name),
+            other => panic!("{}: {:?}", name, other),
+        }
+    }
+    match error_of(login.clone()).await {
+        RocketAPIError::LoginRequired(response) => assert_eq!(response, login),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn upstream_codes_are_left_alone_by_default() {
+    let transport = MockTransport::scripted(vec![
+        Ok(envelope(400, json!({ "message": "challenge_required" }))),
+        Ok(ok(json!({ "message": "login_required", "user": null }))),
+    ]);
+    let mut api = instagram(&transport);
+    let result = api.get_user_info("natgeo").await;
+    assert!(matches!(result, Err(RocketAPIError::BadResponse { status_code: 400, .. })), "{:?}", result);
+    assert_eq!(api.get_user_info("natgeo").await.unwrap()["message"], "login_required");
+}
+
+#[tokio::test]
+async fn other_messages_are_not_typed() {
+    let transport = MockTransport::always(ok(json!({ "message": "Please wait a few minutes", "user": { "pk": 1 } })));
+    let mut api = instagram(&transport);
+    api.api.set_upstream_errors(true);
+    assert_eq!(api.get_user_info("natgeo").await.unwrap()["user"]["pk"], 1);
+}
+
+#[tokio::test]
+async fn action_blocks_are_feedback_required() {
+    let fixtures = [
+        envelope(400, json!({
+            "message": "feedback_required",
+            "spam": true,
+            "feedback_title": "Try Again Later",
+            "feedback_message": "We restrict certain activity to protect our community.",
+            "status": "fail",
+        })),
+        envelope(400, json!({ "message": "", "error_type": "sentry_block", "status": "fail" })),
+        // No known message, only the block fields.
+        ok(json!({ "spam": true, "status": "fail" })),
+        ok(json!({ "feedback_title": "Action Blocked", "status": "fail" })),
+        envelope(400, json!({ "feedback_message": "This action was blocked.", "status": "fail" })),
+    ];
+    for fixture in fixtures {
+        match error_of(fixture.clone()).await {
+            RocketAPIError::FeedbackRequired(response) => assert_eq!(response, fixture),
+            other => panic!("{}: {:?}", fixture, other),
+        }
+    }
+    // `spam: false` isn't a block.
+    let transport = MockTransport::always(ok(json!({ "spam": false, "user": { "pk": 1 } })));
+    let mut api = instagram(&transport);
+    api.api.set_upstream_errors(true);
+    api.get_user_info("natgeo").await.unwrap();
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::{instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use rocketapi::models::UserInfo;
+use serde_json::{json, Value};
+
+fn fixture() -> Value {
+    serde_json::from_str(include_str!("fixtures/user_info.json")).unwrap()
+}
+
+#[tokio::test]
+async fn the_web_profile_sample_is_typed() {
+    let transport = MockTransport::always(ok(fixture()));
+    let mut api = instagram(&transport);
+    let user = api.get_user_info_typed("natgeo").await.unwrap();
+    assert_eq!(
+        user,
+        UserInfo {
+            pk: 787132,
+            username: "natgeo".to_string(),
+            full_name: Some("National Geographic".to_string()),
+            follower_count: Some(283496412),
+            following_count: Some(164),
+            is_private: Some(false),
+            is_verified: Some(true),
+            biography: Some("Experience the world through the eyes of National Geographic photographers.".to_string()),
+            profile_pic_url: Some("https://scontent.cdninstagram.com/v/t51.2885-19/s150x150/natgeo.jpg".to_string()),
+        }
+    );
+    assert_eq!(transport.payloads(), vec![json!({ "username": "natgeo" })]);
+}
+
+#[tokio::test]
+async fn the_mobile_user_shape_is_typed_too() {
+    let user = json!({ "pk": "787132", "id": "1", "username": "natgeo", "follower_count": 10, "following_count": 2 });
+    let transport = MockTransport::always(ok(json!({ "user": user, "status": "ok" })));
+    let mut api = instagram(&transport);
+    let user = api.get_user_info_typed("natgeo").await.unwrap();
+    // `pk` wins over `id`.
+    assert_eq!((user.pk, user.follower_count, user.following_count), (787132, Some(10), Some(2)));
+}
+
+#[tokio::test]
+async fn missing_optional_fields_are_none() {
+    let transport = MockTransport::always(ok(json!({ "data": { "user": { "id": "5", "username": "quiet" } } })));
+    let mut api = instagram(&transport);
+    let user = api.get_user_info_typed("quiet").await.unwrap();
+    assert_eq!((user.pk, user.username.as_str()), (5, "quiet"));
+    assert_eq!((user.full_name, user.follower_count, user.following_count), (None, None, None));
+    assert_eq!((user.is_private, user.is_verified, user.biography, user.profile_pic_url), (None, None, None, None));
+}
+
+#[tokio::test]
+async fn a_body_without_a_user_is_not_found() {
+    let transport = MockTransport::always(ok(json!({ "data": { "user": null }, "status": "ok" })));
+    let mut api = instagram(&transport);
+    let result = api.get_user_info_typed("nobody").await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+
+    let transport = MockTransport::always(ok(json!({ "data": { "user": { "id": "5" } } })));
+    let mut api = instagram(&transport);
+    let result = api.get_user_info_typed("quiet").await;
+    assert!(matches!(result, Err(RocketAPIError::ParseError(_))), "{:?}", result);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{envelope, instagram, ok, MockTransport};
+use rocketapi::errors::RocketAPIError;
+use serde_json::{json, Value};
+
+fn items(pks: &[u64]) -> Vec<Value> {
+    pks.iter().map(|pk| json!({ "id": format!("{}_1", pk), "pk": pk, "media_type": 1 })).collect()
+}
+
+fn pks(items: &[Value]) -> Vec<u64> {
+    items.iter().map(|item| item["pk"].as_u64().unwrap()).collect()
+}
+
+fn cursors(transport: &MockTransport) -> Vec<Value> {
+    transport.payloads().iter().map(|payload| payload["max_id"].clone()).collect()
+}
+
+// Three pages, the last one still carries a cursor but `more_available: false`.
+fn media_pages(_: &str, payload: &Value) -> Result<Value, RocketAPIError> {
+    Ok(match payload["max_id"].as_str() {
+        None => ok(json!({ "items": items(&[1, 2]), "next_max_id": "m2", "more_available": true })),
+        Some("m2") => ok(json!({ "items": items(&[3, 4]), "next_max_id": "m3", "more_available": true })),
+        Some("m3") => ok(json!({ "items": items(&[5]), "next_max_id": "m4", "more_available": false })),
+        Some(cursor) => panic!("unexpected cursor {}", cursor),
+    })
+}
+
+#[tokio::test]
+async fn every_page_is_fetched_with_the_maximum_count() {
+    let transport = MockTransport::new(media_pages);
+    let mut api = instagram(&transport);
+    let media = api.get_user_media_all(&1, None, None).await.unwrap();
+    assert_eq!(pks(&media), vec![1, 2, 3, 4, 5]);
+    assert_eq!(cursors(&transport), vec![Value::Null, json!("m2"), json!("m3")]);
+    assert!(transport.payloads().iter().all(|payload| payload["id"] == 1 && payload["count"] == 50));
+
+    let transport = MockTransport::new(media_pages);
+    let mut api = instagram(&transport);
+    api.get_user_media_all(&1, Some(12), None).await.unwrap();
+    assert!(transport.payloads().iter().all(|payload| payload["count"] == 12));
+}
+
+#[tokio::test]
+async fn max_pages_limits_the_requests() {
+    let transport = MockTransport::new(media_pages);
+    let mut api = instagram(&transport);
+    let media = api.get_user_media_all(&1, None, Some(2)).await.unwrap();
+    assert_eq!(pks(&media), vec![1, 2, 3, 4]);
+    assert_eq!(transport.call_count(), 2);
+    assert!(api.get_user_media_all(&1, None, Some(0)).await.unwrap().is_empty());
+    assert_eq!(transport.call_count(), 2);
+}
+
+#[tokio::test]
+async fn a_missing_or_repeated_cursor_ends_the_crawl() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "items": items(&[1]), "next_max_id": "m2" }))),
+        Ok(ok(json!({ "items": items(&[2]), "next_max_id": "m2", "more_available": true }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert_eq!(pks(&api.get_user_media_all(&1, None, None).await.unwrap()), vec![1, 2]);
+    assert_eq!(transport.call_count(), 2);
+
+    let transport = MockTransport::scripted(vec![Ok(ok(json!({ "items": items(&[1]), "more_available": true })))]);
+    let mut api = instagram(&transport);
+    assert_eq!(pks(&api.get_user_media_all(&1, None, None).await.unwrap()), vec![1]);
+}
+
+#[tokio::test]
+async fn the_error_of_a_later_page_is_returned() {
+    let transport = MockTransport::scripted(vec![
+        Ok(ok(json!({ "items": items(&[1, 2]), "next_max_id": "m2" }))),
+        Ok(envelope(404, json!({ "message": "User not found" }))),
+    ]);
+    let mut api = instagram(&transport);
+    let result = api.get_user_media_all(&1, None, None).await;
+    assert!(matches!(result, Err(RocketAPIError::NotFound(_))), "{:?}", result);
+    assert_eq!(transport.call_count(), 2);
+}
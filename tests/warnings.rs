@@ -0,0 +1,68 @@
+mod common;
+
+use common::{envelope, instagram, ok, threads, MockTransport};
+use rocketapi::helpers::response_warnings;
+use serde_json::{json, Value};
+
+fn with_fields(mut envelope: Value, fields: Value) -> Value {
+    if let (Some(envelope), Some(fields)) = (envelope.as_object_mut(), fields.as_object()) {
+        envelope.extend(fields.clone());
+    }
+    envelope
+}
+
+#[test]
+fn every_advisory_field_is_read_in_order() {
+    let response = with_fields(
+        ok(json!({})),
+        json!({
+            "deprecation": "instagram/user/get_info is deprecated",
+            "notice": ["cached response"],
+            "warnings": ["partial data", "media limit reached"],
+            "warning": "slow upstream",
+        }),
+    );
+    assert_eq!(
+        response_warnings(&response),
+        vec!["slow upstream", "partial data", "media limit reached", "cached response", "instagram/user/get_info is deprecated"]
+    );
+}
+
+#[test]
+fn empty_and_non_string_warnings_are_skipped() {
+    let response = with_fields(
+        ok(json!({})),
+        json!({ "warning": "", "warnings": ["kept", 3, null, { "text": "nested" }], "notice": true, "deprecation": null }),
+    );
+    assert_eq!(response_warnings(&response), vec!["kept"]);
+    // Fields of the body and the `message` of error envelopes are not warnings.
+    assert!(response_warnings(&ok(json!({ "warning": "in the body" }))).is_empty());
+    assert!(response_warnings(&json!({ "status": "error", "message": "Invalid token" })).is_empty());
+    assert!(response_warnings(&Value::Null).is_empty());
+}
+
+#[tokio::test]
+async fn last_warnings_follow_the_last_response() {
+    let transport = MockTransport::scripted(vec![
+        Ok(with_fields(ok(json!({ "user": { "pk": 1 } })), json!({ "warning": "partial data" }))),
+        Ok(ok(json!({ "items": [] }))),
+        Ok(with_fields(envelope(404, json!({ "message": "not found" })), json!({ "notice": ["retry later"] }))),
+    ]);
+    let mut api = instagram(&transport);
+    assert!(api.last_warnings().is_empty());
+    api.get_user_info("natgeo").await.unwrap();
+    assert_eq!(api.last_warnings(), vec!["partial data"]);
+    api.get_media_info(&1).await.unwrap();
+    assert!(api.last_warnings().is_empty());
+    // Warnings of an envelope that ends in an error are still kept.
+    api.get_media_info(&2).await.unwrap_err();
+    assert_eq!(api.last_warnings(), vec!["retry later"]);
+}
+
+#[tokio::test]
+async fn threads_last_warnings() {
+    let transport = MockTransport::always(with_fields(ok(json!({ "threads": [] })), json!({ "deprecation": ["use get_user_feed_v2"] })));
+    let mut api = threads(&transport);
+    api.get_user_feed(&1, None).await.unwrap();
+    assert_eq!(api.last_warnings(), vec!["use get_user_feed_v2"]);
+}